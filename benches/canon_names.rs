@@ -0,0 +1,41 @@
+//! Compares canonicalizing 100k synthetic paths one at a time, in a batch, and (with the
+//! `parallel` feature) in a parallel batch. Run with `cargo bench --features parallel`.
+use botw_utils::{get_canon_name, get_canon_names};
+use std::time::Instant;
+
+const ROOTS: &[&str] = &[
+    "content",
+    "aoc/0010",
+    "D:\\cemu\\graphicPacks\\BreathOfTheWild_Mod\\content",
+    "atmosphere/contents/01007EF00011E000/romfs",
+    "load/01007EF00011F001/My Mod/romfs",
+];
+
+fn main() {
+    let paths: Vec<String> = (0..100_000)
+        .map(|i| {
+            format!(
+                "{}/Actor/Pack/Synthetic_Actor_{:05}.sbactorpack",
+                ROOTS[i % ROOTS.len()],
+                i
+            )
+        })
+        .collect();
+
+    let start = Instant::now();
+    let single: Vec<_> = paths.iter().map(get_canon_name).collect();
+    println!("get_canon_name loop:  {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let batch = get_canon_names(&paths);
+    println!("get_canon_names:      {:?}", start.elapsed());
+    assert!(batch.iter().map(|(_, canon)| canon).eq(single.iter()));
+
+    #[cfg(feature = "parallel")]
+    {
+        let start = Instant::now();
+        let parallel = botw_utils::par_get_canon_names(&paths);
+        println!("par_get_canon_names:  {:?}", start.elapsed());
+        assert_eq!(parallel, batch);
+    }
+}
@@ -0,0 +1,84 @@
+//! Compares checking a few hundred yaz0 compressed files against the stock hashes with a fresh
+//! decompression buffer for each file and with one reused buffer, counting the bytes allocated
+//! along the way, and with the work split across threads when the `parallel` feature is on.
+//! Run with `cargo bench --bench file_hashes --features parallel`.
+use botw_utils::hashes::{Platform, StockHashTable};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATED.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const CANON: &str = "Actor/Physics/FldObj_MountainSheikerWall_A_06.bphysics";
+
+fn main() {
+    let files: Vec<Vec<u8>> = (0..300u32)
+        .map(|i| {
+            let data: Vec<u8> = (0..256 * 1024u32)
+                .map(|j| ((j / 64) ^ i).to_le_bytes()[0])
+                .collect();
+            roead::yaz0::compress(data)
+        })
+        .collect();
+    let table = StockHashTable::new(Platform::Switch);
+
+    let allocated = ALLOCATED.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let fresh: Vec<_> = files
+        .iter()
+        .map(|data| table.check_file(CANON, data))
+        .collect();
+    println!(
+        "check_file:          {:?}, {} MiB allocated",
+        start.elapsed(),
+        (ALLOCATED.load(Ordering::Relaxed) - allocated) >> 20
+    );
+
+    let allocated = ALLOCATED.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let mut buf = Vec::new();
+    let reused: Vec<_> = files
+        .iter()
+        .map(|data| table.check_file_with_buf(CANON, data, &mut buf))
+        .collect();
+    println!(
+        "check_file_with_buf: {:?}, {} MiB allocated",
+        start.elapsed(),
+        (ALLOCATED.load(Ordering::Relaxed) - allocated) >> 20
+    );
+    assert_eq!(fresh, reused);
+
+    #[cfg(feature = "parallel")]
+    {
+        let named: Vec<(&str, &Vec<u8>)> = files.iter().map(|data| (CANON, data)).collect();
+        let start = Instant::now();
+        let parallel = table.par_check_files(&named);
+        println!(
+            "par_check_files:     {:?} on {} threads",
+            start.elapsed(),
+            std::thread::available_parallelism().map_or(1, |n| n.get())
+        );
+        assert_eq!(parallel, reused);
+    }
+}
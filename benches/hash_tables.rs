@@ -0,0 +1,48 @@
+//! Compares how long the stock hash tables take to load cold, the way they used to be loaded by
+//! inflating and parsing the JSON in `data/`, against unpacking the binary tables the build
+//! script embeds. Each table can only be unpacked cold once per process, so each is timed once.
+//! Run with `cargo bench --bench hash_tables`.
+use botw_utils::hashes::{get_hash_table, HashTable, Platform, StockHashTable};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::time::Instant;
+
+fn main() {
+    for &(platform, json) in &[
+        (Platform::WiiU, "data/wiiu_hashes.json"),
+        (Platform::Switch, "data/switch_hashes.json"),
+    ] {
+        let mut encoder = libflate::deflate::Encoder::new(Vec::new());
+        encoder
+            .write_all(&std::fs::read(json).expect("run from the crate root"))
+            .unwrap();
+        let deflated = encoder.finish().into_result().unwrap();
+
+        let start = Instant::now();
+        let mut text = String::new();
+        libflate::deflate::Decoder::new(deflated.as_slice())
+            .read_to_string(&mut text)
+            .unwrap();
+        let parsed: HashMap<String, Vec<u64>> = serde_json::from_str(&text).unwrap();
+        println!("{:?} JSON table: {:?}", platform, start.elapsed());
+
+        let start = Instant::now();
+        let table = StockHashTable::new(platform);
+        println!("{:?} binary table: {:?}", platform, start.elapsed());
+
+        let start = Instant::now();
+        let map: HashTable = get_hash_table(platform);
+        println!(
+            "{:?} HashTable from binary: {:?}",
+            platform,
+            start.elapsed()
+        );
+
+        assert_eq!(map.len(), parsed.len());
+        assert!(parsed.into_iter().all(|(canon, mut hashes)| {
+            hashes.sort_unstable();
+            hashes.dedup();
+            table.get_hashes(&canon) == Some(hashes.as_slice())
+        }));
+    }
+}
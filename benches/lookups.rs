@@ -0,0 +1,54 @@
+//! Compares making a table and looking up ten files in it with a `LazyHashTable`, a
+//! `StockHashTable`, and the `HashMap` from `get_hash_table`. The embedded table is unpacked the
+//! first time any of them is made, which is timed on its own, so the rest compare only what
+//! each costs after that. Run with `cargo bench --bench lookups`.
+use botw_utils::hashes::{get_hash_table, HashLookup, LazyHashTable, Platform, StockHashTable};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const FILES: [&str; 10] = [
+    "Actor/ActorInfo.product.byml",
+    "Actor/Pack/Enemy_Lizalfos_Senior.bactorpack",
+    "Actor/Physics/FldObj_MountainSheikerWall_A_06.bphysics",
+    "Event/EventInfo.product.byml",
+    "GameData/gamedata.sarc",
+    "Map/MainField/A-1/A-1_Dynamic.mubin",
+    "Pack/Bootup.pack",
+    "Pack/TitleBG.pack",
+    "System/Resource/ResourceSizeTable.product.rsizetable",
+    "Hellow/Sweetie.tardis",
+];
+
+/// Times a closure over a number of runs, giving the average time of a run
+fn average(runs: u32, mut run: impl FnMut() -> usize) -> Duration {
+    let start = Instant::now();
+    for _ in 0..runs {
+        black_box(run());
+    }
+    start.elapsed() / runs
+}
+
+fn main() {
+    let start = Instant::now();
+    black_box(LazyHashTable::new(Platform::Switch));
+    println!("Unpacking the table:       {:?}", start.elapsed());
+
+    let lazy = average(100_000, || {
+        let table = LazyHashTable::new(Platform::Switch);
+        FILES.iter().filter(|canon| table.contains(canon)).count()
+    });
+    println!("LazyHashTable + 10 files:  {:?}", lazy);
+    let stock = average(100_000, || {
+        let table = StockHashTable::new(Platform::Switch);
+        FILES.iter().filter(|canon| table.contains(canon)).count()
+    });
+    println!("StockHashTable + 10 files: {:?}", stock);
+    let map = average(5, || {
+        let table = get_hash_table(Platform::Switch);
+        FILES
+            .iter()
+            .filter(|canon| table.contains_key(*canon))
+            .count()
+    });
+    println!("HashMap + 10 files:        {:?}", map);
+}
@@ -0,0 +1,101 @@
+//! Packs the stock hash tables in `data/` into the binary format read by `src/hashes.rs`, so
+//! the JSON does not have to be parsed at runtime. Each table is written to `OUT_DIR` deflate
+//! compressed, as a header of three little-endian u32s (the number of files, the length of the
+//! name data, and the number of hashes), then the end offset of each file's name and each
+//! file's hashes as u32s, then the names, sorted and concatenated, then every hash as a u64,
+//! with the hashes of each file sorted.
+//!
+//! Each table also gets a small JSON header, `{platform}_metadata.json`, of the number of files
+//! in it and whatever `data/{platform}_metadata.json` says about how the table was made, its
+//! `generated` date and the `generator` tool, which are strings. A platform with no metadata
+//! file only gets its number of files.
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+fn pack(table: &BTreeMap<String, Vec<u64>>) -> Vec<u8> {
+    let names_len: usize = table.keys().map(String::len).sum();
+    let hashes_len: usize = table.values().map(Vec::len).sum();
+    let mut out = Vec::with_capacity(12 + table.len() * 8 + names_len + hashes_len * 8);
+    for n in &[table.len(), names_len, hashes_len] {
+        out.extend_from_slice(&(*n as u32).to_le_bytes());
+    }
+    let mut end = 0;
+    for name in table.keys() {
+        end += name.len();
+        out.extend_from_slice(&(end as u32).to_le_bytes());
+    }
+    end = 0;
+    for hashes in table.values() {
+        end += hashes.len();
+        out.extend_from_slice(&(end as u32).to_le_bytes());
+    }
+    for name in table.keys() {
+        out.extend_from_slice(name.as_bytes());
+    }
+    for hash in table.values().flatten() {
+        out.extend_from_slice(&hash.to_le_bytes());
+    }
+    out
+}
+
+fn pack_metadata(table: &BTreeMap<String, Vec<u64>>, src: PathBuf) -> String {
+    let mut metadata: serde_json::Map<String, serde_json::Value> = match fs::read_to_string(&src) {
+        Ok(json) => serde_json::from_str(&json).unwrap(),
+        Err(_) => serde_json::Map::new(),
+    };
+    for key in &["generated", "generator"] {
+        if let Some(value) = metadata.get(*key) {
+            assert!(
+                value.is_string(),
+                "{} in {} is not a string",
+                key,
+                src.display()
+            );
+        }
+    }
+    metadata.insert("files".to_owned(), table.len().into());
+    serde_json::Value::Object(metadata).to_string()
+}
+
+fn main() {
+    let out_dir = env::var_os("OUT_DIR").unwrap();
+    for (name, feature) in &[
+        ("wiiu_hashes", "CARGO_FEATURE_WIIU_DATA"),
+        ("switch_hashes", "CARGO_FEATURE_SWITCH_DATA"),
+    ] {
+        if env::var_os(feature).is_none() {
+            continue;
+        }
+        let src = Path::new("data").join(format!("{}.json", name));
+        println!("cargo:rerun-if-changed={}", src.display());
+        let json = fs::read_to_string(&src).unwrap();
+        let mut table: BTreeMap<String, Vec<u64>> = serde_json::from_str(&json).unwrap();
+        for hashes in table.values_mut() {
+            hashes.sort_unstable();
+            hashes.dedup();
+        }
+        let mut encoder = libflate::deflate::Encoder::new(Vec::new());
+        encoder.write_all(&pack(&table)).unwrap();
+        let packed = encoder.finish().into_result().unwrap();
+        fs::write(Path::new(&out_dir).join(format!("{}.bin", name)), packed).unwrap();
+        let platform = name.trim_end_matches("_hashes");
+        let metadata = pack_metadata(
+            &table,
+            Path::new("data").join(format!("{}_metadata.json", platform)),
+        );
+        fs::write(
+            Path::new(&out_dir).join(format!("{}_metadata.json", platform)),
+            metadata,
+        )
+        .unwrap();
+    }
+    if env::var_os("CARGO_FEATURE_WIIU_DATA").is_some()
+        || env::var_os("CARGO_FEATURE_SWITCH_DATA").is_some()
+    {
+        println!("cargo:rerun-if-changed=data");
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,178 @@
+use crate::{CanonError, Canonicalizer};
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// How many separately locked parts the cache is split into, so threads using it at the same
+/// time rarely wait on each other
+const SHARDS: usize = 16;
+
+/// One separately locked part of a [`CachedCanonicalizer`], which forgets its oldest results
+/// first when it is full
+#[derive(Debug, Default)]
+struct Shard {
+    results: HashMap<String, Result<String, CanonError>>,
+    order: VecDeque<String>,
+}
+
+/// A [`Canonicalizer`] which remembers its results, for tools like mod mergers which
+/// canonicalize the same paths over and over. It can be shared between threads, like in an
+/// [`Arc`](std::sync::Arc). Once it holds as many results as its capacity, the oldest ones are
+/// forgotten first. Example:
+///
+/// ```
+/// use botw_utils::CachedCanonicalizer;
+/// let cache = CachedCanonicalizer::new(1024);
+/// for _ in 0..3 {
+///     assert_eq!(
+///         cache.canonicalize("content/Pack/Bootup.pack").unwrap(),
+///         "Pack/Bootup.pack"
+///     );
+/// }
+/// assert_eq!((cache.hits(), cache.misses()), (2, 1));
+/// ```
+#[derive(Debug)]
+pub struct CachedCanonicalizer {
+    options: Canonicalizer,
+    hasher: RandomState,
+    shards: Vec<Mutex<Shard>>,
+    shard_capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CachedCanonicalizer {
+    /// Makes a cache with room for about `capacity` results, using the default options from
+    /// [`Canonicalizer::new`]
+    pub fn new(capacity: usize) -> CachedCanonicalizer {
+        CachedCanonicalizer::with_options(Canonicalizer::new(), capacity)
+    }
+
+    /// Makes a cache with room for about `capacity` results, using the given options
+    pub fn with_options(options: Canonicalizer, capacity: usize) -> CachedCanonicalizer {
+        CachedCanonicalizer {
+            options,
+            hasher: RandomState::new(),
+            shards: (0..SHARDS).map(|_| Mutex::default()).collect(),
+            shard_capacity: capacity.div_ceil(SHARDS),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Convert a path string relative to a BOTW content root into a canonical resource path,
+    /// like [`Canonicalizer::canonicalize`], reusing the result from an earlier call with the
+    /// same path if there is one
+    pub fn canonicalize(&self, path: &str) -> Result<String, CanonError> {
+        let shard = &self.shards[self.hasher.hash_one(path) as usize % SHARDS];
+        if let Some(result) = lock(shard).results.get(path) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return result.clone();
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let result = self.options.canonicalize(path);
+        if self.shard_capacity > 0 {
+            let mut shard = lock(shard);
+            if !shard.results.contains_key(path) {
+                if shard.results.len() >= self.shard_capacity {
+                    if let Some(oldest) = shard.order.pop_front() {
+                        shard.results.remove(&oldest);
+                    }
+                }
+                shard.order.push_back(path.to_owned());
+                shard.results.insert(path.to_owned(), result.clone());
+            }
+        }
+        result
+    }
+
+    /// Gets how many calls were answered from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Gets how many calls had to canonicalize their path
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Gets how many results the cache holds
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| lock(shard).results.len())
+            .sum()
+    }
+
+    /// Checks whether the cache holds no results
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets every result and resets the hit and miss counters
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            *lock(shard) = Shard::default();
+        }
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Locks a shard, ignoring poisoning, since a panic elsewhere cannot leave a result half written
+fn lock(shard: &Mutex<Shard>) -> std::sync::MutexGuard<'_, Shard> {
+    shard
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn cached_results() {
+        let paths: Vec<String> = (0..200)
+            .map(|i| format!("content/Actor/Pack/Npc_{}.sbactorpack", i))
+            .chain(vec!["Hellow/Sweetie.tardis".to_owned()])
+            .collect();
+        let cache = Arc::new(CachedCanonicalizer::new(1024));
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let cache = Arc::clone(&cache);
+                let paths = paths.clone();
+                std::thread::spawn(move || {
+                    for path in &paths {
+                        assert_eq!(
+                            cache.canonicalize(path),
+                            Canonicalizer::new().canonicalize(path)
+                        );
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(cache.len(), paths.len());
+        assert_eq!(cache.hits() + cache.misses(), 4 * paths.len() as u64);
+        assert!(cache.hits() >= 3 * paths.len() as u64 - 4);
+
+        let small = CachedCanonicalizer::new(SHARDS);
+        for path in &paths {
+            small.canonicalize(path).ok();
+        }
+        assert!(small.len() <= SHARDS);
+        small.clear();
+        assert!(small.is_empty());
+        assert_eq!((small.hits(), small.misses()), (0, 0));
+
+        let none = CachedCanonicalizer::new(0);
+        none.canonicalize("content/Pack/Bootup.pack").unwrap();
+        none.canonicalize("content/Pack/Bootup.pack").unwrap();
+        assert_eq!((none.hits(), none.misses(), none.len()), (0, 2, 0));
+    }
+}
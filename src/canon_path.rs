@@ -0,0 +1,256 @@
+use crate::{titles, CanonError};
+use alloc::borrow::{Borrow, ToOwned};
+use alloc::string::String;
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::Deref;
+
+/// A [canonical resource path](https://zeldamods.org/wiki/Canonical_resource_path) which is known
+/// to be valid. It can only be made by validating a string or by canonicalizing a physical path
+/// with [`get_canon_path`](crate::get_canon_path), so holding one means the path has already been
+/// canonicalized. It derefs to `str` and borrows as `str`, so it can be used anywhere a canonical
+/// path string is expected, including as a `HashMap` key looked up by `&str`. Example:
+///
+/// ```
+/// use botw_utils::CanonPath;
+/// use std::collections::HashMap;
+/// use std::convert::TryFrom;
+/// let canon = CanonPath::try_from("Actor/Pack/Enemy_Lizalfos_Senior.bactorpack").unwrap();
+/// let mut sizes = HashMap::new();
+/// sizes.insert(canon, 1024);
+/// assert_eq!(sizes["Actor/Pack/Enemy_Lizalfos_Senior.bactorpack"], 1024);
+/// assert!(CanonPath::new("content\\Pack\\Bootup.pack").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CanonPath(String);
+
+impl CanonPath {
+    /// Validates a canonical resource path
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The canonical resource path to validate
+    ///
+    /// # Returns
+    ///
+    /// Returns a Result with the CanonPath or [`CanonError::InvalidCanonPath`] describing why the
+    /// string is not a canonical resource path
+    pub fn new<S: Into<String>>(path: S) -> Result<CanonPath, CanonError> {
+        let path = path.into();
+        match invalid_reason(&path) {
+            Some(reason) => Err(CanonError::InvalidCanonPath { path, reason }),
+            None => Ok(CanonPath(path)),
+        }
+    }
+
+    /// Wraps a string produced by canonicalization without validating it again
+    #[cfg(feature = "std")]
+    pub(crate) fn new_unchecked(path: String) -> CanonPath {
+        CanonPath(path)
+    }
+
+    /// Gets the canonical resource path as a string slice
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Unwraps the canonical resource path into an owned String
+    #[inline]
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+/// Checks whether a string is a valid [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path), for sanity checking strings from
+/// logs or mod manifests before using them as hash table keys. Paths with a root folder layout
+/// anywhere in them, which canonicalize to something else, are not canonical. Example:
+///
+/// ```
+/// use botw_utils::is_valid_canon_path;
+/// assert!(is_valid_canon_path("Aoc/0010/Pack/AocMainField.pack"));
+/// assert!(!is_valid_canon_path("content/Pack/Bootup.pack"));
+/// assert!(!is_valid_canon_path("graphicPacks/Mod/content/Pack/Bootup.pack"));
+/// assert!(!is_valid_canon_path("Actor/Pack/Enemy_Lizalfos_Senior.sbactorpack"));
+/// ```
+pub fn is_valid_canon_path(path: &str) -> bool {
+    invalid_reason(path).is_none()
+}
+
+/// Checks whether a string is a valid [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path), like [`is_valid_canon_path`], but
+/// with the reason it is not one.
+///
+/// # Arguments
+///
+/// * `path` - The canonical resource path to validate
+///
+/// # Returns
+///
+/// Returns [`CanonError::InvalidCanonPath`] describing why the string is not a canonical
+/// resource path, if it is not one
+pub fn validate_canon_path(path: &str) -> Result<(), CanonError> {
+    match invalid_reason(path) {
+        Some(reason) => Err(CanonError::InvalidCanonPath {
+            path: path.to_owned(),
+            reason,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Checks a string for problems that mean it cannot be a canonical resource path
+fn invalid_reason(path: &str) -> Option<&'static str> {
+    if path.is_empty() {
+        return Some("it is empty");
+    }
+    if path.contains('\\') {
+        return Some("it contains backslashes");
+    }
+    // Entries of the stock game data archives are bare names with a leading slash
+    let trimmed = path.strip_prefix('/').filter(|name| !name.contains('/'));
+    for segment in trimmed.unwrap_or(path).split('/') {
+        match segment {
+            "" => return Some("it has an empty path segment"),
+            "." | ".." => return Some("it has a relative path segment"),
+            _ => {}
+        }
+    }
+    let mut segments = path.split('/');
+    let first = segments.next().unwrap_or_default();
+    if first.eq_ignore_ascii_case("aoc") {
+        if first != "Aoc" || segments.next() != Some("0010") || segments.next().is_none() {
+            return Some("it has a DLC prefix other than Aoc/0010/");
+        }
+    } else if ["content", "romfs", "atmosphere"]
+        .iter()
+        .any(|root| first.eq_ignore_ascii_case(root))
+        || titles::title_kind(first).is_some()
+    {
+        return Some("it starts with a root folder");
+    }
+    // A root folder layout anywhere in the path, like a mod name folder above `content`, means
+    // it is a physical path which canonicalizes to something else
+    let inner = path.strip_prefix("Aoc/0010/").unwrap_or(path);
+    if !matches!(
+        crate::find_canon_with(inner, &crate::Canonicalizer::new()),
+        Err(crate::Rejection::UnrecognizedRoot)
+    ) {
+        return Some("it has a root folder in it");
+    }
+    if crate::yaz0_prefix_index(path).is_some() {
+        return Some("it has a yaz0 compressed extension");
+    }
+    None
+}
+
+impl Deref for CanonPath {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for CanonPath {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for CanonPath {
+    #[inline]
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CanonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<&str> for CanonPath {
+    type Error = CanonError;
+
+    fn try_from(path: &str) -> Result<CanonPath, CanonError> {
+        CanonPath::new(path)
+    }
+}
+
+impl TryFrom<String> for CanonPath {
+    type Error = CanonError;
+
+    fn try_from(path: String) -> Result<CanonPath, CanonError> {
+        CanonPath::new(path)
+    }
+}
+
+impl From<CanonPath> for String {
+    #[inline]
+    fn from(path: CanonPath) -> String {
+        path.0
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validation() {
+        assert!(CanonPath::new("Actor/Pack/Enemy_Lizal_Senior.bactorpack").is_ok());
+        assert!(CanonPath::new("/bool_data_0.bgdata").is_ok());
+        assert!(CanonPath::new("").is_err());
+        assert!(CanonPath::new("Actor\\Pack\\Enemy_Lizal_Senior.bactorpack").is_err());
+        assert!(CanonPath::new("Actor//Pack/Enemy_Lizal_Senior.bactorpack").is_err());
+        assert!(CanonPath::new("Actor/../Pack/Bootup.pack").is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn validate_paths() {
+        use crate::hashes::Platform;
+        for &platform in &[Platform::WiiU, Platform::Switch] {
+            for name in crate::hashes::get_hash_table(platform).keys() {
+                assert_eq!(validate_canon_path(name), Ok(()));
+            }
+        }
+        for path in &[
+            "content/Pack/Bootup.pack",
+            "aoc/0010/Pack/AocMainField.pack",
+            "Aoc/0011/Pack/AocMainField.pack",
+            "Aoc/0010",
+            "romfs/Pack/Bootup.pack",
+            "01007EF00011E000/romfs/Pack/Bootup.pack",
+            "0005000E101C9400/content/Pack/Bootup.pack",
+            "Actor/Pack/Enemy_Lizalfos_Senior.sbactorpack",
+            "/Pack/Bootup.pack",
+            "Pack/./Bootup.pack",
+        ] {
+            assert!(!is_valid_canon_path(path), "{}", path);
+        }
+        assert!(matches!(
+            validate_canon_path("Map/MainField/A-1/A-1_Static.smubin"),
+            Err(CanonError::InvalidCanonPath {
+                reason: "it has a yaz0 compressed extension",
+                ..
+            })
+        ));
+        assert!(is_valid_canon_path("Actor/Pack/Npc_King.statue.bactorpack"));
+    }
+
+    #[test]
+    #[cfg(feature = "wiiu-data")]
+    fn stock_lookups() {
+        use crate::hashes::{Platform, StockHashTable};
+        let table = StockHashTable::new(Platform::WiiU);
+        let canon = crate::get_canon_path("content/Pack/Bootup.pack").unwrap();
+        assert!(!table.is_file_new(&canon));
+        assert!(table.is_file_modded(&canon, b"Random data", true));
+    }
+}
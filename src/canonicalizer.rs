@@ -0,0 +1,191 @@
+use crate::{
+    find_canon_with, needs_normalizing, normalize_path, percent_decode, strip_windows_prefix,
+    CanonError, RootKind,
+};
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+
+/// Options for how strictly paths are turned into [canonical resource
+/// paths](https://zeldamods.org/wiki/Canonical_resource_path). The free functions like
+/// [`get_canon_name`](crate::get_canon_name) use the defaults from [`Canonicalizer::new`], so a
+/// Canonicalizer is only needed to accept more paths or fewer. Example:
+///
+/// ```
+/// use botw_utils::Canonicalizer;
+/// let strict = Canonicalizer::new()
+///     .allow_bare_romfs(false)
+///     .normalize_dot_segments(false);
+/// assert!(strict.canonicalize("romfs/Pack/Bootup.pack").is_err());
+/// assert!(strict.canonicalize("content/Pack/../Pack/Bootup.pack").is_err());
+/// let lenient = Canonicalizer::new().assume_content_when_unrooted(true);
+/// assert_eq!(lenient.canonicalize("Pack/Bootup.pack").unwrap(), "Pack/Bootup.pack");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Canonicalizer {
+    pub(crate) allow_bare_romfs: bool,
+    pub(crate) assume_content_when_unrooted: bool,
+    normalize_dot_segments: bool,
+    decode_percent_encoding: bool,
+    treat_as_file: bool,
+}
+
+impl Canonicalizer {
+    /// Makes a Canonicalizer with the default options, which accept a bare `romfs` folder at the
+    /// start of a path, resolve `.` and `..` segments, and do not decode percent-encoding or
+    /// accept paths with no root folder or a trailing separator
+    pub const fn new() -> Canonicalizer {
+        Canonicalizer {
+            allow_bare_romfs: true,
+            assume_content_when_unrooted: false,
+            normalize_dot_segments: true,
+            decode_percent_encoding: false,
+            treat_as_file: false,
+        }
+    }
+
+    /// Sets whether a `romfs` folder at the start of a path with no title ID above it is treated
+    /// as base game content
+    pub const fn allow_bare_romfs(mut self, allow: bool) -> Canonicalizer {
+        self.allow_bare_romfs = allow;
+        self
+    }
+
+    /// Sets whether a path with no recognized root folder is treated as already relative to a
+    /// base game content root, instead of failing
+    pub const fn assume_content_when_unrooted(mut self, assume: bool) -> Canonicalizer {
+        self.assume_content_when_unrooted = assume;
+        self
+    }
+
+    /// Sets whether `.` and `..` segments are resolved. When they are not, paths with them fail
+    /// with [`CanonError::NotUnderContentRoot`].
+    pub const fn normalize_dot_segments(mut self, normalize: bool) -> Canonicalizer {
+        self.normalize_dot_segments = normalize;
+        self
+    }
+
+    /// Sets whether paths are percent-decoded first, like
+    /// [`get_canon_name_from_url`](crate::get_canon_name_from_url) does
+    pub const fn decode_percent_encoding(mut self, decode: bool) -> Canonicalizer {
+        self.decode_percent_encoding = decode;
+        self
+    }
+
+    /// Sets whether a path which ends with a separator is treated as a file with the separator
+    /// removed, for paths from places like mod manifests which may not be precise about it,
+    /// instead of failing with [`CanonError::IsDirectory`]
+    pub const fn treat_as_file(mut self, treat_as_file: bool) -> Canonicalizer {
+        self.treat_as_file = treat_as_file;
+        self
+    }
+
+    /// Convert a path string relative to a BOTW content root into a canonical resource path with
+    /// these options
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the BOTW game file relative to the root folder
+    ///
+    /// # Returns
+    ///
+    /// Returns a Result with the canonical resource path as a String or a [`CanonError`]
+    /// describing why the path does not appear valid
+    pub fn canonicalize(&self, path: &str) -> Result<String, CanonError> {
+        self.classify(path, &mut String::new())
+            .map(|(_, canon)| canon)
+    }
+
+    /// Finds the root folder kind and canonical resource path for a path, normalizing it into a
+    /// scratch buffer which can be reused between calls
+    pub(crate) fn classify(
+        &self,
+        path: &str,
+        scratch: &mut String,
+    ) -> Result<(RootKind, String), CanonError> {
+        let decoded;
+        let input = if self.decode_percent_encoding {
+            decoded = percent_decode(path).ok_or_else(|| CanonError::InvalidEncoding {
+                path: path.to_owned(),
+            })?;
+            decoded.as_str()
+        } else {
+            path
+        };
+        let mut stripped = strip_windows_prefix(input);
+        if self.treat_as_file {
+            stripped = stripped.trim_end_matches(['/', '\\']);
+        }
+        let normalized = if needs_normalizing(stripped) {
+            if !self.normalize_dot_segments
+                && stripped
+                    .split(['/', '\\'])
+                    .any(|segment| segment == "." || segment == "..")
+            {
+                return Err(CanonError::NotUnderContentRoot {
+                    path: path.to_owned(),
+                });
+            }
+            normalize_path(stripped, scratch);
+            scratch
+        } else {
+            stripped
+        };
+        find_canon_with(normalized, self)
+            .map(|parts| (parts.kind, parts.build()))
+            .map_err(|rejection| rejection.into_error(path))
+    }
+}
+
+impl Default for Canonicalizer {
+    fn default() -> Canonicalizer {
+        Canonicalizer::new()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configurations() {
+        let default = Canonicalizer::default();
+        let strict = Canonicalizer::new()
+            .allow_bare_romfs(false)
+            .normalize_dot_segments(false);
+        let lenient = Canonicalizer::new()
+            .assume_content_when_unrooted(true)
+            .decode_percent_encoding(true);
+        let results = |path| {
+            [default, strict, lenient]
+                .iter()
+                .map(|options| options.canonicalize(path).ok())
+                .collect::<Vec<_>>()
+        };
+        let canon = Some("Pack/Bootup.pack".to_owned());
+        assert_eq!(
+            results("romfs/Pack/Bootup.pack"),
+            [canon.clone(), None, canon.clone()]
+        );
+        assert_eq!(
+            results("content\\Pack\\..\\Pack\\Bootup.pack"),
+            [canon.clone(), None, None]
+        );
+        assert_eq!(results("/Pack/Bootup.pack"), [None, None, canon.clone()]);
+        assert_eq!(
+            results("content/Pack/Bootup%2Epack"),
+            [
+                Some("Pack/Bootup%2Epack".to_owned()),
+                Some("Pack/Bootup%2Epack".to_owned()),
+                canon
+            ]
+        );
+        assert!(matches!(
+            lenient.canonicalize("content/Pack/Bootup.pack%zz"),
+            Err(CanonError::InvalidEncoding { .. })
+        ));
+        assert_eq!(
+            default.canonicalize("Hellow/Sweetie.tardis"),
+            crate::try_get_canon_name("Hellow/Sweetie.tardis")
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use crate::hashes::{Platform, StockHashTable};
+use crate::walk::error_path;
+use crate::{canon_to_physical, canonicalize_dir, is_texture_pack_name, texture_name_for_platform};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What [`convert_mod_layout`] did with the files of a mod
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConversionReport {
+    /// The files which were copied, with their source and destination paths
+    pub copied: Vec<(PathBuf, PathBuf)>,
+    /// Copied files which may still not work on the target platform, like models and textures
+    /// whose data is in the source platform's format, with the reason
+    pub uncertain: Vec<(PathBuf, String)>,
+    /// Files which were not copied, like compiled shaders, files with no canonical resource
+    /// path, and junk files matching the [default ignores](crate::default_ignores), with the
+    /// reason
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+/// Extensions of files which are compiled for one platform's graphics or audio hardware, like
+/// shaders, sound archives, and Wii U `bflim` or Switch `bntx` layout textures, so they cannot be
+/// used on the other platform at all
+const PLATFORM_EXTS: &[&str] = &["bfsha", "bars", "bfstm", "bflim", "bntx"];
+
+/// What to do with a file when converting it between platforms
+enum Plan {
+    Copy(String),
+    Uncertain(String, String),
+    Skip(String),
+}
+
+/// Converts the folder layout of a mod from one platform to the other, like a Wii U mod with
+/// `content` and `aoc/0010` folders into a Switch mod with `01007EF00011E000/romfs` and
+/// `01007EF00011F001/romfs` folders. Each file with a [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path) is copied to the place
+/// [`canon_to_physical`](crate::canon_to_physical) gives for the target platform, keeping
+/// whether its extension marks it as yaz0 compressed. Textures are renamed between the Wii U's
+/// split `.Tex1.bfres` and `.Tex2.bfres` files and the Switch's single `.Tex.bfres` file. The
+/// data in files is never converted, so everything that may need converting is listed in the
+/// report. Example:
+///
+/// ```no_run
+/// use botw_utils::{convert_mod_layout, hashes::Platform};
+/// use std::path::Path;
+/// let report = convert_mod_layout(
+///     Path::new("mods/My Mod"),
+///     Path::new("mods/My Mod (Switch)"),
+///     Platform::WiiU,
+///     Platform::Switch,
+/// );
+/// for (path, reason) in report.skipped.iter().chain(&report.uncertain) {
+///     println!("{}: {}", path.display(), reason);
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `src_root` - The top folder of the mod to convert
+/// * `dst` - The folder to write the converted mod to
+/// * `from` - The platform the mod is for
+/// * `to` - The platform to convert the mod for
+///
+/// # Returns
+///
+/// Returns a [`ConversionReport`] of the files copied and skipped. Files which cannot be read or
+/// written are listed as skipped.
+pub fn convert_mod_layout(
+    src_root: &Path,
+    dst: &Path,
+    from: Platform,
+    to: Platform,
+) -> ConversionReport {
+    let from_table = StockHashTable::get(from);
+    let to_table = StockHashTable::get(to);
+    let mut report = ConversionReport::default();
+    let mut walk = canonicalize_dir(src_root);
+    for result in walk.by_ref() {
+        let (path, canon) = match result {
+            Ok(file) => file,
+            Err(e) => {
+                report.skipped.push((error_path(&e), e.to_string()));
+                continue;
+            }
+        };
+        let (target, note) = match plan(&canon, from, to) {
+            Plan::Copy(target) => (target, None),
+            Plan::Uncertain(target, note) => (target, Some(note)),
+            Plan::Skip(reason) => {
+                report.skipped.push((path, reason));
+                continue;
+            }
+        };
+        let note = note.or_else(|| {
+            if from != to && from_table.contains(&canon) && !to_table.contains(&target) {
+                Some(format!("{} is not a stock file on {}", target, to))
+            } else {
+                None
+            }
+        });
+        let mut dest = dst.join(canon_to_physical(&target, to));
+        if let Some(ext) = path.extension() {
+            dest.set_extension(ext);
+        }
+        let copy = dest
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::copy(&path, &dest));
+        if let Err(e) = copy {
+            report
+                .skipped
+                .push((path, format!("could not copy: {}", e)));
+            continue;
+        }
+        if let Some(note) = note {
+            report.uncertain.push((path.clone(), note));
+        }
+        report.copied.push((path, dest));
+    }
+    report.skipped.extend(
+        walk.ignored()
+            .iter()
+            .map(|path| (path.clone(), "junk files are not copied".to_owned())),
+    );
+    report
+}
+
+/// Decides where a file goes on the target platform from its canonical resource path
+fn plan(canon: &str, from: Platform, to: Platform) -> Plan {
+    let name_start = canon.rfind('/').map(|i| i + 1).unwrap_or(0);
+    let name = &canon[name_start..];
+    let ext = name.rsplit('.').next().unwrap_or("");
+    if from == to {
+        return Plan::Copy(canon.to_owned());
+    }
+    if PLATFORM_EXTS.contains(&ext) || name.contains(".release.") {
+        return Plan::Skip(format!("{} files cannot be used on {}", ext, to));
+    }
+    if !is_texture_pack_name(name) {
+        if ext == "bfres" {
+            return Plan::Uncertain(canon.to_owned(), "model data must be converted".to_owned());
+        }
+        return Plan::Copy(canon.to_owned());
+    }
+    match (texture_name_for_platform(canon, to), to) {
+        (Some(target), Platform::Switch) => Plan::Uncertain(
+            target,
+            "texture data must be converted and merged with the Tex2 file".to_owned(),
+        ),
+        (Some(target), Platform::WiiU) => Plan::Uncertain(
+            target,
+            "texture data must be converted and split into a Tex2 file".to_owned(),
+        ),
+        (None, _) => Plan::Skip("Switch textures are all in the Tex1 file's .Tex.bfres".to_owned()),
+    }
+}
+
+#[cfg(all(test, feature = "wiiu-data", feature = "switch-data"))]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn wiiu_to_switch() {
+        let dir = TempDir::new("convert");
+        let src = dir.join("src");
+        for file in &[
+            "rules.txt",
+            "content/Actor/Pack/Enemy_Lizal_Senior.sbactorpack",
+            "content/Model/FldObj_Mountain_A.Tex1.sbfres",
+            "content/Model/FldObj_Mountain_A.Tex2.sbfres",
+            "content/Model/FldObj_Mountain_A.sbfres",
+            "content/Shader/uking_mat.product.sbfsha",
+            "content/Sound/Resource/Voice/Npc.bars",
+            "content/UI/StockItem/MyItem.bflim",
+            "aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin",
+            "content/Actor/Thumbs.db",
+        ] {
+            dir.write(&format!("src/{}", file), file);
+        }
+        let out = dir.join("out");
+        let report = convert_mod_layout(&src, &out, Platform::WiiU, Platform::Switch);
+        let relative = |paths: Vec<&PathBuf>, root: &Path| {
+            let mut paths: Vec<String> = paths
+                .into_iter()
+                .map(|path| {
+                    path.strip_prefix(root)
+                        .unwrap()
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                })
+                .collect();
+            paths.sort();
+            paths
+        };
+        assert_eq!(
+            relative(report.copied.iter().map(|(_, dst)| dst).collect(), &out),
+            [
+                "01007EF00011E000/romfs/Actor/Pack/Enemy_Lizal_Senior.sbactorpack",
+                "01007EF00011E000/romfs/Model/FldObj_Mountain_A.Tex.sbfres",
+                "01007EF00011E000/romfs/Model/FldObj_Mountain_A.sbfres",
+                "01007EF00011F001/romfs/Map/MainField/A-1/A-1_Dynamic.mubin",
+            ]
+        );
+        assert_eq!(
+            fs::read(out.join("01007EF00011E000/romfs/Model/FldObj_Mountain_A.Tex.sbfres"))
+                .unwrap(),
+            b"content/Model/FldObj_Mountain_A.Tex1.sbfres"
+        );
+        assert_eq!(
+            relative(report.uncertain.iter().map(|(src, _)| src).collect(), &src),
+            [
+                "content/Model/FldObj_Mountain_A.Tex1.sbfres",
+                "content/Model/FldObj_Mountain_A.sbfres",
+            ]
+        );
+        assert_eq!(
+            relative(report.skipped.iter().map(|(src, _)| src).collect(), &src),
+            [
+                "content/Actor/Thumbs.db",
+                "content/Model/FldObj_Mountain_A.Tex2.sbfres",
+                "content/Shader/uking_mat.product.sbfsha",
+                "content/Sound/Resource/Voice/Npc.bars",
+                "content/UI/StockItem/MyItem.bflim",
+                "rules.txt",
+            ]
+        );
+        match plan(
+            "Sound/Resource/Voice/Npc.bars",
+            Platform::Switch,
+            Platform::WiiU,
+        ) {
+            Plan::Skip(reason) => assert_eq!(reason, "bars files cannot be used on Wii U"),
+            _ => panic!("sound archives should be skipped"),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+/// The CRC-32 lookup table for the reflected IEEE polynomial, the one zlib uses
+const TABLE: [u32; 256] = make_table();
+
+const fn make_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Gets the CRC32 of a canonical resource path, the way BCML and formats like BNP logs and the
+/// resource size table identify files instead of by name. This is the same CRC as zlib's, so it
+/// matches `zlib.crc32(canon.encode())` in Python. The path is hashed as it is, so it should
+/// already be canonical. Stock files can be found from their CRC with
+/// [`canon_for_crc`](crate::hashes::canon_for_crc). Example:
+///
+/// ```
+/// use botw_utils::crc_of_canon;
+/// assert_eq!(crc_of_canon("Pack/Bootup.pack"), 3_558_169_191);
+/// ```
+pub fn crc_of_canon(canon: &str) -> u32 {
+    crc32(canon.as_bytes())
+}
+
+/// Gets the CRC32 of some data, the same CRC as zlib's
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, byte| {
+        TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zlib_crcs() {
+        assert_eq!(crc_of_canon(""), 0);
+        assert_eq!(crc_of_canon("123456789"), 0xCBF4_3926);
+        assert_eq!(crc_of_canon("Actor/ActorInfo.product.byml"), 1_378_804_307);
+        assert_eq!(
+            crc_of_canon("Actor/Pack/Enemy_Lizalfos_Senior.bactorpack"),
+            818_371_988
+        );
+    }
+}
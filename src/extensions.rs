@@ -1,4 +1,4 @@
-//! Contains arrays of file extensions used by BOTW for SARC, AAMP, and BYML files
+//! Contains arrays of file extensions used by BOTW for SARC, AAMP, BYML, and other resource files
 
 /// List of known file extensions in BOTW for SARC files
 pub const SARC_EXTS: &[&str] = &[
@@ -167,3 +167,93 @@ pub const BYML_EXTS: &[&str] = &[
     "bgsvdata",
     "sbgsvdata",
 ];
+
+/// List of other known file extensions in BOTW for resources that are not SARC, AAMP, or BYML
+/// files
+pub const MISC_EXTS: &[&str] = &[
+    "agstats",
+    "baatarc",
+    "bagst",
+    "bars",
+    "bcamanim",
+    "beco",
+    "belnk",
+    "bfevfl",
+    "bfevtm",
+    "bflan",
+    "bflim",
+    "bflyt",
+    "bfotf",
+    "bfres",
+    "sbfres",
+    "bfsar",
+    "bfsha",
+    "bfstm",
+    "bfttf",
+    "bgsh",
+    "bitemico",
+    "sbitemico",
+    "blal",
+    "blwp",
+    "sblwp",
+    "bmapopen",
+    "bmaptex",
+    "sbmaptex",
+    "bnsh",
+    "bntx",
+    "breviewtex",
+    "sbreviewtex",
+    "bslnk",
+    "bstftex",
+    "sbstftex",
+    "bushvt",
+    "byaml",
+    "esetlist",
+    "sesetlist",
+    "extm",
+    "fmc",
+    "fxparam",
+    "genvb",
+    "gsh",
+    "gtx",
+    "harcb",
+    "hght",
+    "hkcl",
+    "hknm2",
+    "shknm2",
+    "hkrb",
+    "hkrg",
+    "hksc",
+    "shksc",
+    "hktmrb",
+    "shktmrb",
+    "kybin",
+    "mate",
+    "ntx",
+    "rsizetable",
+    "srsizetable",
+    "tscb",
+];
+
+/// Checks whether an extension (without the leading dot) is a known BOTW file extension
+///
+/// # Arguments
+///
+/// * `ext` - The file extension to check, e.g. `"sbactorpack"`
+pub fn is_known_ext(ext: &str) -> bool {
+    SARC_EXTS.contains(&ext)
+        || AAMP_EXTS.contains(&ext)
+        || BYML_EXTS.contains(&ext)
+        || MISC_EXTS.contains(&ext)
+}
+
+/// Checks whether files with an extension (without the leading dot) are stored yaz0 compressed
+/// on disk in the unmodded game, meaning their physical name has an extra `s` prefix on the
+/// extension. SARC archives with the `pack` extension are never compressed.
+///
+/// # Arguments
+///
+/// * `ext` - The uncompressed file extension to check, e.g. `"bactorpack"`
+pub fn is_compressible_ext(ext: &str) -> bool {
+    ext != "pack" && is_known_ext(&["s", ext].concat())
+}
@@ -0,0 +1,47 @@
+use std::sync::OnceLock;
+
+/// The canonical resource paths of the files listed in `data/auto_generated.json`, sorted
+fn auto_generated() -> &'static [&'static str] {
+    static FILES: OnceLock<Vec<&'static str>> = OnceLock::new();
+    FILES.get_or_init(|| {
+        let mut files: Vec<&'static str> =
+            serde_json::from_str(include_str!("../data/auto_generated.json"))
+                .expect("auto_generated.json is a list of plain strings");
+        files.sort_unstable();
+        files
+    })
+}
+
+/// Checks whether a file is one which merge tools regenerate from the other files of a mod, like
+/// `Actor/ActorInfo.product.byml`, the resource size table, and the game data archives. Nearly
+/// every mod changes these, so tools usually report them apart from the files a mod really
+/// changes. The list is read from `data/auto_generated.json`. Example:
+///
+/// ```
+/// use botw_utils::is_auto_generated;
+/// assert!(is_auto_generated("System/Resource/ResourceSizeTable.product.rsizetable"));
+/// assert!(!is_auto_generated("Actor/Pack/Enemy_Lizalfos_Senior.bactorpack"));
+/// ```
+///
+/// # Arguments
+///
+/// * `canon` - The canonical resource path of the file
+pub fn is_auto_generated(canon: &str) -> bool {
+    auto_generated().binary_search(&canon).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_generated_files() {
+        assert!(auto_generated()
+            .iter()
+            .all(|canon| crate::is_valid_canon_path(canon)));
+        assert!(is_auto_generated("Actor/ActorInfo.product.byml"));
+        assert!(is_auto_generated("Pack/Bootup/GameData/gamedata.sarc"));
+        assert!(!is_auto_generated("Actor/ActorInfo.product.sbyml"));
+        assert!(!is_auto_generated("Pack/Bootup.pack"));
+    }
+}
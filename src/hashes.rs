@@ -1,63 +1,2021 @@
-use include_flate::flate;
-use roead::yaz0::decompress;
-use std::collections::HashMap;
+use crate::{get_nested_canon_name, strip_windows_prefix, try_get_canon_name, CanonError};
+use roead::yaz0::{decompress_into, get_header};
+use std::borrow::Cow;
+use std::collections::{hash_map, BTreeMap, HashMap};
+use std::fmt;
 use std::hash::Hasher;
+use std::io::{self, Read};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
 use twox_hash::XxHash64;
 
-flate!(static HASHES_U: str from "data/wiiu_hashes.json");
-flate!(static HASHES_NX: str from "data/switch_hashes.json");
+mod export;
+mod lazy;
+pub use lazy::{HashLookup, LazyHashTable};
+#[cfg(all(feature = "walk", feature = "sarc"))]
+mod dump;
+#[cfg(all(feature = "walk", feature = "sarc"))]
+pub use dump::DumpReport;
+mod progress;
+pub use progress::ScanProgress;
+#[cfg(feature = "sarc")]
+mod sarc;
+#[cfg(feature = "sarc")]
+pub use sarc::{SarcCheck, SarcEntry};
+#[cfg(feature = "walk")]
+mod scan;
+#[cfg(feature = "walk")]
+pub use scan::{InvalidYaz0Policy, ModScanReport, ScanOptions};
+
+#[cfg(feature = "wiiu-data")]
+static PACKED_U: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/wiiu_hashes.bin"));
+#[cfg(feature = "switch-data")]
+static PACKED_NX: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/switch_hashes.bin"));
+#[cfg(feature = "wiiu-data")]
+static METADATA_U: &str = include_str!(concat!(env!("OUT_DIR"), "/wiiu_metadata.json"));
+#[cfg(not(feature = "wiiu-data"))]
+static METADATA_U: &str = "{}";
+#[cfg(feature = "switch-data")]
+static METADATA_NX: &str = include_str!(concat!(env!("OUT_DIR"), "/switch_metadata.json"));
+#[cfg(not(feature = "switch-data"))]
+static METADATA_NX: &str = "{}";
 pub type HashTable = HashMap<&'static str, Vec<u64>>;
 
-/// Platform enum for Wii U or Switch copy of BOTW
-#[derive(Debug, Eq, PartialEq)]
-pub enum Platform {
-    WiiU,
-    Switch,
-}
+/// Platform enum for Wii U or Switch copy of BOTW. It parses from `wiiu`, `wii_u`, `switch`,
+/// or `nx`, ignoring case, and displays as `Wii U` or `Switch`. With the `serde` feature, it is
+/// serialized as `wiiu` or `switch`. Example:
+///
+/// ```
+/// use botw_utils::hashes::Platform;
+/// assert_eq!("NX".parse(), Ok(Platform::Switch));
+/// assert_eq!(Platform::WiiU.to_string().parse(), Ok(Platform::WiiU));
+/// assert!("3ds".parse::<Platform>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Platform {
+    WiiU,
+    Switch,
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Platform::WiiU => "Wii U",
+            Platform::Switch => "Switch",
+        })
+    }
+}
+
+/// Error type for a string which does not name a [`Platform`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParsePlatformError {
+    /// The string which could not be parsed
+    pub input: String,
+}
+
+impl fmt::Display for ParsePlatformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unrecognized platform `{}`, expected `wiiu` or `switch`",
+            self.input
+        )
+    }
+}
+
+impl std::error::Error for ParsePlatformError {}
+
+impl FromStr for Platform {
+    type Err = ParsePlatformError;
+
+    fn from_str(name: &str) -> Result<Platform, ParsePlatformError> {
+        let normalized: String = name
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-' | ' '))
+            .collect::<String>()
+            .to_ascii_lowercase();
+        match normalized.as_str() {
+            "wiiu" => Ok(Platform::WiiU),
+            "switch" | "nx" => Ok(Platform::Switch),
+            _ => Err(ParsePlatformError {
+                input: name.to_owned(),
+            }),
+        }
+    }
+}
+
+/// A released version of BOTW, for picking which stock hash table to check files against with
+/// [`StockHashTable::for_version`]. Only the latest version of each platform, 1.5.0 for Wii U
+/// and 1.6.0 for Switch, has a table embedded so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum GameVersion {
+    #[cfg_attr(feature = "serde", serde(rename = "1.0.0"))]
+    V1_0_0,
+    #[cfg_attr(feature = "serde", serde(rename = "1.1.0"))]
+    V1_1_0,
+    #[cfg_attr(feature = "serde", serde(rename = "1.2.0"))]
+    V1_2_0,
+    #[cfg_attr(feature = "serde", serde(rename = "1.3.0"))]
+    V1_3_0,
+    #[cfg_attr(feature = "serde", serde(rename = "1.3.1"))]
+    V1_3_1,
+    #[cfg_attr(feature = "serde", serde(rename = "1.4.0"))]
+    V1_4_0,
+    #[cfg_attr(feature = "serde", serde(rename = "1.5.0"))]
+    V1_5_0,
+    /// Only released for Switch
+    #[cfg_attr(feature = "serde", serde(rename = "1.6.0"))]
+    V1_6_0,
+}
+
+impl GameVersion {
+    /// Every version, oldest first
+    pub const ALL: [GameVersion; 8] = [
+        GameVersion::V1_0_0,
+        GameVersion::V1_1_0,
+        GameVersion::V1_2_0,
+        GameVersion::V1_3_0,
+        GameVersion::V1_3_1,
+        GameVersion::V1_4_0,
+        GameVersion::V1_5_0,
+        GameVersion::V1_6_0,
+    ];
+
+    /// Gets the latest version of the game for a platform, which the tables made by
+    /// [`StockHashTable::new`] are for
+    pub fn latest(platform: Platform) -> GameVersion {
+        match platform {
+            Platform::WiiU => GameVersion::V1_5_0,
+            Platform::Switch => GameVersion::V1_6_0,
+        }
+    }
+
+    /// Gets the version number, like `1.5.0`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GameVersion::V1_0_0 => "1.0.0",
+            GameVersion::V1_1_0 => "1.1.0",
+            GameVersion::V1_2_0 => "1.2.0",
+            GameVersion::V1_3_0 => "1.3.0",
+            GameVersion::V1_3_1 => "1.3.1",
+            GameVersion::V1_4_0 => "1.4.0",
+            GameVersion::V1_5_0 => "1.5.0",
+            GameVersion::V1_6_0 => "1.6.0",
+        }
+    }
+}
+
+impl fmt::Display for GameVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error type for a string which is not the number of a [`GameVersion`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseGameVersionError {
+    /// The string which could not be parsed
+    pub input: String,
+}
+
+impl fmt::Display for ParseGameVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unrecognized game version `{}`, expected a version number like `1.5.0`",
+            self.input
+        )
+    }
+}
+
+impl std::error::Error for ParseGameVersionError {}
+
+impl FromStr for GameVersion {
+    type Err = ParseGameVersionError;
+
+    fn from_str(version: &str) -> Result<GameVersion, ParseGameVersionError> {
+        GameVersion::ALL
+            .iter()
+            .find(|known| known.as_str() == version)
+            .copied()
+            .ok_or_else(|| ParseGameVersionError {
+                input: version.to_owned(),
+            })
+    }
+}
+
+/// Checks whether the stock hash table for a platform is embedded in this build. The Wii U
+/// table needs the `wiiu-data` feature and the Switch table the `switch-data` feature, which
+/// are both on by default. Functions which use the stock hashes panic if asked for a platform
+/// whose table is not embedded.
+///
+/// # Arguments
+///
+/// * `platform` - The platform to check for
+pub fn has_stock_hashes(platform: Platform) -> bool {
+    match platform {
+        Platform::WiiU => cfg!(feature = "wiiu-data"),
+        Platform::Switch => cfg!(feature = "switch-data"),
+    }
+}
+
+/// Gets the metadata of every stock hash table embedded in this build, Wii U first, for
+/// showing which data a tool was built with, like in its version output or bug reports.
+/// Example:
+///
+#[cfg_attr(feature = "wiiu-data", doc = "```")]
+#[cfg_attr(not(feature = "wiiu-data"), doc = "```ignore")]
+/// use botw_utils::hashes::{data_versions, GameVersion, Platform};
+/// let versions = data_versions();
+/// assert_eq!(versions[0].platform, Some(Platform::WiiU));
+/// assert_eq!(versions[0].version, Some(GameVersion::V1_5_0));
+/// assert!(versions[0].files > 0);
+/// ```
+pub fn data_versions() -> Vec<&'static TableMetadata> {
+    [Platform::WiiU, Platform::Switch]
+        .iter()
+        .filter(|platform| has_stock_hashes(**platform))
+        .map(|platform| &**embedded_metadata(*platform))
+        .collect()
+}
+
+/// Gets the metadata of the embedded table for a platform, reading the header `build.rs`
+/// writes beside it the first time it is asked for
+fn embedded_metadata(platform: Platform) -> &'static Arc<TableMetadata> {
+    static WIIU: OnceLock<Arc<TableMetadata>> = OnceLock::new();
+    static SWITCH: OnceLock<Arc<TableMetadata>> = OnceLock::new();
+    let (cell, json) = match platform {
+        Platform::WiiU => (&WIIU, METADATA_U),
+        Platform::Switch => (&SWITCH, METADATA_NX),
+    };
+    cell.get_or_init(|| {
+        // `build.rs` checks the header, so it only fails to read if it is missing
+        let metadata = serde_json::from_str(json)
+            .map_err(|e| e.to_string())
+            .and_then(|value| TableMetadata::from_json(&value))
+            .unwrap_or_default();
+        Arc::new(TableMetadata {
+            platform: Some(platform),
+            version: Some(GameVersion::latest(platform)),
+            ..metadata
+        })
+    })
+}
+
+/// The files of a hash table and their hashes, unpacked from the tables the build script
+/// embeds or loaded from JSON. The names are sorted and concatenated so files can be found by
+/// binary search, with the name of the file at index `i` at
+/// `names[name_starts[i]..name_starts[i + 1]]` and its hashes, sorted so they can be checked by
+/// binary search too, at `hashes[hash_starts[i]..hash_starts[i + 1]]`. Keeping every hash in
+/// one buffer costs a u32 per file instead of a `Vec` each, which matters with over a hundred
+/// thousand files and nearly all of them with only one or two hashes.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct StockData {
+    names: String,
+    name_starts: Vec<u32>,
+    hash_starts: Vec<u32>,
+    hashes: Vec<u64>,
+}
+
+impl StockData {
+    /// Gets the stock data for the latest version of the game on a platform, unpacking it the
+    /// first time it is asked for
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table for the platform is not embedded or is corrupt.
+    #[cfg(any(feature = "wiiu-data", feature = "switch-data"))]
+    fn get(platform: Platform) -> &'static StockData {
+        StockData::try_get(platform).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Gets the stock data for the latest version of the game on a platform, or why it cannot
+    fn try_get(platform: Platform) -> Result<&'static StockData, TableError> {
+        StockData::try_for_version(platform, GameVersion::latest(platform))
+            .unwrap_or(Err(TableError::NotEmbedded(platform)))
+    }
+
+    /// Gets the stock data for a version of the game, if it is embedded, like
+    /// [`try_for_version`](StockData::try_for_version)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the embedded table is corrupt.
+    fn for_version(platform: Platform, version: GameVersion) -> Option<&'static StockData> {
+        StockData::try_for_version(platform, version)
+            .map(|result| result.unwrap_or_else(|e| panic!("{}", e)))
+    }
+
+    /// Gets the stock data for a version of the game, if it is embedded, unpacking it the first
+    /// time it is asked for, or why it could not be unpacked
+    fn try_for_version(
+        platform: Platform,
+        version: GameVersion,
+    ) -> Option<Result<&'static StockData, TableError>> {
+        match (platform, version) {
+            #[cfg(feature = "wiiu-data")]
+            (Platform::WiiU, GameVersion::V1_5_0) => {
+                static TABLE: OnceLock<Result<StockData, String>> = OnceLock::new();
+                let table = TABLE.get_or_init(|| StockData::unpack(PACKED_U));
+                Some(StockData::unpacked(platform, table))
+            }
+            #[cfg(feature = "switch-data")]
+            (Platform::Switch, GameVersion::V1_6_0) => {
+                static TABLE: OnceLock<Result<StockData, String>> = OnceLock::new();
+                let table = TABLE.get_or_init(|| StockData::unpack(PACKED_NX));
+                Some(StockData::unpacked(platform, table))
+            }
+            _ => None,
+        }
+    }
+
+    /// Turns what is wrong with an embedded table into an error for its platform
+    #[cfg(any(feature = "wiiu-data", feature = "switch-data"))]
+    fn unpacked(
+        platform: Platform,
+        table: &'static Result<StockData, String>,
+    ) -> Result<&'static StockData, TableError> {
+        table.as_ref().map_err(|reason| TableError::Corrupt {
+            platform,
+            reason: reason.clone(),
+        })
+    }
+
+    /// Decompresses and splits up a table in the format written by `build.rs`, checking that the
+    /// offsets in it are consistent and the names are sorted, or gives what is wrong with it
+    #[cfg(any(feature = "wiiu-data", feature = "switch-data"))]
+    fn unpack(packed: &[u8]) -> Result<StockData, String> {
+        use libflate::deflate::Decoder;
+        use std::convert::TryInto;
+        let mut data = Vec::new();
+        Decoder::new(packed)
+            .read_to_end(&mut data)
+            .map_err(|e| format!("it could not be inflated: {}", e))?;
+        if data.len() < 12 {
+            return Err(format!("its header is truncated at {} bytes", data.len()));
+        }
+        let word = |i: usize| u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+        let (count, names_len, hashes_len) = (word(0) as usize, word(4) as usize, word(8) as usize);
+        let name_ends = 12;
+        let hash_ends = name_ends + count * 4;
+        let names_start = hash_ends + count * 4;
+        let hashes_start = names_start + names_len;
+        let expected = hashes_start + hashes_len * 8;
+        if data.len() != expected {
+            return Err(format!(
+                "its header gives {} bytes of data but it has {}",
+                expected,
+                data.len()
+            ));
+        }
+        let starts = |ends: usize, len: usize, what: &str| {
+            let starts: Vec<u32> = std::iter::once(0)
+                .chain((0..count).map(|i| word(ends + i * 4)))
+                .collect();
+            if starts.windows(2).any(|pair| pair[0] > pair[1])
+                || starts.last().copied() != Some(len as u32)
+            {
+                return Err(format!("its {} offsets are out of order", what));
+            }
+            Ok(starts)
+        };
+        let name_starts = starts(name_ends, names_len, "name")?;
+        let hash_starts = starts(hash_ends, hashes_len, "hash")?;
+        let hashes = data[hashes_start..]
+            .chunks_exact(8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        data.truncate(hashes_start);
+        data.drain(..names_start);
+        let names = String::from_utf8(data).map_err(|_| "its names are not UTF-8".to_owned())?;
+        if name_starts
+            .iter()
+            .any(|start| !names.is_char_boundary(*start as usize))
+        {
+            return Err("its name offsets split a character".to_owned());
+        }
+        let table = StockData {
+            names,
+            name_starts,
+            hash_starts,
+            hashes,
+        };
+        if (1..table.len()).any(|index| table.name_at(index - 1) >= table.name_at(index)) {
+            return Err("its names are not sorted".to_owned());
+        }
+        Ok(table)
+    }
+
+    /// Packs the entries of a table, sorted by name, with no name repeated, sorting the hashes
+    /// of each
+    fn from_sorted<I: IntoIterator<Item = (String, Vec<u64>)>>(entries: I) -> StockData {
+        let mut data = StockData {
+            names: String::new(),
+            name_starts: vec![0],
+            hash_starts: vec![0],
+            hashes: Vec::new(),
+        };
+        for (name, mut hashes) in entries {
+            hashes.sort_unstable();
+            hashes.dedup();
+            data.names.push_str(&name);
+            data.hashes.extend(hashes);
+            data.name_starts.push(data.names.len() as u32);
+            data.hash_starts.push(data.hashes.len() as u32);
+        }
+        data
+    }
+
+    /// Gets the number of files in the table
+    pub fn len(&self) -> usize {
+        self.name_starts.len() - 1
+    }
+
+    /// Gets the name of the file at an index in the table
+    fn name_at(&self, index: usize) -> &str {
+        &self.names[self.name_starts[index] as usize..self.name_starts[index + 1] as usize]
+    }
+
+    /// Gets the hashes of the file at an index in the table
+    fn hashes_at(&self, index: usize) -> &[u64] {
+        &self.hashes[self.hash_starts[index] as usize..self.hash_starts[index + 1] as usize]
+    }
+
+    /// Finds the hashes of a file by its canonical resource path
+    fn find(&self, canon: &str) -> Option<&[u64]> {
+        let index = self.lower_bound(canon);
+        if index < self.len() && self.name_at(index) == canon {
+            Some(self.hashes_at(index))
+        } else {
+            None
+        }
+    }
+
+    /// Finds the index of the first file whose name is not less than a key, by binary search
+    fn lower_bound(&self, key: &str) -> usize {
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.name_at(mid) < key {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+}
+
+/// Gets a hash table of stock BOTW 1.5.0 (for Wii U) or stock 1.6.0 (for Switch) game files and
+/// possible hashes for them. These include, where applicable, the original hash and variants
+/// created by processing unmodified files with common libraries and tools. The table is built
+/// fresh on each call; [`StockHashTable`] looks files up in the embedded data without one.
+///
+/// # Panics
+///
+/// Panics if the table for the platform is not embedded, see [`has_stock_hashes`], or is
+/// corrupt, with the reason. Use [`try_get_hash_table`] to handle those instead.
+#[inline]
+pub fn get_hash_table(platform: Platform) -> HashTable {
+    try_get_hash_table(platform).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Gets a hash table of stock game files and possible hashes for them, like
+/// [`get_hash_table`], but without panicking
+///
+/// # Returns
+///
+/// Returns a Result with the table, or [`TableError::NotEmbedded`] if the table for the
+/// platform is not embedded, or [`TableError::Corrupt`] if its embedded data is corrupt.
+pub fn try_get_hash_table(platform: Platform) -> Result<HashTable, TableError> {
+    let data = StockData::try_get(platform)?;
+    Ok((0..data.len())
+        .map(|index| (data.name_at(index), data.hashes_at(index).to_vec()))
+        .collect())
+}
+
+/// The files which differ between the Wii U and Switch stock tables, as found by
+/// [`diff_platforms`]. Each list is sorted by canonical resource path.
+#[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlatformDiff {
+    /// The files only in the Wii U game
+    pub wiiu_only: Vec<&'static str>,
+    /// The files only in the Switch game
+    pub switch_only: Vec<&'static str>,
+    /// The files in both games
+    pub shared: Vec<SharedFile>,
+}
+
+/// A file in both the Wii U and Switch stock tables
+#[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SharedFile {
+    /// The canonical resource path of the file
+    pub canon: &'static str,
+    /// Whether the file has a hash accepted on both platforms, which means the same content
+    /// can be used for both
+    pub same_content: bool,
+}
+
+/// Compares the stock Wii U and Switch tables, finding the files only in one of the games and
+/// whether the files in both have the same content. Both tables are already sorted, so they
+/// are walked side by side without building any maps. Example:
+///
+/// ```
+/// use botw_utils::hashes::diff_platforms;
+/// let diff = diff_platforms();
+/// println!("Wii U only: {}", diff.wiiu_only.len());
+/// println!("Switch only: {}", diff.switch_only.len());
+/// println!(
+///     "Shared: {} ({} with the same content)",
+///     diff.shared.len(),
+///     diff.shared.iter().filter(|file| file.same_content).count()
+/// );
+/// ```
+#[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+pub fn diff_platforms() -> PlatformDiff {
+    use core::cmp::Ordering;
+    let (wiiu, switch) = (
+        StockData::get(Platform::WiiU),
+        StockData::get(Platform::Switch),
+    );
+    let mut diff = PlatformDiff {
+        wiiu_only: Vec::new(),
+        switch_only: Vec::new(),
+        shared: Vec::new(),
+    };
+    let (mut u, mut nx) = (0, 0);
+    while u < wiiu.len() && nx < switch.len() {
+        let (u_name, nx_name) = (wiiu.name_at(u), switch.name_at(nx));
+        match u_name.cmp(nx_name) {
+            Ordering::Less => {
+                diff.wiiu_only.push(u_name);
+                u += 1;
+            }
+            Ordering::Greater => {
+                diff.switch_only.push(nx_name);
+                nx += 1;
+            }
+            Ordering::Equal => {
+                diff.shared.push(SharedFile {
+                    canon: u_name,
+                    same_content: sorted_overlap(wiiu.hashes_at(u), switch.hashes_at(nx)),
+                });
+                u += 1;
+                nx += 1;
+            }
+        }
+    }
+    diff.wiiu_only
+        .extend((u..wiiu.len()).map(|index| wiiu.name_at(index)));
+    diff.switch_only
+        .extend((nx..switch.len()).map(|index| switch.name_at(index)));
+    diff
+}
+
+/// Checks whether two sorted lists of hashes have one in common
+#[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+fn sorted_overlap(a: &[u64], b: &[u64]) -> bool {
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            core::cmp::Ordering::Less => i += 1,
+            core::cmp::Ordering::Greater => j += 1,
+            core::cmp::Ordering::Equal => return true,
+        }
+    }
+    false
+}
+
+/// Gets the stock files of every embedded table sorted by the CRC32 of their canonical resource
+/// paths, made the first time they are needed
+#[cfg(any(feature = "wiiu-data", feature = "switch-data"))]
+fn stock_crcs() -> &'static [(u32, &'static str)] {
+    static CRCS: OnceLock<Vec<(u32, &'static str)>> = OnceLock::new();
+    CRCS.get_or_init(|| {
+        let mut crcs: Vec<(u32, &'static str)> = [Platform::WiiU, Platform::Switch]
+            .iter()
+            .copied()
+            .filter(|platform| has_stock_hashes(*platform))
+            .flat_map(|platform| {
+                let data = StockData::get(platform);
+                (0..data.len()).map(move |index| {
+                    let canon = data.name_at(index);
+                    (crate::crc_of_canon(canon), canon)
+                })
+            })
+            .collect();
+        crcs.sort_unstable();
+        crcs.dedup();
+        crcs
+    })
+}
+
+/// Finds the stock file whose canonical resource path has a CRC32, as made by
+/// [`crc_of_canon`](crate::crc_of_canon), for reading BCML logs and other formats which name
+/// files by CRC. Files from the stock tables of every embedded platform are searched. A few
+/// stock paths share a CRC, and for those this returns `None` like for CRCs of no stock file,
+/// so use [`canons_for_crc`] to tell them apart. Example:
+///
+/// ```
+/// use botw_utils::{crc_of_canon, hashes::canon_for_crc};
+/// let crc = crc_of_canon("Actor/ActorInfo.product.byml");
+/// assert_eq!(canon_for_crc(crc), Some("Actor/ActorInfo.product.byml"));
+/// ```
+///
+/// # Arguments
+///
+/// * `crc` - The CRC32 of the canonical resource path
+#[cfg(any(feature = "wiiu-data", feature = "switch-data"))]
+pub fn canon_for_crc(crc: u32) -> Option<&'static str> {
+    let mut matches = canons_for_crc(crc);
+    match (matches.next(), matches.next()) {
+        (Some(canon), None) => Some(canon),
+        _ => None,
+    }
+}
+
+/// Finds every stock file whose canonical resource path has a CRC32, sorted by path, like
+/// [`canon_for_crc`] but also for CRCs shared by more than one file
+///
+/// # Arguments
+///
+/// * `crc` - The CRC32 of the canonical resource path
+#[cfg(any(feature = "wiiu-data", feature = "switch-data"))]
+pub fn canons_for_crc(crc: u32) -> impl Iterator<Item = &'static str> {
+    let crcs = stock_crcs();
+    let start = crcs.partition_point(|(other, _)| *other < crc);
+    crcs[start..]
+        .iter()
+        .take_while(move |(other, _)| *other == crc)
+        .map(|(_, canon)| *canon)
+}
+
+/// How a file compares to the stock game, as found by [`StockHashTable::check_file`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ModdedState {
+    /// The file matches one of its stock hashes
+    Vanilla,
+    /// The file is a stock file, but does not match any of its stock hashes
+    Modified {
+        /// The hash of the file's decompressed data, made with the table's [`Algorithm`], for
+        /// callers to cache
+        hash: u64,
+    },
+    /// The file is not present in the stock game
+    NewFile,
+    /// The file starts with a yaz0 header, but its data could not be decompressed
+    InvalidYaz0,
+    /// The file is a stock file, but its data is too short to be one, like from a truncated
+    /// download or an empty placeholder file
+    TooShort,
+}
+
+/// The size of the smallest header of any BOTW file format, shorter than which file data cannot
+/// be a stock file
+const MIN_FILE_SIZE: usize = 4;
+
+/// Error type describing why file data could not be hashed
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum HashError {
+    /// The data has a yaz0 header but could not be decompressed, with the reason why
+    InvalidYaz0(String),
+}
+
+impl fmt::Display for HashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashError::InvalidYaz0(reason) => {
+                write!(f, "Could not decompress yaz0 data: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HashError {}
+
+/// Error type describing why a hash table could not be loaded or is not valid
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TableError {
+    /// The table could not be read, with the kind of I/O error
+    Io(io::ErrorKind),
+    /// The table is not JSON in the schema of the stock tables, an object of canonical resource
+    /// paths to arrays of hashes, with the reason why
+    InvalidJson(String),
+    /// The stock table for the platform is not embedded in this build, see
+    /// [`has_stock_hashes`]
+    NotEmbedded(Platform),
+    /// The embedded stock table for the platform is corrupt, like from a broken build, with
+    /// what is wrong with it
+    Corrupt { platform: Platform, reason: String },
+    /// The table has no files, or a file which is not a canonical resource path or has no
+    /// valid hashes, as found by [`StockHashTable::validate`]
+    Invalid(String),
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableError::Io(kind) => write!(f, "Could not read hash table: {:?}", kind),
+            TableError::InvalidJson(reason) => write!(f, "Invalid hash table JSON: {}", reason),
+            TableError::NotEmbedded(platform) => write!(
+                f,
+                "The stock hash table for {} is not embedded in this build, enable the `{}` \
+                 feature of botw-utils to use it",
+                platform,
+                match platform {
+                    Platform::WiiU => "wiiu-data",
+                    Platform::Switch => "switch-data",
+                }
+            ),
+            TableError::Corrupt { platform, reason } => write!(
+                f,
+                "The embedded stock hash table for {} is corrupt: {}",
+                platform, reason
+            ),
+            TableError::Invalid(reason) => write!(f, "Invalid hash table: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
+
+/// The algorithm a [`StockHashTable`] hashes decompressed file data with. The embedded stock
+/// tables use [`Xxh64`](Algorithm::Xxh64), and custom tables can declare another one, see
+/// [`StockHashTable::from_json`], so inventories made with other tools can be reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Algorithm {
+    /// The xxHash64 digest with a seed of 0, which the stock tables use
+    #[default]
+    Xxh64,
+    /// The 64-bit XXH3 digest with a seed of 0
+    Xxh3,
+    /// The CRC32 of the data, the same CRC as zlib's
+    Crc32,
+}
+
+impl Algorithm {
+    /// Every algorithm
+    pub const ALL: [Algorithm; 3] = [Algorithm::Xxh64, Algorithm::Xxh3, Algorithm::Crc32];
+
+    /// Gets the name of the algorithm in table files, like `xxh64`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Algorithm::Xxh64 => "xxh64",
+            Algorithm::Xxh3 => "xxh3",
+            Algorithm::Crc32 => "crc32",
+        }
+    }
+
+    /// Hashes data with the algorithm as it is, without decompressing it
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The data to hash
+    pub fn hash(self, data: &[u8]) -> u64 {
+        match self {
+            Algorithm::Xxh64 => {
+                let mut hasher = XxHash64::with_seed(0);
+                hasher.write(data);
+                hasher.finish()
+            }
+            Algorithm::Xxh3 => crate::xxh3::hash64(data),
+            Algorithm::Crc32 => crate::crc::crc32(data) as u64,
+        }
+    }
+
+    /// Hashes file data with the algorithm like [`compute_file_hash`] does with xxHash64, after
+    /// decompressing it if it is yaz0 compressed. Example:
+    ///
+    /// ```
+    /// use botw_utils::hashes::Algorithm;
+    /// assert_eq!(Algorithm::Crc32.compute_file_hash(b"123456789"), Ok(0xCBF4_3926));
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The binary data for the file
+    ///
+    /// # Returns
+    ///
+    /// Returns a Result with the hash, or a [`HashError`] if the data has a yaz0 header but
+    /// could not be decompressed.
+    pub fn compute_file_hash(self, data: &[u8]) -> Result<u64, HashError> {
+        self.compute_file_hash_with_buf(data, &mut Vec::new())
+    }
+
+    /// Hashes file data with the algorithm like [`compute_file_hash_with_buf`] does with
+    /// xxHash64, decompressing yaz0 data into a scratch buffer which can be reused between calls
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The binary data for the file
+    /// * `buf` - The buffer to decompress into. Its contents are replaced.
+    ///
+    /// # Returns
+    ///
+    /// Returns a Result with the hash, or a [`HashError`] if the data has a yaz0 header but
+    /// could not be decompressed.
+    pub fn compute_file_hash_with_buf(
+        self,
+        data: &[u8],
+        buf: &mut Vec<u8>,
+    ) -> Result<u64, HashError> {
+        if data.starts_with(b"Yaz0") {
+            let invalid = |e: roead::Error| HashError::InvalidYaz0(e.to_string());
+            let header = get_header(data).ok_or_else(|| {
+                invalid(roead::Error::InvalidData("Missing or corrupt Yaz0 header"))
+            })?;
+            buf.clear();
+            buf.resize(header.uncompressed_size as usize, 0);
+            let size = decompress_into(data, buf.as_mut_slice()).map_err(invalid)?;
+            Ok(self.hash(&buf[..size]))
+        } else {
+            Ok(self.hash(data))
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error type for a string which does not name an [`Algorithm`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseAlgorithmError {
+    /// The string which could not be parsed
+    pub input: String,
+}
+
+impl fmt::Display for ParseAlgorithmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unrecognized hash algorithm `{}`, expected `xxh64`, `xxh3`, or `crc32`",
+            self.input
+        )
+    }
+}
+
+impl std::error::Error for ParseAlgorithmError {}
+
+impl FromStr for Algorithm {
+    type Err = ParseAlgorithmError;
+
+    fn from_str(name: &str) -> Result<Algorithm, ParseAlgorithmError> {
+        Algorithm::ALL
+            .iter()
+            .find(|known| known.as_str().eq_ignore_ascii_case(name))
+            .copied()
+            .ok_or_else(|| ParseAlgorithmError {
+                input: name.to_owned(),
+            })
+    }
+}
+
+/// Hashes file data the way the stock hash tables do, as the xxHash64 digest with a seed of 0 of
+/// the data, after decompressing it if it is yaz0 compressed. Use [`Algorithm::compute_file_hash`]
+/// for tables with another algorithm. Example:
+///
+/// ```
+/// use botw_utils::hashes::compute_file_hash;
+/// assert_eq!(compute_file_hash(b"").unwrap(), 0xEF46_DB37_51D8_E999);
+/// assert!(compute_file_hash(b"Yaz0 broken").is_err());
+/// ```
+///
+/// # Arguments
+///
+/// * `data` - The binary data for the file
+///
+/// # Returns
+///
+/// Returns a Result with the hash, or a [`HashError`] if the data has a yaz0 header but could
+/// not be decompressed.
+pub fn compute_file_hash(data: &[u8]) -> Result<u64, HashError> {
+    Algorithm::Xxh64.compute_file_hash(data)
+}
+
+/// Hashes file data like [`compute_file_hash`], decompressing yaz0 data into a scratch buffer
+/// which can be reused between calls, so hashing many files does not allocate for each one
+///
+/// # Arguments
+///
+/// * `data` - The binary data for the file
+/// * `buf` - The buffer to decompress into. Its contents are replaced.
+///
+/// # Returns
+///
+/// Returns a Result with the hash, or a [`HashError`] if the data has a yaz0 header but could
+/// not be decompressed.
+pub fn compute_file_hash_with_buf(data: &[u8], buf: &mut Vec<u8>) -> Result<u64, HashError> {
+    Algorithm::Xxh64.compute_file_hash_with_buf(data, buf)
+}
+
+/// Checks the data of a stock file against its accepted hashes, made with an algorithm. Data
+/// with a yaz0 header which cannot be decompressed gives the [`HashError`] saying why, for
+/// callers which report it.
+fn check_data(
+    hashes: &[u64],
+    algorithm: Algorithm,
+    data: &[u8],
+    buf: &mut Vec<u8>,
+) -> Result<ModdedState, HashError> {
+    if data.len() < MIN_FILE_SIZE {
+        return Ok(ModdedState::TooShort);
+    }
+    let hash = algorithm.compute_file_hash_with_buf(data, buf)?;
+    if hashes.binary_search(&hash).is_ok() {
+        Ok(ModdedState::Vanilla)
+    } else {
+        Ok(ModdedState::Modified { hash })
+    }
+}
+
+/// How a [`StockHashTable`] stores its files. Tables start out packed, either borrowing an
+/// embedded stock table or sharing a loaded one, and are moved into a map the first time they
+/// are changed, borrowing the names of embedded stock files instead of copying them, so that
+/// changed tables are still looked up with a single probe. Loaded and changed data is kept
+/// behind an [`Arc`] so cloning a table never copies it.
+#[derive(Debug, Clone)]
+enum Storage {
+    Stock(&'static StockData),
+    Loaded(Arc<StockData>),
+    Map(Arc<HashMap<Cow<'static, str>, Box<[u64]>>>),
+}
+
+/// Indexes of the names in a [`StockHashTable`], built the first time they are needed and
+/// dropped when the table is changed. Packed tables are already sorted, so only tables moved
+/// into a map need a sorted copy of their names.
+#[derive(Debug, Clone, Default)]
+struct Indexes {
+    sorted: OnceLock<Arc<[Cow<'static, str>]>>,
+    /// The positions in sorted order of the files with each final extension
+    by_extension: OnceLock<Arc<HashMap<String, Vec<u32>>>>,
+    /// The position in sorted order of the file with each lowercased name, for tables which
+    /// look files up ignoring case
+    by_lowercase: OnceLock<Arc<HashMap<String, u32>>>,
+}
+
+/// Adds a hash to the sorted accepted hashes of a file, unless it is already one of them
+fn accept(accepted: &mut Box<[u64]>, hash: u64) {
+    if let Err(index) = accepted.binary_search(&hash) {
+        let mut hashes = core::mem::take(accepted).into_vec();
+        hashes.insert(index, hash);
+        *accepted = hashes.into_boxed_slice();
+    }
+}
+
+/// An iterator over the files in a [`StockHashTable`] and their accepted hashes, made by
+/// [`StockHashTable::iter`]
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    inner: IterInner<'a>,
+}
+
+/// The storage a [`Iter`] is going through
+#[derive(Debug, Clone)]
+enum IterInner<'a> {
+    Packed {
+        data: &'a StockData,
+        indices: Range<usize>,
+    },
+    Map(hash_map::Iter<'a, Cow<'static, str>, Box<[u64]>>),
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, &'a [u64]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            IterInner::Packed { data, indices } => indices
+                .next()
+                .map(|index| (data.name_at(index), data.hashes_at(index))),
+            IterInner::Map(iter) => iter
+                .next()
+                .map(|(canon, hashes)| (canon.as_ref(), &**hashes)),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            IterInner::Packed { indices, .. } => indices.size_hint(),
+            IterInner::Map(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl<'a> IntoIterator for &'a StockHashTable {
+    type Item = (&'a str, &'a [u64]);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+/// Where the data of a [`StockHashTable`] came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TableSource {
+    /// One of the embedded stock tables, for a version of the game on a platform
+    Stock {
+        platform: Platform,
+        version: GameVersion,
+    },
+    /// A custom table loaded from JSON
+    Custom,
+}
+
+/// Information about the data of a [`StockHashTable`], given by
+/// [`StockHashTable::metadata`] and [`data_versions`]. The embedded tables know their platform,
+/// version, and number of files, and how they were made if `data/` says so. Custom tables only
+/// know what the JSON they were loaded from declares, beside their number of files.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableMetadata {
+    /// The platform the data is for
+    pub platform: Option<Platform>,
+    /// The version of the game the data is for
+    pub version: Option<GameVersion>,
+    /// The number of files in the data, before any changes made to the table
+    pub files: usize,
+    /// When the data was generated, as written by the tool which made it
+    pub generated: Option<String>,
+    /// The name and version of the tool which generated the data
+    pub generator: Option<String>,
+}
+
+impl TableMetadata {
+    /// Reads metadata from a JSON object with any of `platform`, `version`, `files`,
+    /// `generated`, and `generator`, ignoring other keys, or gives what is wrong with it
+    fn from_json(value: &serde_json::Value) -> Result<TableMetadata, String> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| "the metadata is not an object".to_owned())?;
+        let text = |key: &str| match object.get(key) {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(serde_json::Value::String(text)) => Ok(Some(text.clone())),
+            Some(_) => Err(format!("{} in the metadata is not a string", key)),
+        };
+        let platform = match text("platform")? {
+            Some(name) => Some(
+                name.parse()
+                    .map_err(|_| format!("unknown platform {:?} in the metadata", name))?,
+            ),
+            None => None,
+        };
+        let version = match text("version")? {
+            Some(number) => Some(
+                number
+                    .parse()
+                    .map_err(|_| format!("unknown game version {:?} in the metadata", number))?,
+            ),
+            None => None,
+        };
+        Ok(TableMetadata {
+            platform,
+            version,
+            files: object
+                .get("files")
+                .and_then(serde_json::Value::as_u64)
+                .unwrap_or(0) as usize,
+            generated: text("generated")?,
+            generator: text("generator")?,
+        })
+    }
+}
+
+/// A struct wrapping a hash table for stock BOTW files with a few convenience methods. Cloning
+/// a table is cheap, since clones share its data until one of them is changed, and tables are
+/// `Send` and `Sync`, so one table can be cloned into as many threads as needed.
+#[derive(Clone)]
+pub struct StockHashTable {
+    source: TableSource,
+    storage: Storage,
+    indexes: Indexes,
+    case_insensitive: bool,
+    algorithm: Algorithm,
+    metadata: Arc<TableMetadata>,
+}
+
+/// Shows where the table came from and its [`metadata`](StockHashTable::metadata), so a table
+/// logged in a bug report says which data it was
+impl fmt::Debug for StockHashTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StockHashTable")
+            .field("source", &self.source)
+            .field("files", &self.len())
+            .field("metadata", &*self.metadata)
+            .finish()
+    }
+}
+
+/// Tables are equal when they have the same algorithm, files, and hashes, wherever they came
+/// from
+impl PartialEq for StockHashTable {
+    fn eq(&self, other: &StockHashTable) -> bool {
+        if self.algorithm != other.algorithm {
+            return false;
+        }
+        match (self.packed(), other.packed()) {
+            (Some(data), Some(other_data)) => data == other_data,
+            _ => {
+                self.len() == other.len()
+                    && self
+                        .get_stock_files()
+                        .all(|canon| self.find(canon) == other.find(canon))
+            }
+        }
+    }
+}
+
+impl Eq for StockHashTable {}
+
+/// Gets the name of the environment variable which can give a JSON file to use instead of the
+/// embedded stock table for a platform
+fn override_var(platform: Platform) -> &'static str {
+    match platform {
+        Platform::WiiU => "BOTW_UTILS_HASHES_WIIU",
+        Platform::Switch => "BOTW_UTILS_HASHES_NX",
+    }
+}
+
+/// Gets the JSON file the environment gives to use instead of the embedded stock table for a
+/// platform, if there is one
+fn override_path(platform: Platform) -> Option<PathBuf> {
+    std::env::var_os(override_var(platform))
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Reads a table which declares its algorithm or metadata, an object of the files and their
+/// hashes as `hashes` beside the name of the algorithm as `algorithm`, the metadata as
+/// `metadata`, or both, or gives `None` if the JSON is not one
+fn declared_table(json: &str) -> Option<Result<StockHashTable, TableError>> {
+    let mut value: serde_json::Map<String, serde_json::Value> = serde_json::from_str(json).ok()?;
+    let algorithm = match value.get("algorithm") {
+        Some(name) => {
+            let name = name.as_str()?;
+            match name.parse() {
+                Ok(algorithm) => algorithm,
+                Err(ParseAlgorithmError { .. }) => {
+                    return Some(Err(TableError::InvalidJson(format!(
+                        "unknown hash algorithm {:?}",
+                        name
+                    ))))
+                }
+            }
+        }
+        None => Algorithm::Xxh64,
+    };
+    if value
+        .keys()
+        .any(|key| !matches!(key.as_str(), "algorithm" | "hashes" | "metadata"))
+    {
+        return None;
+    }
+    let metadata = match value.get("metadata").map(TableMetadata::from_json) {
+        Some(Ok(metadata)) => metadata,
+        Some(Err(reason)) => return Some(Err(TableError::InvalidJson(reason))),
+        None => TableMetadata::default(),
+    };
+    let table = serde_json::from_value(value.remove("hashes")?)
+        .map_err(|e| TableError::InvalidJson(e.to_string()));
+    Some(table.map(|table| StockHashTable::loaded(algorithm, table, metadata)))
+}
+
+impl StockHashTable {
+    /// Constructs StockHashTable instance for the specified platform. The embedded hash data
+    /// for the platform is unpacked the first time it is needed and then shared by every table,
+    /// taking several megabytes until the process exits, so constructing more tables is cheap.
+    ///
+    /// If the `BOTW_UTILS_HASHES_WIIU` or `BOTW_UTILS_HASHES_NX` environment variable is set
+    /// for the platform, the JSON file it gives is loaded instead of the embedded data, like
+    /// with [`new_with_source`](StockHashTable::new_with_source). The file is loaded again by
+    /// every call, so use [`get`](StockHashTable::get) to share one table.
+    ///
+    /// # Arguments
+    ///
+    /// * `platform` - Specifies whether to use a Wii U 1.5.0 or Switch 1.6.0 hash table
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table for the platform is not embedded, see [`has_stock_hashes`], or if
+    /// the file given by the environment cannot be loaded.
+    #[inline]
+    pub fn new(platform: Platform) -> StockHashTable {
+        StockHashTable::new_with_source(platform, None).unwrap_or_else(|e| {
+            match override_path(platform) {
+                Some(path) => panic!(
+                    "Could not load the {} hash table {} given by {}: {}",
+                    platform,
+                    path.display(),
+                    override_var(platform),
+                    e
+                ),
+                None => panic!("{}", e),
+            }
+        })
+    }
+
+    /// Constructs a StockHashTable instance for the specified platform like
+    /// [`new`](StockHashTable::new), but from a JSON file in the schema of the stock tables if
+    /// one is given, for testing updated tables or using patched ones. The table used is the
+    /// first of:
+    ///
+    /// 1. The file given as `source`
+    /// 2. The file given by the `BOTW_UTILS_HASHES_WIIU` or `BOTW_UTILS_HASHES_NX` environment
+    ///    variable for the platform
+    /// 3. The embedded stock table for the platform
+    ///
+    /// A file which cannot be loaded is an error, rather than falling back to the next source.
+    /// Tables loaded from a file are still reported as stock tables by
+    /// [`source`](StockHashTable::source).
+    ///
+    /// # Arguments
+    ///
+    /// * `platform` - The platform the table is for
+    /// * `source` - The JSON file to load the table from, if any
+    ///
+    /// # Returns
+    ///
+    /// Returns a Result with the table, or a [`TableError`] if the file could not be read or is
+    /// not a valid table, or the embedded table is missing or corrupt.
+    pub fn new_with_source(
+        platform: Platform,
+        source: Option<&Path>,
+    ) -> Result<StockHashTable, TableError> {
+        let version = GameVersion::latest(platform);
+        let (storage, algorithm, metadata) = match source
+            .map(Path::to_path_buf)
+            .or_else(|| override_path(platform))
+        {
+            Some(path) => {
+                let table = StockHashTable::from_path(path)?;
+                let mut metadata = TableMetadata::clone(&table.metadata);
+                metadata.platform.get_or_insert(platform);
+                metadata.version.get_or_insert(version);
+                (table.storage, table.algorithm, Arc::new(metadata))
+            }
+            None => (
+                Storage::Stock(StockData::try_get(platform)?),
+                Algorithm::Xxh64,
+                embedded_metadata(platform).clone(),
+            ),
+        };
+        Ok(StockHashTable {
+            source: TableSource::Stock { platform, version },
+            storage,
+            indexes: Indexes::default(),
+            case_insensitive: false,
+            algorithm,
+            metadata,
+        })
+    }
+
+    /// Loads a custom hash table from JSON in the same schema as the stock tables, an object of
+    /// canonical resource paths to arrays of accepted hashes, for builds of the game the stock
+    /// tables do not cover. Tables hashed with another [`Algorithm`] than the stock tables'
+    /// xxHash64 wrap the object as `hashes` beside the name of the algorithm, like
+    /// `{"algorithm": "crc32", "hashes": {...}}`. The object can also carry
+    /// [`metadata`](StockHashTable::metadata) about the table as `metadata`, an object with any
+    /// of `platform`, `version`, `generated`, and `generator` as strings. Example:
+    ///
+    /// ```
+    /// use botw_utils::hashes::{Algorithm, StockHashTable};
+    /// let table = StockHashTable::from_json(r#"{"Pack/Bootup.pack": [1, 2]}"#).unwrap();
+    /// assert_eq!(table.get_hashes("Pack/Bootup.pack"), Some(&[1, 2][..]));
+    /// assert!(table.is_file_new("Pack/TitleBG.pack"));
+    /// let crcs = StockHashTable::from_json(
+    ///     r#"{"algorithm": "crc32", "hashes": {"Pack/Bootup.pack": [639479525]}}"#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(crcs.algorithm(), Algorithm::Crc32);
+    /// assert!(!crcs.is_file_modded("Pack/Bootup.pack", b"1234567890", true));
+    /// let described = StockHashTable::from_json(
+    ///     r#"{"metadata": {"version": "1.2.0"}, "hashes": {"Pack/Bootup.pack": [1]}}"#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(described.metadata().version.unwrap().as_str(), "1.2.0");
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - The JSON text of the table
+    ///
+    /// # Returns
+    ///
+    /// Returns a Result with the table, or a [`TableError`] if the JSON is not a valid table.
+    pub fn from_json(json: &str) -> Result<StockHashTable, TableError> {
+        match serde_json::from_str(json) {
+            Ok(table) => Ok(StockHashTable::loaded(
+                Algorithm::Xxh64,
+                table,
+                TableMetadata::default(),
+            )),
+            Err(e) => {
+                declared_table(json).unwrap_or_else(|| Err(TableError::InvalidJson(e.to_string())))
+            }
+        }
+    }
+
+    /// Makes a custom table of files and their hashes made with an algorithm, counting its
+    /// files into its metadata
+    fn loaded(
+        algorithm: Algorithm,
+        table: BTreeMap<String, Vec<u64>>,
+        metadata: TableMetadata,
+    ) -> StockHashTable {
+        StockHashTable {
+            source: TableSource::Custom,
+            metadata: Arc::new(TableMetadata {
+                files: table.len(),
+                ..metadata
+            }),
+            storage: Storage::Loaded(Arc::new(StockData::from_sorted(table))),
+            indexes: Indexes::default(),
+            case_insensitive: false,
+            algorithm,
+        }
+    }
+
+    /// Loads a custom hash table from a reader, like [`from_json`](StockHashTable::from_json)
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The reader to read the JSON text of the table from
+    ///
+    /// # Returns
+    ///
+    /// Returns a Result with the table, or a [`TableError`] if it could not be read or is not a
+    /// valid table.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<StockHashTable, TableError> {
+        let mut json = String::new();
+        reader
+            .read_to_string(&mut json)
+            .map_err(|e| TableError::Io(e.kind()))?;
+        StockHashTable::from_json(&json)
+    }
+
+    /// Loads a custom hash table from a JSON file, like [`from_json`](StockHashTable::from_json)
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the JSON file
+    ///
+    /// # Returns
+    ///
+    /// Returns a Result with the table, or a [`TableError`] if the file could not be read or is
+    /// not a valid table.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<StockHashTable, TableError> {
+        let json = std::fs::read_to_string(path).map_err(|e| TableError::Io(e.kind()))?;
+        StockHashTable::from_json(&json)
+    }
+
+    /// Constructs a StockHashTable instance for a specific version of the game. Only the latest
+    /// version of each platform has an embedded table so far, since the tables for older
+    /// versions have to be made from dumps of them, so every other version gives None for now.
+    /// Example:
+    ///
+    #[cfg_attr(feature = "wiiu-data", doc = "```")]
+    #[cfg_attr(not(feature = "wiiu-data"), doc = "```ignore")]
+    /// use botw_utils::hashes::{GameVersion, Platform, StockHashTable};
+    /// let table = StockHashTable::for_version(Platform::WiiU, GameVersion::V1_5_0).unwrap();
+    /// assert_eq!(table, StockHashTable::new(Platform::WiiU));
+    /// assert!(StockHashTable::for_version(Platform::WiiU, GameVersion::V1_6_0).is_none());
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `platform` - The platform of the game
+    /// * `version` - The version of the game
+    ///
+    /// # Returns
+    ///
+    /// Returns an Option with the table, or None if no table for that version is embedded,
+    /// either because none ships with the crate or because its feature is off.
+    pub fn for_version(platform: Platform, version: GameVersion) -> Option<StockHashTable> {
+        StockData::for_version(platform, version).map(|data| StockHashTable {
+            source: TableSource::Stock { platform, version },
+            storage: Storage::Stock(data),
+            indexes: Indexes::default(),
+            case_insensitive: false,
+            algorithm: Algorithm::Xxh64,
+            metadata: embedded_metadata(platform).clone(),
+        })
+    }
+
+    /// Gets the stock hash table for a platform, shared by the whole process, for code which
+    /// wants a `'static` table to hold on to. It looks up the same data as a table made with
+    /// [`new`](StockHashTable::new).
+    ///
+    /// # Arguments
+    ///
+    /// * `platform` - Specifies whether to use a Wii U 1.5.0 or Switch 1.6.0 hash table
+    pub fn get(platform: Platform) -> &'static StockHashTable {
+        static WIIU: OnceLock<StockHashTable> = OnceLock::new();
+        static SWITCH: OnceLock<StockHashTable> = OnceLock::new();
+        let cell = match platform {
+            Platform::WiiU => &WIIU,
+            Platform::Switch => &SWITCH,
+        };
+        cell.get_or_init(|| StockHashTable::new(platform))
+    }
+
+    /// Gets where the table's data came from
+    pub fn source(&self) -> TableSource {
+        self.source
+    }
+
+    /// Gets what is known about the table's data, like the game version it is for and when it
+    /// was generated. Its number of files is counted when the table is made, so it does not
+    /// change when files are added or removed. Example:
+    ///
+    #[cfg_attr(feature = "switch-data", doc = "```")]
+    #[cfg_attr(not(feature = "switch-data"), doc = "```ignore")]
+    /// use botw_utils::hashes::{GameVersion, Platform, StockHashTable};
+    /// let table = StockHashTable::new(Platform::Switch);
+    /// assert_eq!(table.metadata().version, Some(GameVersion::V1_6_0));
+    /// assert_eq!(table.metadata().files, table.len());
+    /// ```
+    pub fn metadata(&self) -> &TableMetadata {
+        &self.metadata
+    }
+
+    /// Gets the algorithm the table's hashes are made with, which is
+    /// [`Xxh64`](Algorithm::Xxh64) for the stock tables
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Gets the platform the table is for, or None for a custom table. Example:
+    ///
+    #[cfg_attr(feature = "wiiu-data", doc = "```")]
+    #[cfg_attr(not(feature = "wiiu-data"), doc = "```ignore")]
+    /// use botw_utils::hashes::{Platform, StockHashTable};
+    /// assert_eq!(StockHashTable::new(Platform::WiiU).platform(), Some(Platform::WiiU));
+    /// assert_eq!(StockHashTable::from_json("{}").unwrap().platform(), None);
+    /// ```
+    pub fn platform(&self) -> Option<Platform> {
+        match self.source {
+            TableSource::Stock { platform, .. } => Some(platform),
+            TableSource::Custom => None,
+        }
+    }
+
+    /// Gets the number of files in the table
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Map(map) => map.len(),
+            _ => self.packed().map_or(0, StockData::len),
+        }
+    }
+
+    /// Checks if the table has no files in it
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks that the table looks like a hash table of game files: that it has files, that
+    /// each is a valid canonical resource path, and that each has hashes, none of them zero.
+    /// The tests of this crate check the stock tables like this, so this is mostly for tables
+    /// loaded from JSON.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`TableError::Invalid`] with the first problem found, if there is one
+    pub fn validate(&self) -> Result<(), TableError> {
+        if self.is_empty() {
+            return Err(TableError::Invalid("it has no files".to_owned()));
+        }
+        for (canon, hashes) in self {
+            crate::validate_canon_path(canon).map_err(|e| TableError::Invalid(e.to_string()))?;
+            if hashes.is_empty() {
+                return Err(TableError::Invalid(format!("{} has no hashes", canon)));
+            }
+            if hashes.contains(&0) {
+                return Err(TableError::Invalid(format!("{} has a hash of 0", canon)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Iterates the files in the table by their canonical resource paths, with the hashes
+    /// accepted for each one. Example:
+    ///
+    #[cfg_attr(feature = "wiiu-data", doc = "```")]
+    #[cfg_attr(not(feature = "wiiu-data"), doc = "```ignore")]
+    /// use botw_utils::hashes::{Platform, StockHashTable};
+    /// use std::collections::BTreeMap;
+    /// let table = StockHashTable::new(Platform::WiiU);
+    /// let sorted: BTreeMap<&str, &[u64]> = table.iter().collect();
+    /// assert_eq!(sorted.len(), table.len());
+    /// assert_eq!(
+    ///     sorted["Actor/ModelList/DgnMrgPrt_Dungeon023.bmodellist"],
+    ///     [3_305_211_212_481_695_363, 6_042_644_272_755_124_234]
+    /// );
+    /// ```
+    pub fn iter(&self) -> Iter<'_> {
+        let inner = match &self.storage {
+            Storage::Map(map) => IterInner::Map(map.iter()),
+            _ => {
+                let data = self.packed().unwrap();
+                IterInner::Packed {
+                    data,
+                    indices: 0..data.len(),
+                }
+            }
+        };
+        Iter { inner }
+    }
+
+    /// Gets the packed data of the table, unless it has been moved into a map
+    fn packed(&self) -> Option<&StockData> {
+        match &self.storage {
+            Storage::Stock(data) => Some(data),
+            Storage::Loaded(data) => Some(data),
+            Storage::Map(..) => None,
+        }
+    }
+
+    /// Iterates the files in the stock hash table by their canonical resource paths.
+    #[inline]
+    pub fn get_stock_files(&self) -> impl Iterator<Item = &str> {
+        self.iter().map(|(canon, _)| canon)
+    }
+
+    /// Iterates the files in a folder of the table, and the folders inside it, by their
+    /// canonical resource paths, in sorted order. The files are found by a binary search
+    /// instead of checking every file in the table. Example:
+    ///
+    #[cfg_attr(feature = "switch-data", doc = "```")]
+    #[cfg_attr(not(feature = "switch-data"), doc = "```ignore")]
+    /// use botw_utils::hashes::{Platform, StockHashTable};
+    /// let table = StockHashTable::new(Platform::Switch);
+    /// let files: Vec<&str> = table.files_under("Map/MainField/A-1").collect();
+    /// assert!(files.contains(&"Map/MainField/A-1/A-1_Static.mubin"));
+    /// assert!(files.iter().all(|canon| canon.starts_with("Map/MainField/A-1/")));
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - The folder to look in, with or without a trailing `/`. An empty prefix
+    ///   covers every file.
+    pub fn files_under<'a>(&'a self, prefix: &str) -> impl Iterator<Item = &'a str> + 'a {
+        let folder = match prefix {
+            "" => String::new(),
+            _ => [prefix.trim_end_matches('/'), "/"].concat(),
+        };
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.sorted_name(mid) < folder.as_str() {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        (low..self.len())
+            .map(move |index| self.sorted_name(index))
+            .take_while(move |canon| canon.starts_with(&folder))
+    }
+
+    /// Iterates the files in the table with an extension, by their canonical resource paths, in
+    /// sorted order. The extension is the last one of the file name, so `.Tex1.bfres` textures
+    /// count as `bfres`. The files are grouped by extension the first time this is called, so
+    /// later queries do not check every file in the table. Example:
+    ///
+    #[cfg_attr(feature = "switch-data", doc = "```")]
+    #[cfg_attr(not(feature = "switch-data"), doc = "```ignore")]
+    /// use botw_utils::hashes::{Platform, StockHashTable};
+    /// let table = StockHashTable::new(Platform::Switch);
+    /// let physics: Vec<&str> = table.files_with_extension(".sbphysics").collect();
+    /// assert!(physics.contains(&"Actor/Physics/FldObj_MountainSheikerWall_A_06.bphysics"));
+    /// assert_eq!(physics, table.files_with_extension("bphysics").collect::<Vec<_>>());
+    /// let ai_programs = table
+    ///     .files_with_extension("baiprog")
+    ///     .filter(|canon| canon.starts_with("Actor/AIProgram/"));
+    /// assert!(ai_programs.count() > 100);
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `ext` - The extension, with or without a leading dot. The `s` prefixed spelling of a
+    ///   compressed extension, like `sbfres`, finds the same files as the canonical one, unless
+    ///   files have that spelling in the table themselves, like `stera`.
+    pub fn files_with_extension<'a>(&'a self, ext: &str) -> impl Iterator<Item = &'a str> + 'a {
+        let ext = ext.trim_start_matches('.');
+        let index = self.indexes.by_extension.get_or_init(|| {
+            let mut index: HashMap<String, Vec<u32>> = HashMap::new();
+            for position in 0..self.len() {
+                let canon = self.sorted_name(position);
+                let name = &canon[canon.rfind('/').map_or(0, |i| i + 1)..];
+                if let Some(dot) = name.rfind('.') {
+                    index
+                        .entry(name[dot + 1..].to_owned())
+                        .or_default()
+                        .push(position as u32);
+                }
+            }
+            Arc::new(index)
+        });
+        let files = index.get(ext).or_else(|| match ext.strip_prefix('s') {
+            Some(uncompressed) if crate::extensions::is_compressible_ext(uncompressed) => {
+                index.get(uncompressed)
+            }
+            _ => None,
+        });
+        files
+            .into_iter()
+            .flatten()
+            .map(move |position| self.sorted_name(*position as usize))
+    }
+
+    /// Gets the name of the file at a position in the table in sorted order. A table which has
+    /// been moved into a map has its names sorted the first time this is called.
+    fn sorted_name(&self, position: usize) -> &str {
+        match &self.storage {
+            Storage::Map(map) => {
+                let sorted = self.indexes.sorted.get_or_init(|| {
+                    let mut keys: Vec<_> = map.keys().cloned().collect();
+                    keys.sort_unstable();
+                    keys.into()
+                });
+                &sorted[position]
+            }
+            _ => self.packed().unwrap().name_at(position),
+        }
+    }
+
+    /// Gets an owend list of the canonical resource paths for all files in the stock hash table.
+    #[inline]
+    pub fn list_stock_files(&self) -> Vec<String> {
+        self.get_stock_files().map(str::to_owned).collect()
+    }
+
+    /// Sets whether files are looked up ignoring the case of their names, for mods whose paths
+    /// have been lowercased or otherwise case mangled by Windows tools. A name which matches a
+    /// file exactly always finds that file; otherwise, when files differ only by case, the first
+    /// of them in sorted order is found. An index of lowercased names is built when this is
+    /// turned on, so tables which do not ignore case do not pay for it. Example:
+    ///
+    #[cfg_attr(feature = "wiiu-data", doc = "```")]
+    #[cfg_attr(not(feature = "wiiu-data"), doc = "```ignore")]
+    /// use botw_utils::hashes::{Platform, StockHashTable};
+    /// let table = StockHashTable::new(Platform::WiiU).case_insensitive(true);
+    /// assert!(!table.is_file_new("actor/pack/enemy_lizalfos_senior.bactorpack"));
+    /// assert_eq!(
+    ///     table.stock_name("actor/pack/enemy_lizalfos_senior.bactorpack"),
+    ///     Some("Actor/Pack/Enemy_Lizalfos_Senior.bactorpack")
+    /// );
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `insensitive` - Whether to ignore case
+    pub fn case_insensitive(mut self, insensitive: bool) -> StockHashTable {
+        self.case_insensitive = insensitive;
+        if insensitive {
+            self.lowercase_index();
+        }
+        self
+    }
+
+    /// Gets the name a file has in the table, which differs from the name given only when the
+    /// table ignores case
+    ///
+    /// # Arguments
+    ///
+    /// * `canon` - The canonical resource name of the file
+    ///
+    /// # Returns
+    ///
+    /// Returns an Option with the name of the file in the table, or None if it is not in it.
+    pub fn stock_name<'a>(&'a self, canon: &'a str) -> Option<&'a str> {
+        if self.find_exact(canon).is_some() {
+            return Some(canon);
+        }
+        if !self.case_insensitive {
+            return None;
+        }
+        self.lowercase_index()
+            .get(&canon.to_ascii_lowercase())
+            .map(|position| self.sorted_name(*position as usize))
+    }
+
+    /// Gets the index of lowercased names, building it the first time
+    fn lowercase_index(&self) -> &HashMap<String, u32> {
+        self.indexes.by_lowercase.get_or_init(|| {
+            let mut index = HashMap::with_capacity(self.len());
+            for position in 0..self.len() {
+                index
+                    .entry(self.sorted_name(position).to_ascii_lowercase())
+                    .or_insert(position as u32);
+            }
+            Arc::new(index)
+        })
+    }
 
-/// Gets a hash table of stock BOTW 1.5.0 (for Wii U) or stock 1.6.0 (for Switch) game files and
-/// possible hashes for them. These include, where applicable, the original hash and variants
-/// created by processing unmodified files with common libraries and tools.
-#[inline]
-pub fn get_hash_table(platform: &Platform) -> HashTable {
-    match platform {
-        Platform::WiiU => serde_json::from_str(HASHES_U.as_ref()).unwrap(),
-        Platform::Switch => serde_json::from_str(HASHES_NX.as_ref()).unwrap(),
+    /// Finds the accepted hashes of a file, ignoring case if the table does
+    fn find(&self, canon: &str) -> Option<&[u64]> {
+        match self.find_exact(canon) {
+            None if self.case_insensitive => self.find_exact(self.stock_name(canon)?),
+            found => found,
+        }
     }
-}
 
-/// A struct wrapping a hash table for stock BOTW files with a few convenience methods
-#[derive(Debug, Eq, PartialEq)]
-pub struct StockHashTable {
-    table: HashTable,
-}
+    /// Finds the accepted hashes of a file by its exact name
+    fn find_exact(&self, canon: &str) -> Option<&[u64]> {
+        match &self.storage {
+            Storage::Map(map) => map.get(canon).map(|hashes| &**hashes),
+            _ => self.packed().and_then(|data| data.find(canon)),
+        }
+    }
 
-impl StockHashTable {
-    /// Constructs StockHashTable instance for the specified platform
+    /// Gets the files of the table as a map to change them, moving them into one the first time
+    fn map_mut(&mut self) -> &mut HashMap<Cow<'static, str>, Box<[u64]>> {
+        let map = match &self.storage {
+            Storage::Map(..) => None,
+            Storage::Stock(data) => Some(
+                (0..data.len())
+                    .map(|index| {
+                        (
+                            Cow::Borrowed(data.name_at(index)),
+                            data.hashes_at(index).into(),
+                        )
+                    })
+                    .collect(),
+            ),
+            Storage::Loaded(data) => Some(
+                (0..data.len())
+                    .map(|index| {
+                        (
+                            Cow::Owned(data.name_at(index).to_owned()),
+                            data.hashes_at(index).into(),
+                        )
+                    })
+                    .collect(),
+            ),
+        };
+        if let Some(map) = map {
+            self.storage = Storage::Map(Arc::new(map));
+        }
+        self.indexes = Indexes::default();
+        match &mut self.storage {
+            Storage::Map(map) => Arc::make_mut(map),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Adds a hash to accept for a file, like one made by re-saving a stock file with a newer
+    /// library, so data with that hash counts as vanilla. The file is added to the table if it
+    /// is not already in it. Example:
+    ///
+    #[cfg_attr(feature = "switch-data", doc = "```")]
+    #[cfg_attr(not(feature = "switch-data"), doc = "```ignore")]
+    /// use botw_utils::hashes::{compute_file_hash, Platform, StockHashTable};
+    /// let canon = "Actor/Physics/FldObj_MountainSheikerWall_A_06.bphysics";
+    /// let mut table = StockHashTable::new(Platform::Switch);
+    /// assert!(table.is_file_modded(canon, b"Resaved data", true));
+    /// table.add_hash(canon, compute_file_hash(b"Resaved data").unwrap());
+    /// assert!(!table.is_file_modded(canon, b"Resaved data", true));
+    /// ```
     ///
     /// # Arguments
     ///
-    /// * `platform` - Specifies whether to use a Wii U 1.5.0 or Switch 1.6.0 hash table
-    #[inline]
-    pub fn new(platform: &Platform) -> StockHashTable {
-        StockHashTable {
-            table: get_hash_table(platform),
+    /// * `canon` - The canonical resource name of the file
+    /// * `hash` - The hash to accept, made like [`compute_file_hash`] does, or with the
+    ///   table's [`algorithm`](StockHashTable::algorithm) if it has another one
+    pub fn add_hash(&mut self, canon: &str, hash: u64) {
+        let map = self.map_mut();
+        match map.get_mut(canon) {
+            Some(accepted) => accept(accepted, hash),
+            None => {
+                map.insert(Cow::Owned(canon.to_owned()), Box::new([hash]));
+            }
         }
     }
 
-    /// Iterates the files in the stock hash table by their canonical resource paths.
+    /// Adds several hashes to accept for a file, like [`add_hash`](StockHashTable::add_hash).
+    /// Hashes the file already has are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `canon` - The canonical resource name of the file
+    /// * `hashes` - The hashes to accept
+    pub fn add_entry(&mut self, canon: String, hashes: Vec<u64>) {
+        let accepted = self.map_mut().entry(Cow::Owned(canon)).or_default();
+        for hash in hashes {
+            accept(accepted, hash);
+        }
+    }
+
+    /// Adds every file and hash in a hash table to accept, like
+    /// [`add_entry`](StockHashTable::add_entry) for each file in it
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The hash table to add
+    pub fn extend_from(&mut self, other: &HashTable) {
+        let map = self.map_mut();
+        for (canon, hashes) in other {
+            let accepted = map.entry(Cow::Borrowed(*canon)).or_default();
+            for hash in hashes {
+                accept(accepted, *hash);
+            }
+        }
+    }
+
+    /// Gets the accepted hashes of a stock file, for comparing against hashes computed ahead of
+    /// time. The hashes are xxHash64 digests, with a seed of 0, of the decompressed file data,
+    /// in ascending order. Example:
+    ///
+    #[cfg_attr(feature = "wiiu-data", doc = "```")]
+    #[cfg_attr(not(feature = "wiiu-data"), doc = "```ignore")]
+    /// use botw_utils::hashes::{Platform, StockHashTable};
+    /// let table = StockHashTable::new(Platform::WiiU);
+    /// let precomputed: u64 = 3_305_211_212_481_695_363;
+    /// let hashes = table
+    ///     .get_hashes("Actor/ModelList/DgnMrgPrt_Dungeon023.bmodellist")
+    ///     .unwrap();
+    /// assert!(hashes.contains(&precomputed));
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `canon` - The canonical resource name of the file
     #[inline]
-    pub fn get_stock_files(&self) -> impl Iterator<Item = &&str> {
-        self.table.keys()
+    pub fn get_hashes(&self, canon: &str) -> Option<&[u64]> {
+        self.find(canon)
     }
 
-    /// Gets an owend list of the canonical resource paths for all files in the stock hash table.
+    /// Checks if a file is present in the stock hash table, the opposite of
+    /// [`is_file_new`](StockHashTable::is_file_new)
+    ///
+    /// # Arguments
+    ///
+    /// * `canon` - The canonical resource name of the file
     #[inline]
-    pub fn list_stock_files(&self) -> Vec<String> {
-        self.table.keys().map(|x| x.to_owned().to_owned()).collect()
+    pub fn contains(&self, canon: &str) -> bool {
+        self.find(canon).is_some()
+    }
+
+    /// Checks a file against its stock hashes, telling apart the ways it can differ from stock.
+    /// Automatically decompresses yaz0 data. Example:
+    ///
+    #[cfg_attr(feature = "switch-data", doc = "```")]
+    #[cfg_attr(not(feature = "switch-data"), doc = "```ignore")]
+    /// use botw_utils::hashes::{ModdedState, Platform, StockHashTable};
+    /// let table = StockHashTable::new(Platform::Switch);
+    /// assert!(matches!(
+    ///     table.check_file(
+    ///         "Actor/Physics/FldObj_MountainSheikerWall_A_06.bphysics",
+    ///         b"Random data"
+    ///     ),
+    ///     ModdedState::Modified { .. }
+    /// ));
+    /// assert_eq!(
+    ///     table.check_file("Actor/Physics/Hellow_Sweetie.bphysics", b"Random data"),
+    ///     ModdedState::NewFile
+    /// );
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - The canonical resource name of the file to check as a string slice
+    /// * `data` - The binary data for the file, as a binary data slice (`&[u8]`)
+    pub fn check_file<S: AsRef<str>, D: AsRef<[u8]>>(&self, file_name: S, data: D) -> ModdedState {
+        self.check_file_with_buf(file_name, data, &mut Vec::new())
+    }
+
+    /// Checks a file against its stock hashes like [`check_file`](StockHashTable::check_file),
+    /// decompressing yaz0 data into a scratch buffer which can be reused between calls, so
+    /// scanning many files does not allocate a decompressed copy of each one
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - The canonical resource name of the file to check as a string slice
+    /// * `data` - The binary data for the file, as a binary data slice (`&[u8]`)
+    /// * `buf` - The buffer to decompress into. Its contents are replaced.
+    pub fn check_file_with_buf<S: AsRef<str>, D: AsRef<[u8]>>(
+        &self,
+        file_name: S,
+        data: D,
+        buf: &mut Vec<u8>,
+    ) -> ModdedState {
+        self.try_check_file_with_buf(file_name.as_ref(), data.as_ref(), buf)
+            .unwrap_or(ModdedState::InvalidYaz0)
+    }
+
+    /// Checks a file like [`check_file_with_buf`](StockHashTable::check_file_with_buf), giving
+    /// why its yaz0 data cannot be decompressed instead of [`ModdedState::InvalidYaz0`]
+    pub(crate) fn try_check_file_with_buf(
+        &self,
+        file_name: &str,
+        data: &[u8],
+        buf: &mut Vec<u8>,
+    ) -> Result<ModdedState, HashError> {
+        match self.find(file_name) {
+            Some(hashes) => check_data(hashes, self.algorithm, data, buf),
+            None => Ok(ModdedState::NewFile),
+        }
+    }
+
+    /// Checks many files against their stock hashes at once, like
+    /// [`check_file`](StockHashTable::check_file), reusing one decompression buffer for all of
+    /// them. The results can be zipped with the names for a map keyed by file. Example:
+    ///
+    #[cfg_attr(feature = "switch-data", doc = "```")]
+    #[cfg_attr(not(feature = "switch-data"), doc = "```ignore")]
+    /// use botw_utils::hashes::{ModdedState, Platform, StockHashTable};
+    /// use std::collections::HashMap;
+    /// let table = StockHashTable::new(Platform::Switch);
+    /// let files = vec![
+    ///     ("Actor/Physics/Hellow_Sweetie.bphysics".to_owned(), b"Random data".to_vec()),
+    ///     ("Pack/Bootup.pack".to_owned(), b"Random data".to_vec()),
+    /// ];
+    /// let states: HashMap<&str, ModdedState> = files
+    ///     .iter()
+    ///     .map(|(name, _)| name.as_str())
+    ///     .zip(table.check_files(files.iter().map(|(name, data)| (name, data))))
+    ///     .collect();
+    /// assert_eq!(states["Actor/Physics/Hellow_Sweetie.bphysics"], ModdedState::NewFile);
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The canonical resource names of the files paired with their data
+    ///
+    /// # Returns
+    ///
+    /// Returns a Vec of the [`ModdedState`] of each file, in the same order as the input.
+    pub fn check_files<I, S, D>(&self, files: I) -> Vec<ModdedState>
+    where
+        I: IntoIterator<Item = (S, D)>,
+        S: AsRef<str>,
+        D: AsRef<[u8]>,
+    {
+        let mut buf = Vec::new();
+        files
+            .into_iter()
+            .map(|(file_name, data)| self.check_file_with_buf(file_name, data, &mut buf))
+            .collect()
+    }
+
+    /// Checks many files against their stock hashes at once, splitting the work across one
+    /// thread per available CPU. Each thread reuses its own decompression buffer. The results
+    /// are the same and in the same order as [`check_files`](StockHashTable::check_files).
+    ///
+    /// Requires the `parallel` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The canonical resource names of the files paired with their data
+    ///
+    /// # Returns
+    ///
+    /// Returns a Vec of the [`ModdedState`] of each file, in the same order as the input.
+    #[cfg(feature = "parallel")]
+    pub fn par_check_files<S, D>(&self, files: &[(S, D)]) -> Vec<ModdedState>
+    where
+        S: AsRef<str> + Sync,
+        D: AsRef<[u8]> + Sync,
+    {
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        let chunk_size = files.len().div_ceil(threads).max(1);
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = files
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        self.check_files(chunk.iter().map(|(name, data)| (name, data)))
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        })
     }
 
-    /// Checks a file to see if it has been modified. Automatically decompresses yaz0 data.
+    /// Checks a file to see if it has been modified. Automatically decompresses yaz0 data, and
+    /// counts data which fails to decompress or is too short to be a stock file as modified. Use
+    /// [`check_file`](StockHashTable::check_file) to tell those cases apart.
     ///
     /// # Arguments
     ///
@@ -70,21 +2028,76 @@ impl StockHashTable {
         data: D,
         flag_new: bool,
     ) -> bool {
-        if self.table.contains_key(file_name.as_ref()) {
-            let data = data.as_ref();
-            let mut hasher = XxHash64::with_seed(0);
-            if &data[0..4] == b"Yaz0" {
-                match decompress(data) {
-                    Ok(data) => hasher.write(&data),
-                    Err(_) => return true,
-                }
-            } else {
-                hasher.write(data);
-            }
-            let hash: u64 = hasher.finish();
-            !self.table[file_name.as_ref()].contains(&hash)
-        } else {
-            flag_new
+        match self.check_file(file_name, data) {
+            ModdedState::Vanilla => false,
+            ModdedState::NewFile => flag_new,
+            ModdedState::Modified { .. } | ModdedState::InvalidYaz0 | ModdedState::TooShort => true,
+        }
+    }
+
+    /// Checks a file by its path, finding its canonical resource path like [`try_get_canon_name`]
+    /// and then checking it like [`check_file`](StockHashTable::check_file), for the usual case
+    /// of a file read from a mod folder. A composite `<archive>//<inner path>` path is
+    /// canonicalized like [`get_nested_canon_name`] and checks the innermost file.
+    /// Paths which cannot be canonicalized are errors, so they can be told apart from files
+    /// which are not in the table, which are always given as [`ModdedState::NewFile`]. Example:
+    ///
+    #[cfg_attr(feature = "wiiu-data", doc = "```")]
+    #[cfg_attr(not(feature = "wiiu-data"), doc = "```ignore")]
+    /// use botw_utils::{hashes::{ModdedState, Platform, StockHashTable}, CanonError};
+    /// use std::path::Path;
+    /// let table = StockHashTable::new(Platform::WiiU);
+    /// let state = table.is_path_modded(Path::new("content/Pack/Bootup.pack"), b"Random data");
+    /// assert!(matches!(state, Ok(ModdedState::Modified { .. })));
+    /// let state = table.is_path_modded(Path::new("content/Hellow/Sweetie.tardis"), b"New");
+    /// assert_eq!(state, Ok(ModdedState::NewFile));
+    /// let state = table.is_path_modded(Path::new("rules.txt"), b"[Definition]");
+    /// assert!(matches!(state, Err(CanonError::UnrecognizedRoot { .. })));
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file under a content root, or a composite path into an archive
+    /// * `data` - The binary data for the file
+    ///
+    /// # Returns
+    ///
+    /// Returns a Result with how the file compares to stock, or a [`CanonError`] describing why
+    /// the path does not appear valid
+    pub fn is_path_modded(&self, path: &Path, data: &[u8]) -> Result<ModdedState, CanonError> {
+        Ok(self.check_file(&path_canon(path)?, data))
+    }
+
+    /// Checks a file to see if it has been modified from a hash computed ahead of time, so the
+    /// same hash can be checked against several tables without reading the file again. The hash
+    /// has to be made like [`compute_file_hash`] does, as the xxHash64 digest with a seed of 0
+    /// of the file's decompressed data, or with the table's
+    /// [`algorithm`](StockHashTable::algorithm) if it has another one, or it will never match.
+    /// Example:
+    ///
+    #[cfg_attr(all(feature = "wiiu-data", feature = "switch-data"), doc = "```")]
+    #[cfg_attr(
+        not(all(feature = "wiiu-data", feature = "switch-data")),
+        doc = "```ignore"
+    )]
+    /// use botw_utils::hashes::{compute_file_hash, Platform, StockHashTable};
+    /// let hash = compute_file_hash(b"Random data").unwrap();
+    /// for &platform in &[Platform::WiiU, Platform::Switch] {
+    ///     let table = StockHashTable::new(platform);
+    ///     assert!(table.is_hash_modded("Pack/Bootup.pack", hash, true));
+    /// }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `canon` - The canonical resource name of the file to check
+    /// * `hash` - The hash of the file's decompressed data
+    /// * `flag_new` - Whether to count files not present in stock BOTW as modified
+    #[inline]
+    pub fn is_hash_modded(&self, canon: &str, hash: u64, flag_new: bool) -> bool {
+        match self.find(canon) {
+            Some(hashes) => hashes.binary_search(&hash).is_err(),
+            None => flag_new,
         }
     }
 
@@ -95,22 +2108,50 @@ impl StockHashTable {
     /// * `file_name` - The canonical resource name of the file to check as a string slice
     #[inline]
     pub fn is_file_new<S: AsRef<str>>(&self, file_name: S) -> bool {
-        !self.table.contains_key(file_name.as_ref())
+        self.find(file_name.as_ref()).is_none()
+    }
+}
+
+/// Finds the canonical resource path of a file path for
+/// [`is_path_modded`](StockHashTable::is_path_modded), as the innermost file of a composite
+/// `<archive>//<inner path>` path
+fn path_canon(path: &Path) -> Result<String, CanonError> {
+    let name = path
+        .to_str()
+        .ok_or_else(|| CanonError::NonUtf8Path(path.to_path_buf()))?;
+    let name = strip_windows_prefix(name).replace('\\', "/");
+    let parent = match name.split_once("//") {
+        Some((parent, _)) => parent,
+        None => return try_get_canon_name(path),
+    };
+    match get_nested_canon_name(path) {
+        Some(mut nested) => Ok(nested.inner.pop().unwrap_or(nested.parent)),
+        None => match try_get_canon_name(parent) {
+            Err(e @ CanonError::UnrecognizedRoot { .. }) if parent.is_empty() => Err(e),
+            Err(CanonError::UnrecognizedRoot { .. }) | Ok(_) => Err(CanonError::InvalidCanonPath {
+                path: name.clone(),
+                reason: "a nested path in it is empty",
+            }),
+            Err(e) => Err(e),
+        },
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::TempDir;
     #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
     fn cst_hash_table() {
-        get_hash_table(&Platform::WiiU);
-        get_hash_table(&Platform::Switch);
+        get_hash_table(Platform::WiiU);
+        get_hash_table(Platform::Switch);
     }
 
     #[test]
+    #[cfg(feature = "wiiu-data")]
     fn check_val() {
-        let table = get_hash_table(&Platform::WiiU);
+        let table = get_hash_table(Platform::WiiU);
         assert_eq!(
             table
                 .get("Actor/ModelList/DgnMrgPrt_Dungeon023.bmodellist")
@@ -120,8 +2161,26 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "wiiu-data")]
+    fn borrowed_hashes() {
+        let tbl = StockHashTable::new(Platform::WiiU);
+        let canon = "Actor/ModelList/DgnMrgPrt_Dungeon023.bmodellist";
+        assert!(tbl.contains(canon));
+        assert_eq!(
+            tbl.get_hashes(canon),
+            Some(&[3_305_211_212_481_695_363_u64, 6_042_644_272_755_124_234_u64][..])
+        );
+        assert!(!tbl.contains("Actor/ModelList/Hellow_Sweetie.bmodellist"));
+        assert_eq!(
+            tbl.get_hashes("Actor/ModelList/Hellow_Sweetie.bmodellist"),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "switch-data")]
     fn is_file_modded() {
-        let tbl = StockHashTable::new(&Platform::Switch);
+        let tbl = StockHashTable::new(Platform::Switch);
         assert!(tbl.is_file_modded(
             "Actor/Physics/FldObj_MountainSheikerWall_A_06.bphysics",
             b"Random data",
@@ -130,8 +2189,775 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "switch-data")]
+    fn path_checks() {
+        let mut tbl = StockHashTable::new(Platform::Switch);
+        tbl.add_hash(
+            "Aoc/0010/Actor/AIProgram/Npc_A.baiprog",
+            compute_file_hash(b"Stock AI").unwrap(),
+        );
+        let check = |path: &str, data: &[u8]| tbl.is_path_modded(Path::new(path), data);
+        assert_eq!(
+            check("content\\Pack\\Bootup.pack", b"Random data"),
+            Ok(ModdedState::Modified {
+                hash: compute_file_hash(b"Random data").unwrap()
+            })
+        );
+        assert_eq!(
+            check(
+                "aoc/0010/Pack/AocMainField.pack//Actor/Pack/Npc_A.sbactorpack//Actor/AIProgram/Npc_A.baiprog",
+                b"Stock AI"
+            ),
+            Ok(ModdedState::Vanilla)
+        );
+        assert_eq!(
+            check("Pack/Bootup.pack//Hellow/Sweetie.tardis", b"New"),
+            Ok(ModdedState::NewFile)
+        );
+        assert_eq!(
+            check("romfs/Hellow/Sweetie.tardis", b"New"),
+            Ok(ModdedState::NewFile)
+        );
+        assert!(matches!(
+            check("Hellow/Sweetie.tardis", b"New"),
+            Err(CanonError::UnrecognizedRoot { .. })
+        ));
+        assert!(matches!(
+            check("Pack/Bootup.pack//", b"Empty"),
+            Err(CanonError::InvalidCanonPath { .. })
+        ));
+        assert!(matches!(
+            check("//Actor/Pack/Npc_A.sbactorpack", b"No archive"),
+            Err(CanonError::UnrecognizedRoot { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "switch-data")]
+    fn modded_states() {
+        let tbl = StockHashTable::new(Platform::Switch);
+        let canon = "Actor/Physics/FldObj_MountainSheikerWall_A_06.bphysics";
+        let mut hasher = XxHash64::with_seed(0);
+        hasher.write(b"Random data");
+        assert_eq!(
+            tbl.check_file(canon, b"Random data"),
+            ModdedState::Modified {
+                hash: hasher.finish()
+            }
+        );
+        assert_eq!(
+            tbl.check_file(canon, b"Yaz0 broken"),
+            ModdedState::InvalidYaz0
+        );
+        assert!(tbl.is_file_modded(canon, b"Yaz0 broken", false));
+        assert_eq!(
+            tbl.check_file("Actor/Physics/Hellow_Sweetie.bphysics", b"Random data"),
+            ModdedState::NewFile
+        );
+        assert!(!tbl.is_file_modded("Actor/Physics/Hellow_Sweetie.bphysics", b"", false));
+    }
+
+    #[test]
+    #[cfg(feature = "wiiu-data")]
+    fn precomputed_hashes() {
+        let tbl = StockHashTable::new(Platform::WiiU);
+        let canon = "Actor/ModelList/DgnMrgPrt_Dungeon023.bmodellist";
+        assert!(!tbl.is_hash_modded(canon, 3_305_211_212_481_695_363, true));
+        let hash = compute_file_hash(b"Random data").unwrap();
+        assert!(tbl.is_hash_modded(canon, hash, false));
+        assert!(!tbl.is_hash_modded("Hellow/Sweetie.tardis", hash, false));
+        assert!(tbl.is_hash_modded("Hellow/Sweetie.tardis", hash, true));
+    }
+
+    #[test]
+    #[cfg(feature = "switch-data")]
+    fn file_hash_parity() {
+        let tbl = StockHashTable::new(Platform::Switch);
+        let canon = "Actor/Physics/FldObj_MountainSheikerWall_A_06.bphysics";
+        let data = b"Random data, long enough to be worth compressing. Random data.".to_vec();
+        let compressed = roead::yaz0::compress(&data);
+        assert!(compressed.starts_with(b"Yaz0"));
+        let mut hasher = XxHash64::with_seed(0);
+        hasher.write(&data);
+        let hash = hasher.finish();
+        assert_eq!(compute_file_hash(&data), Ok(hash));
+        assert_eq!(compute_file_hash(&compressed), Ok(hash));
+        for input in &[&data, &compressed] {
+            assert_eq!(tbl.check_file(canon, input), ModdedState::Modified { hash });
+            assert_eq!(
+                tbl.is_file_modded(canon, input, false),
+                tbl.is_hash_modded(canon, hash, false)
+            );
+        }
+        assert!(matches!(
+            compute_file_hash(b"Yaz0 broken"),
+            Err(HashError::InvalidYaz0(_))
+        ));
+        let files = [
+            (canon, &data),
+            (canon, &compressed),
+            ("Hellow/Sweetie.tardis", &data),
+        ];
+        let states = tbl.check_files(files.iter().copied());
+        assert_eq!(
+            states,
+            [
+                ModdedState::Modified { hash },
+                ModdedState::Modified { hash },
+                ModdedState::NewFile
+            ]
+        );
+        #[cfg(feature = "parallel")]
+        assert_eq!(tbl.par_check_files(&files), states);
+        let mut buf = vec![0xFF; 4096];
+        for input in &[&compressed, &data, &compressed] {
+            assert_eq!(compute_file_hash_with_buf(input, &mut buf), Ok(hash));
+            assert_eq!(
+                tbl.check_file_with_buf(canon, input, &mut buf),
+                ModdedState::Modified { hash }
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "switch-data")]
+    fn short_data() {
+        let tbl = StockHashTable::new(Platform::Switch);
+        let canon = "Actor/Physics/FldObj_MountainSheikerWall_A_06.bphysics";
+        for data in &[&b""[..], b"Ya", b"Yaz"] {
+            assert_eq!(tbl.check_file(canon, data), ModdedState::TooShort);
+            assert!(tbl.is_file_modded(canon, data, false));
+            assert!(compute_file_hash(data).is_ok());
+            assert!(!tbl.is_file_modded("Hellow/Sweetie.tardis", data, false));
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn cached_tables() {
+        let wiiu = StockHashTable::get(Platform::WiiU);
+        assert!(std::ptr::eq(wiiu, StockHashTable::get(Platform::WiiU)));
+        assert!(!std::ptr::eq(wiiu, StockHashTable::get(Platform::Switch)));
+        assert_eq!(*wiiu, StockHashTable::new(Platform::WiiU));
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn table_validation() {
+        for &platform in &[Platform::WiiU, Platform::Switch] {
+            assert_eq!(
+                try_get_hash_table(platform).unwrap().len(),
+                StockHashTable::new(platform).len()
+            );
+            assert_eq!(StockHashTable::new(platform).validate(), Ok(()));
+        }
+        for (json, problem) in &[
+            ("{}", "it has no files"),
+            (r#"{"content/Pack/Bootup.pack": [1]}"#, "root folder"),
+            (
+                r#"{"Pack/Bootup.pack": []}"#,
+                "Pack/Bootup.pack has no hashes",
+            ),
+            (
+                r#"{"Pack/Bootup.pack": [0, 1]}"#,
+                "Pack/Bootup.pack has a hash of 0",
+            ),
+        ] {
+            match StockHashTable::from_json(json).unwrap().validate() {
+                Err(TableError::Invalid(reason)) => assert!(reason.contains(problem), "{}", reason),
+                other => panic!("{} gave {:?}", json, other),
+            }
+        }
+
+        use std::io::Write;
+        let deflate = |data: &[u8]| {
+            let mut encoder = libflate::deflate::Encoder::new(Vec::new());
+            encoder.write_all(data).unwrap();
+            encoder.finish().into_result().unwrap()
+        };
+        let mut data = Vec::new();
+        libflate::deflate::Decoder::new(PACKED_U)
+            .read_to_end(&mut data)
+            .unwrap();
+        assert!(StockData::unpack(&deflate(&data)).is_ok());
+        let truncated = StockData::unpack(&deflate(&data[..data.len() - 1]));
+        assert!(truncated.unwrap_err().contains("bytes of data"));
+        data.swap(12, 16);
+        let reordered = StockData::unpack(&deflate(&data));
+        assert_eq!(reordered.unwrap_err(), "its name offsets are out of order");
+        assert!(StockData::unpack(&deflate(&[1, 2, 3])).is_err());
+        assert!(StockData::unpack(b"Not deflated").is_err());
+        let error = TableError::Corrupt {
+            platform: Platform::WiiU,
+            reason: "its names are not sorted".to_owned(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "The embedded stock hash table for Wii U is corrupt: its names are not sorted"
+        );
+    }
+
+    #[test]
+    fn custom_tables() {
+        let hash = compute_file_hash(b"Demo data").unwrap();
+        let json = format!(
+            r#"{{"Pack/TitleBG.pack": [{}], "Actor/Pack/Demo.sbactorpack": [1, {}]}}"#,
+            hash, hash
+        );
+        let table = StockHashTable::from_json(&json).unwrap();
+        assert_eq!(
+            table.get_stock_files().collect::<Vec<_>>(),
+            ["Actor/Pack/Demo.sbactorpack", "Pack/TitleBG.pack"]
+        );
+        assert!(!table.is_file_modded("Pack/TitleBG.pack", b"Demo data", true));
+        assert!(table.is_file_modded("Pack/TitleBG.pack", b"Random data", true));
+        assert!(table.is_file_new("Pack/Bootup.pack"));
+        assert_eq!(
+            table.get_hashes("Actor/Pack/Demo.sbactorpack"),
+            Some(&[1, hash][..])
+        );
+        assert_eq!(
+            StockHashTable::from_reader(json.as_bytes()).as_ref(),
+            Ok(&table)
+        );
+        let dir = TempDir::new("table");
+        let path = dir.write("table.json", &json);
+        assert_eq!(StockHashTable::from_path(&path).as_ref(), Ok(&table));
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            StockHashTable::from_path(&path),
+            Err(TableError::Io(io::ErrorKind::NotFound))
+        );
+        assert!(matches!(
+            StockHashTable::from_json(r#"{"Pack/TitleBG.pack": "hash"}"#),
+            Err(TableError::InvalidJson(_))
+        ));
+        assert_eq!(
+            StockHashTable::from_json("{}")
+                .unwrap()
+                .list_stock_files()
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "switch-data")]
+    fn extended_tables() {
+        let canon = "Actor/Physics/FldObj_MountainSheikerWall_A_06.bphysics";
+        let resaved = b"Resaved data";
+        let hash = compute_file_hash(resaved).unwrap();
+        let mut tbl = StockHashTable::new(Platform::Switch);
+        let stock = tbl.get_hashes(canon).unwrap().to_vec();
+        assert_eq!(
+            tbl.check_file(canon, resaved),
+            ModdedState::Modified { hash }
+        );
+        tbl.add_hash(canon, hash);
+        tbl.add_hash(canon, hash);
+        assert_eq!(tbl.check_file(canon, resaved), ModdedState::Vanilla);
+        let mut accepted = [stock, vec![hash]].concat();
+        accepted.sort_unstable();
+        assert_eq!(tbl.get_hashes(canon), Some(&accepted[..]));
+        assert_ne!(tbl, StockHashTable::new(Platform::Switch));
+        tbl.add_entry("Hellow/Sweetie.tardis".to_owned(), vec![1, 2]);
+        let mut extra = HashTable::new();
+        extra.insert("Hellow/Sweetie.tardis", vec![2, 3]);
+        extra.insert("Pack/Sweetie.pack", vec![4]);
+        tbl.extend_from(&extra);
+        assert_eq!(
+            tbl.get_hashes("Hellow/Sweetie.tardis"),
+            Some(&[1, 2, 3][..])
+        );
+        assert!(!tbl.is_hash_modded("Pack/Sweetie.pack", 4, true));
+        assert_eq!(
+            tbl.get_stock_files().count(),
+            StockHashTable::get(Platform::Switch)
+                .get_stock_files()
+                .count()
+                + 2
+        );
+        let mut custom = StockHashTable::from_json(r#"{"Pack/TitleBG.pack": [1]}"#).unwrap();
+        custom.add_hash("Pack/TitleBG.pack", 2);
+        assert_eq!(
+            custom,
+            StockHashTable::from_json(r#"{"Pack/TitleBG.pack": [1, 2]}"#).unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn platform_names() {
+        for (name, platform) in &[
+            ("wiiu", Platform::WiiU),
+            ("Wii_U", Platform::WiiU),
+            ("Wii U", Platform::WiiU),
+            ("SWITCH", Platform::Switch),
+            ("nx", Platform::Switch),
+        ] {
+            assert_eq!(name.parse(), Ok(*platform), "{}", name);
+            assert_eq!(platform.to_string().parse(), Ok(*platform));
+        }
+        assert_eq!(
+            "ps4".parse::<Platform>(),
+            Err(ParsePlatformError {
+                input: "ps4".to_owned()
+            })
+        );
+        let tables: HashMap<Platform, &StockHashTable> = [Platform::WiiU, Platform::Switch]
+            .iter()
+            .map(|platform| (*platform, StockHashTable::get(*platform)))
+            .collect();
+        assert!(std::ptr::eq(
+            tables[&Platform::Switch],
+            StockHashTable::get(Platform::Switch)
+        ));
+        #[cfg(feature = "serde")]
+        {
+            assert_eq!(serde_json::to_string(&Platform::WiiU).unwrap(), r#""wiiu""#);
+            assert_eq!(
+                serde_json::from_str::<Platform>(r#""switch""#).unwrap(),
+                Platform::Switch
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn table_metadata() {
+        let table = StockHashTable::new(Platform::WiiU);
+        let metadata = table.metadata();
+        assert_eq!(metadata.platform, Some(Platform::WiiU));
+        assert_eq!(metadata.version, Some(GameVersion::V1_5_0));
+        assert_eq!(metadata.files, table.len());
+        assert_eq!(
+            data_versions(),
+            [metadata, StockHashTable::new(Platform::Switch).metadata()]
+        );
+        assert_eq!(
+            StockHashTable::from(LazyHashTable::new(Platform::WiiU)).metadata(),
+            metadata
+        );
+        let mut changed = table.clone();
+        changed.add_hash("Hellow/Sweetie.tardis", 1);
+        assert_eq!(changed.metadata().files, table.len());
+
+        let custom = StockHashTable::from_json(
+            r#"{"metadata": {"platform": "switch", "version": "1.6.0", "files": 9,
+                "generated": "2021-05-01", "generator": "hashgen 0.2"},
+                "hashes": {"Pack/Bootup.pack": [1], "Pack/TitleBG.pack": [2]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            custom.metadata(),
+            &TableMetadata {
+                platform: Some(Platform::Switch),
+                version: Some(GameVersion::V1_6_0),
+                files: 2,
+                generated: Some("2021-05-01".to_owned()),
+                generator: Some("hashgen 0.2".to_owned()),
+            }
+        );
+        assert_eq!(custom.source(), TableSource::Custom);
+        let plain = StockHashTable::from_json(r#"{"hashes": {"Pack/Bootup.pack": [1]}}"#).unwrap();
+        assert_eq!(
+            plain.metadata(),
+            &TableMetadata {
+                files: 1,
+                ..Default::default()
+            }
+        );
+        for bad in &[
+            r#"{"metadata": [], "hashes": {}}"#,
+            r#"{"metadata": {"generated": 2021}, "hashes": {}}"#,
+            r#"{"metadata": {"version": "1.7.0"}, "hashes": {}}"#,
+        ] {
+            assert!(matches!(
+                StockHashTable::from_json(bad),
+                Err(TableError::InvalidJson(_))
+            ));
+        }
+        assert!(StockHashTable::from_json(r#"{"extra": 1, "hashes": {}}"#).is_err());
+
+        let dir = TempDir::new("meta");
+        let path = dir.write(
+            "metadata.json",
+            r#"{"metadata": {"generator": "hashgen"}, "hashes": {}}"#,
+        );
+        let patched = StockHashTable::new_with_source(Platform::WiiU, Some(&path)).unwrap();
+        assert_eq!(patched.metadata().platform, Some(Platform::WiiU));
+        assert_eq!(patched.metadata().version, Some(GameVersion::V1_5_0));
+        assert_eq!(patched.metadata().generator.as_deref(), Some("hashgen"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn table_sources() {
+        let mut tbl = StockHashTable::new(Platform::Switch);
+        assert_eq!(
+            tbl.source(),
+            TableSource::Stock {
+                platform: Platform::Switch,
+                version: GameVersion::V1_6_0
+            }
+        );
+        tbl.add_hash("Pack/Bootup.pack", 1);
+        assert_eq!(tbl.platform(), Some(Platform::Switch));
+        let debug = format!("{:?}", StockHashTable::new(Platform::WiiU));
+        assert!(debug.starts_with("StockHashTable { source: Stock { platform: WiiU"));
+        assert!(debug.contains("metadata: TableMetadata { platform: Some(WiiU)"));
+        assert!(debug.len() < 300);
+        let custom = StockHashTable::from_json(r#"{"Pack/Bootup.pack": [1]}"#).unwrap();
+        assert_eq!(custom.source(), TableSource::Custom);
+        assert_eq!(
+            format!("{:?}", custom),
+            "StockHashTable { source: Custom, files: 1, metadata: TableMetadata { platform: None, \
+             version: None, files: 1, generated: None, generator: None } }"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "wiiu-data")]
+    fn shared_tables() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<StockHashTable>();
+        let custom = StockHashTable::from_json(r#"{"Pack/Bootup.pack": [1]}"#).unwrap();
+        let mut changed = custom.clone();
+        let packed = |table: &StockHashTable| table.packed().map(|data| data as *const _);
+        assert_eq!(packed(&custom), packed(&changed));
+        changed.add_hash("Pack/Bootup.pack", 2);
+        let shared = changed.clone();
+        assert!(matches!(
+            (&changed.storage, &shared.storage),
+            (Storage::Map(a), Storage::Map(b)) if Arc::ptr_eq(a, b)
+        ));
+        changed.add_hash("Pack/Bootup.pack", 3);
+        assert_eq!(custom.get_hashes("Pack/Bootup.pack"), Some(&[1][..]));
+        assert_eq!(shared.get_hashes("Pack/Bootup.pack"), Some(&[1, 2][..]));
+        assert_eq!(changed.get_hashes("Pack/Bootup.pack"), Some(&[1, 2, 3][..]));
+        let table = StockHashTable::new(Platform::WiiU);
+        let canon = "Actor/ModelList/DgnMrgPrt_Dungeon023.bmodellist";
+        let found: Vec<bool> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..4)
+                .map(|_| {
+                    let table = table.clone();
+                    scope.spawn(move || table.contains(canon))
+                })
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+        assert_eq!(found, [true; 4]);
+    }
+
+    #[test]
+    #[cfg(feature = "switch-data")]
+    fn table_entries() {
+        let tbl = StockHashTable::new(Platform::Switch);
+        assert_eq!(tbl.iter().len(), tbl.len());
+        assert!(tbl
+            .iter()
+            .all(|(canon, hashes)| tbl.get_hashes(canon) == Some(hashes)));
+        let json = r#"{"Pack/Bootup.pack": [1, 2], "Pack/TitleBG.pack": [3]}"#;
+        let mut custom = StockHashTable::from_json(json).unwrap();
+        let entries = |table: &StockHashTable| {
+            let mut entries: Vec<(String, Vec<u64>)> = table
+                .into_iter()
+                .map(|(canon, hashes)| (canon.to_owned(), hashes.to_vec()))
+                .collect();
+            entries.sort();
+            entries
+        };
+        let expected = vec![
+            ("Pack/Bootup.pack".to_owned(), vec![1, 2]),
+            ("Pack/TitleBG.pack".to_owned(), vec![3]),
+        ];
+        assert_eq!(entries(&custom), expected);
+        custom.add_entry("Pack/Bootup.pack".to_owned(), vec![2]);
+        assert_eq!(entries(&custom), expected);
+        assert_eq!((custom.len(), custom.is_empty()), (2, false));
+        let empty = StockHashTable::from_json("{}").unwrap();
+        assert!(empty.is_empty() && empty.iter().next().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "wiiu-data")]
+    fn prefix_queries() {
+        let tbl = StockHashTable::new(Platform::WiiU);
+        let packs: Vec<&str> = tbl.files_under("Actor/Pack/").collect();
+        assert_eq!(packs, tbl.files_under("Actor/Pack").collect::<Vec<_>>());
+        assert!(packs.len() > 1000);
+        assert!(packs.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(
+            packs.len(),
+            tbl.get_stock_files()
+                .filter(|canon| canon.starts_with("Actor/Pack/"))
+                .count()
+        );
+        assert_eq!(tbl.files_under("Hellow/Sweetie").next(), None);
+        assert_eq!(tbl.files_under("").count(), tbl.len());
+        let json = r#"{"Map/A-1/A-1_Static.mubin": [1], "Map/A-10/A-10_Static.mubin": [2],
+            "Map/A-1.txt": [3], "Pack/Bootup.pack": [4]}"#;
+        let mut custom = StockHashTable::from_json(json).unwrap();
+        assert_eq!(
+            custom.files_under("Map/A-1").collect::<Vec<_>>(),
+            ["Map/A-1/A-1_Static.mubin"]
+        );
+        custom.add_hash("Map/A-1/A-1_Dynamic.mubin", 5);
+        assert_eq!(
+            custom.files_under("Map/A-1/").collect::<Vec<_>>(),
+            ["Map/A-1/A-1_Dynamic.mubin", "Map/A-1/A-1_Static.mubin"]
+        );
+        custom.add_hash("Map/A-1/A-1_Clustering.blwp", 6);
+        assert_eq!(custom.files_under("Map/A-1").count(), 3);
+        assert_eq!(custom.files_under("Map").count(), 5);
+        assert_eq!(custom.files_under("Map/B-1").count(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "wiiu-data")]
+    fn extension_queries() {
+        let tbl = StockHashTable::new(Platform::WiiU);
+        let textures: Vec<&str> = tbl.files_with_extension("bfres").collect();
+        assert!(textures.contains(&"Model/UMii_Korogu_Face_M_007.Tex1.bfres"));
+        assert_eq!(
+            textures,
+            tbl.files_with_extension(".sbfres").collect::<Vec<_>>()
+        );
+        assert_eq!(
+            textures.len(),
+            tbl.get_stock_files()
+                .filter(|canon| canon.ends_with(".bfres"))
+                .count()
+        );
+        let terrain = tbl.files_with_extension("stera").count();
+        assert!(terrain > 0);
+        assert_eq!(tbl.files_with_extension("tera").count(), 0);
+        assert_eq!(tbl.files_with_extension("tardis").count(), 0);
+        let json = r#"{"Pack/Bootup.pack": [1], "Actor/Pack/Demo.bactorpack": [2]}"#;
+        let mut custom = StockHashTable::from_json(json).unwrap();
+        assert_eq!(custom.files_with_extension("pack").count(), 1);
+        custom.add_hash("Pack/TitleBG.pack", 3);
+        assert_eq!(
+            custom.files_with_extension("pack").collect::<Vec<_>>(),
+            ["Pack/Bootup.pack", "Pack/TitleBG.pack"]
+        );
+        assert_eq!(
+            custom
+                .files_with_extension("sbactorpack")
+                .collect::<Vec<_>>(),
+            ["Actor/Pack/Demo.bactorpack"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "wiiu-data")]
+    fn case_insensitive_lookups() {
+        let canon = "Actor/Pack/Enemy_Lizalfos_Senior.bactorpack";
+        let mangled = "actor/pack/ENEMY_LIZALFOS_SENIOR.bactorpack";
+        let tbl = StockHashTable::new(Platform::WiiU);
+        assert!(tbl.is_file_new(mangled));
+        assert!(tbl.indexes.by_lowercase.get().is_none());
+        let tbl = tbl.case_insensitive(true);
+        assert!(!tbl.is_file_new(mangled));
+        assert_eq!(tbl.get_hashes(mangled), tbl.get_hashes(canon));
+        assert_eq!(tbl.stock_name(mangled), Some(canon));
+        assert!(tbl.case_insensitive(false).is_file_new(mangled));
+        let json = r#"{"Pack/Bootup.pack": [1], "Pack/BOOTUP.pack": [2]}"#;
+        let mut custom = StockHashTable::from_json(json)
+            .unwrap()
+            .case_insensitive(true);
+        assert_eq!(custom.get_hashes("Pack/Bootup.pack"), Some(&[1][..]));
+        assert_eq!(custom.get_hashes("Pack/BOOTUP.pack"), Some(&[2][..]));
+        assert_eq!(
+            custom.stock_name("pack/bootup.pack"),
+            Some("Pack/BOOTUP.pack")
+        );
+        custom.add_hash("Pack/TitleBG.pack", 3);
+        assert!(!custom.is_hash_modded("PACK/TITLEBG.PACK", 3, true));
+        assert_eq!(custom.stock_name("Pack/Sweetie.pack"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "wiiu-data")]
+    fn compact_storage() {
+        use core::mem::size_of;
+        let data = StockData::get(Platform::WiiU);
+        assert!((0..data.len()).all(|index| data
+            .hashes_at(index)
+            .windows(2)
+            .all(|pair| pair[0] < pair[1])));
+        // What the hashes would take with a Vec for each file, against the packed offsets
+        let vecs = data.len() * size_of::<Vec<u64>>() + data.hashes.len() * size_of::<u64>();
+        let packed = data.hash_starts.capacity() * size_of::<u32>()
+            + data.hashes.capacity() * size_of::<u64>();
+        assert!(
+            packed * 2 < vecs,
+            "{} bytes packed vs {} in Vecs",
+            packed,
+            vecs
+        );
+
+        let mut tbl = StockHashTable::from_json(r#"{"Pack/Bootup.pack": [3, 1, 3]}"#).unwrap();
+        assert_eq!(tbl.get_hashes("Pack/Bootup.pack"), Some(&[1, 3][..]));
+        tbl.add_hash("Pack/Bootup.pack", 2);
+        tbl.add_entry("Pack/Bootup.pack".to_owned(), vec![0, 3]);
+        assert_eq!(tbl.get_hashes("Pack/Bootup.pack"), Some(&[0, 1, 2, 3][..]));
+        assert!(!tbl.is_hash_modded("Pack/Bootup.pack", 2, true));
+        assert!(tbl.is_hash_modded("Pack/Bootup.pack", 4, true));
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn platform_diff() {
+        let diff = diff_platforms();
+        assert_eq!(
+            diff.wiiu_only.len() + diff.shared.len(),
+            StockHashTable::new(Platform::WiiU).len()
+        );
+        assert_eq!(
+            diff.switch_only.len() + diff.shared.len(),
+            StockHashTable::new(Platform::Switch).len()
+        );
+        assert_eq!((diff.wiiu_only.len(), diff.switch_only.len()), (5847, 8480));
+        assert!(diff.wiiu_only.contains(&"/bool_data_0.bgdata"));
+        assert!(diff
+            .switch_only
+            .contains(&"Aoc/0010/Model/DgnMrgPrt_Dungeon120.Tex.bfres"));
+        let shared = |canon| {
+            diff.shared
+                .iter()
+                .find(|file| file.canon == canon)
+                .map(|file| file.same_content)
+        };
+        assert_eq!(shared("5000000000.hght"), Some(true));
+        assert_eq!(shared("Actor/AIDef/AIDef_Game.product.byml"), Some(false));
+        assert_eq!(
+            diff.shared.iter().filter(|file| file.same_content).count(),
+            66846
+        );
+        assert!(sorted_overlap(&[1, 4, 9], &[2, 9]) && !sorted_overlap(&[1, 4], &[2, 3, 5]));
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn crc_lookups() {
+        use crate::crc_of_canon;
+        for canon in &["Pack/Bootup.pack", "/bool_data_0.bgdata"] {
+            assert_eq!(canon_for_crc(crc_of_canon(canon)), Some(*canon));
+        }
+        assert_eq!(canon_for_crc(crc_of_canon("Hellow/Sweetie.tardis")), None);
+        let crc = crc_of_canon("Model/Item_Roast_08.Tex2.bfres");
+        assert_eq!(crc, crc_of_canon("NavMesh/MainField/34-12.hknm2"));
+        assert_eq!(canon_for_crc(crc), None);
+        assert_eq!(
+            canons_for_crc(crc).collect::<Vec<_>>(),
+            [
+                "Model/Item_Roast_08.Tex2.bfres",
+                "NavMesh/MainField/34-12.hknm2"
+            ]
+        );
+        let shared = stock_crcs()
+            .windows(2)
+            .filter(|pair| pair[0].0 == pair[1].0)
+            .count();
+        assert_eq!(shared, 5);
+    }
+
+    #[test]
+    #[cfg(feature = "wiiu-data")]
+    fn hash_algorithms() {
+        assert_eq!(Algorithm::Xxh64.hash(b""), 0xEF46_DB37_51D8_E999);
+        assert_eq!(Algorithm::Xxh3.hash(b""), 0x2D06_8005_38D3_94C2);
+        assert_eq!(Algorithm::Crc32.hash(b"123456789"), 0xCBF4_3926);
+        assert_eq!("XXH3".parse(), Ok(Algorithm::Xxh3));
+        assert_eq!(
+            "md5".parse::<Algorithm>(),
+            Err(ParseAlgorithmError {
+                input: "md5".to_owned()
+            })
+        );
+        let data = b"Random data, long enough to be worth compressing. Random data.".to_vec();
+        let compressed = roead::yaz0::compress(&data);
+        for algorithm in Algorithm::ALL.iter() {
+            assert_eq!(algorithm.as_str().parse(), Ok(*algorithm));
+            assert_eq!(
+                algorithm.compute_file_hash(&compressed),
+                Ok(algorithm.hash(&data))
+            );
+        }
+
+        let json = format!(
+            r#"{{"algorithm": "xxh3", "hashes": {{"Pack/Bootup.pack": [{}]}}}}"#,
+            Algorithm::Xxh3.hash(&data)
+        );
+        let table = StockHashTable::from_json(&json).unwrap();
+        assert_eq!(table.algorithm(), Algorithm::Xxh3);
+        assert_eq!(
+            table.check_file("Pack/Bootup.pack", &data),
+            ModdedState::Vanilla
+        );
+        assert_eq!(
+            table.check_file("Pack/Bootup.pack", &compressed),
+            ModdedState::Vanilla
+        );
+        assert_eq!(
+            table.check_file("Pack/Bootup.pack", b"Random data"),
+            ModdedState::Modified {
+                hash: Algorithm::Xxh3.hash(b"Random data")
+            }
+        );
+        let plain = StockHashTable::from_json(&json.replace("xxh3", "xxh64")).unwrap();
+        assert_eq!(plain.algorithm(), Algorithm::Xxh64);
+        assert_ne!(plain, table);
+        assert!(plain.is_file_modded("Pack/Bootup.pack", &data, true));
+        assert_eq!(
+            StockHashTable::new(Platform::WiiU).algorithm(),
+            Algorithm::Xxh64
+        );
+        match StockHashTable::from_json(&json.replace("xxh3", "md5")) {
+            Err(TableError::InvalidJson(reason)) => assert!(reason.contains("md5")),
+            other => panic!("{:?}", other),
+        }
+        assert!(matches!(
+            StockHashTable::from_json(r#"{"algorithm": "crc32", "hashes": {}, "extra": 1}"#),
+            Err(TableError::InvalidJson(_))
+        ));
+        assert!(StockHashTable::from_json(r#"{"algorithm": [1]}"#)
+            .unwrap()
+            .contains("algorithm"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn game_versions() {
+        for version in GameVersion::ALL.iter() {
+            assert_eq!(version.as_str().parse(), Ok(*version));
+            for &platform in &[Platform::WiiU, Platform::Switch] {
+                let table = StockHashTable::for_version(platform, *version);
+                assert_eq!(table.is_some(), *version == GameVersion::latest(platform));
+            }
+        }
+        assert_eq!(
+            StockHashTable::for_version(Platform::Switch, GameVersion::V1_6_0),
+            Some(StockHashTable::new(Platform::Switch))
+        );
+        let error = "1.3".parse::<GameVersion>().unwrap_err();
+        assert_eq!(
+            error,
+            ParseGameVersionError {
+                input: "1.3".to_owned()
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Unrecognized game version `1.3`, expected a version number like `1.5.0`"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "wiiu-data")]
     fn print_files() {
-        let tbl = StockHashTable::new(&Platform::WiiU);
+        let tbl = StockHashTable::new(Platform::WiiU);
         for file in tbl.get_stock_files() {
             println!("{}", file)
         }
@@ -0,0 +1,395 @@
+use super::sarc::read_sarc;
+use super::{ModdedState, ScanProgress, StockHashTable};
+use crate::extensions::SARC_EXTS;
+use crate::walk::{error_path, io_error};
+use crate::{canonicalize_dir, get_nested_file_canon, RootKind};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// What [`StockHashTable::verify_game_dump`] found in a game dump. Files are listed with their
+/// path and [canonical resource path](https://zeldamods.org/wiki/Canonical_resource_path), and
+/// every list is sorted, so the report can be serialized with the `serde` feature and compared
+/// or summarized as is.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DumpReport {
+    /// The number of files in the dump which were read and checked
+    pub checked: usize,
+    /// Stock files which are not in the dump, either as loose files or inside its archives.
+    /// DLC files are only expected when a DLC folder is given.
+    pub missing: Vec<String>,
+    /// Files in the dump which are not in the stock game
+    pub extra: Vec<(PathBuf, String)>,
+    /// Stock files which do not match any of their stock hashes, with how they differ
+    pub mismatched: Vec<(PathBuf, String, ModdedState)>,
+    /// Files and folders which could not be read or which have no canonical resource path, with
+    /// the reason
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+impl DumpReport {
+    /// Checks if the dump is complete and unmodified, with nothing missing, extra, mismatched,
+    /// or unreadable
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty()
+            && self.extra.is_empty()
+            && self.mismatched.is_empty()
+            && self.errors.is_empty()
+    }
+}
+
+impl StockHashTable {
+    /// Checks that a dump of the game is complete and unmodified, by walking its content folders
+    /// and checking each file against the table like [`check_file`](StockHashTable::check_file).
+    /// Files are read one at a time, so the dump is never held in memory. Stock files inside
+    /// the dump's archives count as present, both under their own canonical resource paths and
+    /// under the paths the stock tables also list them by, like `Pack/Bootup/` for the files in
+    /// `Pack/Bootup.pack`, so they are not reported missing. Update files
+    /// replace the base files with the same canonical resource path, and on Switch, where a
+    /// dump of the base game and update is usually a single `romfs` folder, the update folder
+    /// can be left out or be the same as the base one. Example:
+    ///
+    /// ```no_run
+    /// use botw_utils::hashes::{Platform, StockHashTable};
+    /// use std::path::Path;
+    /// let table = StockHashTable::new(Platform::Switch);
+    /// let report = table.verify_game_dump(Path::new("dump/romfs"), None, None);
+    /// println!("{} missing, {} changed", report.missing.len(), report.mismatched.len());
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The base game content folder, Wii U `content` or Switch `romfs`
+    /// * `update` - The update content folder, if it is separate from the base folder
+    /// * `aoc` - The DLC content folder, Wii U `content` or `content/0010` or Switch `romfs`,
+    ///   if DLC files should be checked
+    ///
+    /// # Returns
+    ///
+    /// Returns a [`DumpReport`] of the files which are missing, extra, or do not match stock.
+    /// Folders and files which cannot be read, including a folder given which does not exist,
+    /// are listed in its errors rather than stopping the check.
+    pub fn verify_game_dump(
+        &self,
+        content: &Path,
+        update: Option<&Path>,
+        aoc: Option<&Path>,
+    ) -> DumpReport {
+        self.verify_game_dump_with_progress(content, update, aoc, ())
+    }
+
+    /// Checks a dump of the game like [`verify_game_dump`](StockHashTable::verify_game_dump),
+    /// telling an observer about each file as it is checked, for showing progress in a UI. The
+    /// folders are walked before any file is checked, so [`ScanProgress::on_total`] is called
+    /// first. Example:
+    ///
+    /// ```no_run
+    /// use botw_utils::hashes::{ModdedState, Platform, ScanProgress, StockHashTable};
+    /// use std::path::Path;
+    /// struct Counter(usize, usize);
+    /// impl ScanProgress for Counter {
+    ///     fn on_total(&mut self, files: usize) {
+    ///         self.1 = files;
+    ///     }
+    ///     fn on_file_done(&mut self, _canon: &str, _state: ModdedState) {
+    ///         self.0 += 1;
+    ///         eprint!("\r{}/{}", self.0, self.1);
+    ///     }
+    /// }
+    /// let table = StockHashTable::new(Platform::WiiU);
+    /// let report = table.verify_game_dump_with_progress(
+    ///     Path::new("dump/base/content"),
+    ///     Some(Path::new("dump/update/content")),
+    ///     Some(Path::new("dump/dlc/content")),
+    ///     Counter(0, 0),
+    /// );
+    /// assert!(report.is_clean());
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The base game content folder, Wii U `content` or Switch `romfs`
+    /// * `update` - The update content folder, if it is separate from the base folder
+    /// * `aoc` - The DLC content folder, if DLC files should be checked
+    /// * `progress` - The observer to tell about each file, or `()` for none
+    pub fn verify_game_dump_with_progress<P: ScanProgress>(
+        &self,
+        content: &Path,
+        update: Option<&Path>,
+        aoc: Option<&Path>,
+        mut progress: P,
+    ) -> DumpReport {
+        let mut report = DumpReport::default();
+        let mut roots = Vec::with_capacity(3);
+        if let Some(update) = update.filter(|update| !same_folder(content, update)) {
+            roots.push((update, RootKind::Update));
+        }
+        roots.push((content, RootKind::Base));
+        roots.extend(aoc.map(|aoc| (aoc, RootKind::Dlc)));
+        let mut seen = HashSet::new();
+        let mut nested = HashSet::new();
+        let mut files = Vec::new();
+        for (root, kind) in roots {
+            for result in canonicalize_dir(root).root_kind(kind) {
+                match result {
+                    Ok((path, canon)) => {
+                        if seen.insert(canon.clone()) {
+                            files.push((path, canon));
+                        }
+                    }
+                    Err(e) => report.errors.push((error_path(&e), e.to_string())),
+                }
+            }
+        }
+        progress.on_total(files.len());
+        let mut data = Vec::new();
+        let mut buf = Vec::new();
+        for (path, canon) in files {
+            progress.on_file_start(&path, &canon);
+            data.clear();
+            let read = fs::File::open(&path).and_then(|mut file| file.read_to_end(&mut data));
+            let state = read.map(|_| {
+                progress.on_bytes(data.len() as u64);
+                if is_archive(&path) {
+                    add_nested(&canon, &canon, &data, &mut seen, &mut nested);
+                }
+                let state = self.check_file_with_buf(&canon, &data, &mut buf);
+                progress.on_file_done(&canon, state);
+                state
+            });
+            match state {
+                Ok(ModdedState::Vanilla) => report.checked += 1,
+                Ok(ModdedState::NewFile) => {
+                    report.checked += 1;
+                    report.extra.push((path, canon));
+                }
+                Ok(state) => {
+                    report.checked += 1;
+                    report.mismatched.push((path, canon, state));
+                }
+                Err(e) => {
+                    let error = io_error(&path, &e);
+                    progress.on_file_error(&path, &error);
+                    report.errors.push((path, error.to_string()));
+                }
+            }
+        }
+        report.missing = self
+            .get_stock_files()
+            .filter(|canon| aoc.is_some() || !canon.starts_with("Aoc/"))
+            .filter(|canon| !seen.contains(*canon) && !nested.contains(&canon.to_lowercase()))
+            .map(str::to_owned)
+            .collect();
+        report.missing.sort_unstable();
+        report.extra.sort_by(|a, b| a.1.cmp(&b.1));
+        report.mismatched.sort_by(|a, b| a.1.cmp(&b.1));
+        report.errors.sort();
+        report
+    }
+}
+
+/// Checks whether two paths are the same folder, following symlinks when both exist
+fn same_folder(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Checks whether a file has the extension of a SARC archive
+fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SARC_EXTS.contains(&ext))
+}
+
+/// Adds the canonical resource paths of the files in an archive, at any depth, to the files
+/// found in the dump, and the lowercase paths the stock tables also list them by, which are
+/// the path of the archive without its extension followed by their own path. Data which is not
+/// a SARC archive or cannot be decompressed is skipped.
+fn add_nested(
+    parent_canon: &str,
+    parent_listed: &str,
+    data: &[u8],
+    seen: &mut HashSet<String>,
+    nested: &mut HashSet<String>,
+) {
+    let decompressed;
+    let data = if data.starts_with(b"Yaz0") {
+        match roead::yaz0::decompress(data) {
+            Ok(data) => {
+                decompressed = data;
+                decompressed.as_slice()
+            }
+            Err(_) => return,
+        }
+    } else {
+        data
+    };
+    for (name, file) in read_sarc(data).into_iter().flatten() {
+        let canon = get_nested_file_canon(parent_canon, name);
+        let stem = match parent_listed.rfind('.') {
+            Some(dot) if !parent_listed[dot..].contains('/') => &parent_listed[..dot],
+            _ => parent_listed,
+        };
+        let listed = [stem, "/", canon.strip_prefix("Aoc/0010/").unwrap_or(&canon)].concat();
+        if is_archive(Path::new(name)) {
+            add_nested(&canon, &listed, file, seen, nested);
+        }
+        nested.insert(listed.to_lowercase());
+        seen.insert(canon);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashes::compute_file_hash;
+    use crate::hashes::sarc::tests::write_sarc;
+    use crate::test_util::TempDir;
+    use crate::CanonError;
+
+    #[derive(Default)]
+    struct Events {
+        total: Option<usize>,
+        started: Vec<String>,
+        done: Vec<String>,
+        bytes: u64,
+    }
+
+    impl ScanProgress for Events {
+        fn on_total(&mut self, files: usize) {
+            self.total = Some(files);
+        }
+
+        fn on_file_start(&mut self, _path: &Path, canon: &str) {
+            self.started.push(canon.to_owned());
+        }
+
+        fn on_file_done(&mut self, canon: &str, _state: ModdedState) {
+            self.done.push(canon.to_owned());
+        }
+
+        fn on_file_error(&mut self, path: &Path, _error: &CanonError) {
+            self.done.push(path.display().to_string());
+        }
+
+        fn on_bytes(&mut self, bytes: u64) {
+            self.bytes += bytes;
+        }
+    }
+
+    #[test]
+    fn verify_dump() {
+        let dir = TempDir::new("dump");
+        let game_data = roead::yaz0::compress(write_sarc(&[("/Flags.bgdata", b"Flags")], false));
+        let bootup = write_sarc(
+            &[
+                ("Event/EventInfo.product.sbyml", b"Events".as_ref()),
+                ("GameData/gamedata.ssarc", &game_data),
+            ],
+            false,
+        );
+        let files: &[(&str, &[u8])] = &[
+            ("base/Pack/Bootup.pack", &bootup),
+            ("base/Actor/Physics/A.bphysics", b"Stock physics"),
+            ("base/Actor/Physics/B.bphysics", b"Base physics"),
+            ("base/Model/C.sbfres", b"Changed model"),
+            ("base/Hellow/Sweetie.tardis", b"New file"),
+            ("update/Actor/Physics/B.bphysics", b"Updated physics"),
+            ("dlc/0010/Pack/AocMainField.pack", b"DLC pack"),
+        ];
+        for (file, data) in files {
+            dir.write(file, data);
+        }
+        let mut table = StockHashTable::from_json("{}").unwrap();
+        for (canon, data) in &[
+            ("Pack/Bootup.pack", bootup.as_slice()),
+            ("Event/EventInfo.product.byml", b"Events"),
+            ("Pack/Bootup/event/EventInfo.product.byml", b"Events"),
+            ("GameData/gamedata.sarc", b"Game data"),
+            ("/Flags.bgdata", b"Flags"),
+            ("Actor/Physics/A.bphysics", b"Stock physics"),
+            ("Actor/Physics/B.bphysics", b"Updated physics"),
+            ("Model/C.bfres", b"Stock model"),
+            ("Model/D.bfres", b"Lost model"),
+            ("Pack/Bootup/GameData/gamedata.sarc", b"Game data"),
+            ("Aoc/0010/Pack/AocMainField.pack", b"DLC pack"),
+            ("Aoc/0010/Map/AocField.mubin", b"Lost map"),
+        ] {
+            table.add_hash(canon, compute_file_hash(data).unwrap());
+        }
+
+        let mut events = Events::default();
+        let report = table.verify_game_dump_with_progress(
+            &dir.join("base"),
+            Some(&dir.join("update")),
+            None,
+            &mut events,
+        );
+        assert_eq!(events.total, Some(5));
+        assert_eq!(events.started.len(), 5);
+        assert_eq!(events.started[0], "Actor/Physics/B.bphysics");
+        assert_eq!(events.started, events.done);
+        assert_eq!(
+            events.bytes,
+            files[..6]
+                .iter()
+                .map(|(_, data)| data.len() as u64)
+                .sum::<u64>()
+                - b"Base physics".len() as u64
+        );
+        assert_eq!(report.checked, 5);
+        assert_eq!(report.missing, ["Model/D.bfres"]);
+        assert_eq!(
+            report.extra,
+            [(
+                dir.join("base/Hellow/Sweetie.tardis"),
+                "Hellow/Sweetie.tardis".to_owned()
+            )]
+        );
+        assert_eq!(
+            report.mismatched,
+            [(
+                dir.join("base/Model/C.sbfres"),
+                "Model/C.bfres".to_owned(),
+                ModdedState::Modified {
+                    hash: compute_file_hash(b"Changed model").unwrap()
+                }
+            )]
+        );
+        assert!(report.errors.is_empty());
+        assert!(!report.is_clean());
+        #[cfg(feature = "serde")]
+        assert_eq!(
+            serde_json::from_str::<DumpReport>(&serde_json::to_string(&report).unwrap()).unwrap(),
+            report
+        );
+
+        let shared = table.verify_game_dump(
+            &dir.join("base"),
+            Some(&dir.join("base")),
+            Some(&dir.join("dlc")),
+        );
+        assert_eq!(shared.checked, 6);
+        assert_eq!(
+            shared.missing,
+            ["Aoc/0010/Map/AocField.mubin", "Model/D.bfres"]
+        );
+        assert_eq!(
+            shared
+                .mismatched
+                .iter()
+                .map(|(_, canon, _)| canon.as_str())
+                .collect::<Vec<_>>(),
+            ["Actor/Physics/B.bphysics", "Model/C.bfres"]
+        );
+
+        let gone = table.verify_game_dump(&dir.join("missing"), None, None);
+        assert_eq!(gone.checked, 0);
+        assert_eq!(gone.errors.len(), 1);
+        assert_eq!(gone.missing.len(), 10);
+    }
+}
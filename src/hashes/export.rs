@@ -0,0 +1,204 @@
+use super::{Algorithm, StockHashTable};
+use std::io::{self, Write};
+
+impl StockHashTable {
+    /// Iterates the files in the table and their accepted hashes, sorted by canonical resource
+    /// path
+    fn sorted_entries(&self) -> impl Iterator<Item = (&str, &[u64])> {
+        (0..self.len()).map(move |position| {
+            let canon = self.sorted_name(position);
+            (canon, self.find_exact(canon).unwrap_or_default())
+        })
+    }
+
+    /// Writes the table as JSON in the schema of the stock tables, an object of canonical
+    /// resource paths to arrays of accepted hashes, which
+    /// [`from_json`](StockHashTable::from_json) loads back as the same table. The files are
+    /// sorted with one on each line, so exported tables can be compared with a diff. Tables with
+    /// another [`Algorithm`] than xxHash64 are written with it, wrapping the object as `hashes`.
+    /// Example:
+    ///
+    /// ```
+    /// use botw_utils::hashes::StockHashTable;
+    /// let mut table = StockHashTable::from_json(r#"{"Pack/TitleBG.pack": [3]}"#).unwrap();
+    /// table.add_entry("Pack/Bootup.pack".to_owned(), vec![2, 1]);
+    /// assert_eq!(
+    ///     table.to_json(),
+    ///     "{\n  \"Pack/Bootup.pack\": [1, 2],\n  \"Pack/TitleBG.pack\": [3]\n}"
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{");
+        for (index, (canon, hashes)) in self.sorted_entries().enumerate() {
+            json.push_str(if index == 0 { "\n  " } else { ",\n  " });
+            json.push_str(&serde_json::to_string(canon).unwrap());
+            json.push_str(": [");
+            for (index, hash) in hashes.iter().enumerate() {
+                if index > 0 {
+                    json.push_str(", ");
+                }
+                json.push_str(&hash.to_string());
+            }
+            json.push(']');
+        }
+        json.push_str(if self.is_empty() { "}" } else { "\n}" });
+        if self.algorithm() == Algorithm::Xxh64 {
+            return json;
+        }
+        format!(
+            "{{\n  \"algorithm\": \"{}\",\n  \"hashes\": {}\n}}",
+            self.algorithm(),
+            json.replace('\n', "\n  ")
+        )
+    }
+
+    /// Writes the table as MessagePack, in the same shape as [`to_json`](StockHashTable::to_json)
+    /// writes it, as a map of canonical resource paths to arrays of accepted hashes sorted by
+    /// path, with every value in its smallest encoding, and wrapped with the algorithm like the
+    /// JSON is if the table has another one than xxHash64
+    #[cfg(feature = "msgpack")]
+    pub fn to_msgpack(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        if self.algorithm() != Algorithm::Xxh64 {
+            let name = self.algorithm().as_str();
+            out.extend_from_slice(b"\x82\xA9algorithm");
+            out.push(0xA0 | name.len() as u8);
+            out.extend_from_slice(name.as_bytes());
+            out.extend_from_slice(b"\xA6hashes");
+        }
+        msgpack_len(&mut out, self.len(), 0x80, 0xDE);
+        for (canon, hashes) in self.sorted_entries() {
+            if canon.len() < 32 {
+                out.push(0xA0 | canon.len() as u8);
+            } else if canon.len() <= 0xFF {
+                out.extend_from_slice(&[0xD9, canon.len() as u8]);
+            } else {
+                msgpack_len(&mut out, canon.len(), 0xA0, 0xDA);
+            }
+            out.extend_from_slice(canon.as_bytes());
+            msgpack_len(&mut out, hashes.len(), 0x90, 0xDC);
+            for hash in hashes {
+                match *hash {
+                    hash if hash < 0x80 => out.push(hash as u8),
+                    hash if hash <= 0xFF => out.extend_from_slice(&[0xCC, hash as u8]),
+                    hash if hash <= 0xFFFF => {
+                        out.push(0xCD);
+                        out.extend_from_slice(&(hash as u16).to_be_bytes());
+                    }
+                    hash if hash <= 0xFFFF_FFFF => {
+                        out.push(0xCE);
+                        out.extend_from_slice(&(hash as u32).to_be_bytes());
+                    }
+                    hash => {
+                        out.push(0xCF);
+                        out.extend_from_slice(&hash.to_be_bytes());
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Writes the table as CSV with `canon` and `hash` columns and a row for each accepted hash
+    /// of each file, sorted by canonical resource path
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Where to write the CSV
+    ///
+    /// # Returns
+    ///
+    /// Returns an I/O error if the CSV could not be written
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(b"canon,hash\n")?;
+        for (canon, hashes) in self.sorted_entries() {
+            let quoted;
+            let canon = if canon.contains([',', '"', '\n']) {
+                quoted = format!("\"{}\"", canon.replace('"', "\"\""));
+                quoted.as_str()
+            } else {
+                canon
+            };
+            for hash in hashes {
+                writeln!(writer, "{},{}", canon, hash)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes the header of a MessagePack map, array, or string of a length, as the fixed form
+/// starting at `fixed` if it fits, or else the 16 bit form at `wide` or the 32 bit form after it
+#[cfg(feature = "msgpack")]
+fn msgpack_len(out: &mut Vec<u8>, len: usize, fixed: u8, wide: u8) {
+    let fixed_max = if fixed == 0xA0 { 31 } else { 15 };
+    if len <= fixed_max {
+        out.push(fixed | len as u8);
+    } else if len <= 0xFFFF {
+        out.push(wide);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(wide + 1);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "wiiu-data")]
+    fn exported_tables() {
+        use crate::hashes::{Platform, StockHashTable};
+        let mut tbl = StockHashTable::new(Platform::WiiU);
+        tbl.add_hash("Pack/Bootup.pack", 1);
+        tbl.add_entry("Hellow/Sweetie,\"Tardis\".pack".to_owned(), vec![300, 2]);
+        let json = tbl.to_json();
+        assert_eq!(StockHashTable::from_json(&json).unwrap(), tbl);
+        assert!(json.starts_with("{\n  \"/"));
+        assert!(json.contains("\n  \"Hellow/Sweetie,\\\"Tardis\\\".pack\": [2, 300],\n"));
+        assert_eq!(StockHashTable::from_json("{}").unwrap().to_json(), "{}");
+        let crcs =
+            StockHashTable::from_json(r#"{"algorithm": "crc32", "hashes": {"A": [1]}}"#).unwrap();
+        let json = crcs.to_json();
+        assert_eq!(
+            json,
+            "{\n  \"algorithm\": \"crc32\",\n  \"hashes\": {\n    \"A\": [1]\n  }\n}"
+        );
+        assert_eq!(StockHashTable::from_json(&json).unwrap(), crcs);
+
+        let mut csv = Vec::new();
+        tbl.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("canon,hash\n"));
+        assert!(csv.contains("\n\"Hellow/Sweetie,\"\"Tardis\"\".pack\",2\n"));
+        assert!(csv.contains("\nPack/Bootup.pack,1\n"));
+        let hashes: usize = tbl.iter().map(|(_, hashes)| hashes.len()).sum();
+        assert_eq!(csv.lines().count(), hashes + 1);
+    }
+
+    #[cfg(all(feature = "msgpack", feature = "switch-data"))]
+    #[test]
+    fn msgpack_tables() {
+        use crate::hashes::{Platform, StockHashTable};
+        let json = r#"{"Pack/Bootup.pack": [1, 200, 70000], "A": [18446744073709551615]}"#;
+        let tbl = StockHashTable::from_json(json).unwrap();
+        let mut expected = vec![0x82, 0xA1, b'A', 0x91, 0xCF];
+        expected.extend_from_slice(&[0xFF; 8]);
+        expected.push(0xB0);
+        expected.extend_from_slice(b"Pack/Bootup.pack");
+        expected.extend_from_slice(&[0x93, 0x01, 0xCC, 200, 0xCE, 0, 1, 0x11, 0x70]);
+        assert_eq!(tbl.to_msgpack(), expected);
+        let crcs =
+            StockHashTable::from_json(r#"{"algorithm": "xxh3", "hashes": {"A": [1]}}"#).unwrap();
+        let mut expected = vec![0x82, 0xA9];
+        expected.extend_from_slice(b"algorithm");
+        expected.push(0xA4);
+        expected.extend_from_slice(b"xxh3");
+        expected.push(0xA6);
+        expected.extend_from_slice(b"hashes");
+        expected.extend_from_slice(&[0x81, 0xA1, b'A', 0x91, 0x01]);
+        assert_eq!(crcs.to_msgpack(), expected);
+        let packed = StockHashTable::new(Platform::Switch).to_msgpack();
+        assert_eq!(packed[0], 0xDF);
+    }
+}
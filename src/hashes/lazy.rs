@@ -0,0 +1,276 @@
+use super::{
+    check_data, embedded_metadata, Algorithm, GameVersion, Indexes, ModdedState, Platform,
+    StockData, StockHashTable, Storage, TableError, TableSource,
+};
+use std::fmt;
+
+/// Looking up files and their accepted hashes, shared by [`StockHashTable`] and
+/// [`LazyHashTable`] so code which only checks files can take either one. Example:
+///
+#[cfg_attr(feature = "wiiu-data", doc = "```")]
+#[cfg_attr(not(feature = "wiiu-data"), doc = "```ignore")]
+/// use botw_utils::hashes::{HashLookup, LazyHashTable, Platform, StockHashTable};
+/// fn count_modded(table: &dyn HashLookup, files: &[(&str, &[u8])]) -> usize {
+///     files
+///         .iter()
+///         .filter(|(canon, data)| table.is_file_modded(canon, data, false))
+///         .count()
+/// }
+/// let files = [("Pack/Bootup.pack", b"Random data".as_ref())];
+/// assert_eq!(count_modded(&LazyHashTable::new(Platform::WiiU), &files), 1);
+/// assert_eq!(count_modded(&StockHashTable::new(Platform::WiiU), &files), 1);
+/// ```
+pub trait HashLookup {
+    /// Gets the sorted accepted hashes of a file, or None if it is not in the table
+    ///
+    /// # Arguments
+    ///
+    /// * `canon` - The canonical resource name of the file
+    fn get_hashes(&self, canon: &str) -> Option<&[u64]>;
+
+    /// Gets the algorithm the table's hashes are made with
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::Xxh64
+    }
+
+    /// Checks a file against its accepted hashes like [`StockHashTable::check_file_with_buf`],
+    /// decompressing yaz0 data into a scratch buffer which can be reused between calls
+    ///
+    /// # Arguments
+    ///
+    /// * `canon` - The canonical resource name of the file
+    /// * `data` - The binary data for the file
+    /// * `buf` - The buffer to decompress into. Its contents are replaced.
+    fn check_file_with_buf(&self, canon: &str, data: &[u8], buf: &mut Vec<u8>) -> ModdedState {
+        match self.get_hashes(canon) {
+            Some(hashes) => {
+                check_data(hashes, self.algorithm(), data, buf).unwrap_or(ModdedState::InvalidYaz0)
+            }
+            None => ModdedState::NewFile,
+        }
+    }
+
+    /// Checks if a file is in the table
+    ///
+    /// # Arguments
+    ///
+    /// * `canon` - The canonical resource name of the file
+    fn contains(&self, canon: &str) -> bool {
+        self.get_hashes(canon).is_some()
+    }
+
+    /// Checks a file against its accepted hashes like [`StockHashTable::check_file`]
+    ///
+    /// # Arguments
+    ///
+    /// * `canon` - The canonical resource name of the file
+    /// * `data` - The binary data for the file
+    fn check_file(&self, canon: &str, data: &[u8]) -> ModdedState {
+        self.check_file_with_buf(canon, data, &mut Vec::new())
+    }
+
+    /// Checks a file to see if it has been modified like [`StockHashTable::is_file_modded`]
+    ///
+    /// # Arguments
+    ///
+    /// * `canon` - The canonical resource name of the file
+    /// * `data` - The binary data for the file
+    /// * `flag_new` - Whether to count files not in the table as modified
+    fn is_file_modded(&self, canon: &str, data: &[u8], flag_new: bool) -> bool {
+        match self.check_file(canon, data) {
+            ModdedState::Vanilla => false,
+            ModdedState::NewFile => flag_new,
+            ModdedState::Modified { .. } | ModdedState::InvalidYaz0 | ModdedState::TooShort => true,
+        }
+    }
+
+    /// Checks a hash computed ahead of time like [`StockHashTable::is_hash_modded`]
+    ///
+    /// # Arguments
+    ///
+    /// * `canon` - The canonical resource name of the file
+    /// * `hash` - The hash of the file's decompressed data, made with the table's algorithm
+    /// * `flag_new` - Whether to count files not in the table as modified
+    fn is_hash_modded(&self, canon: &str, hash: u64, flag_new: bool) -> bool {
+        match self.get_hashes(canon) {
+            Some(hashes) => hashes.binary_search(&hash).is_err(),
+            None => flag_new,
+        }
+    }
+}
+
+impl HashLookup for StockHashTable {
+    fn get_hashes(&self, canon: &str) -> Option<&[u64]> {
+        self.find(canon)
+    }
+
+    fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    fn check_file_with_buf(&self, canon: &str, data: &[u8], buf: &mut Vec<u8>) -> ModdedState {
+        StockHashTable::check_file_with_buf(self, canon, data, buf)
+    }
+}
+
+/// A read-only handle to the embedded stock hash table for a platform, for tools which only
+/// check a few files. It looks files up by binary search in the packed table shared with
+/// [`StockHashTable`], the same way packed tables do, but has none of their support for
+/// changes, custom tables, case-insensitive lookups, or indexes, so making one is only a
+/// pointer copy once the platform's table has been unpacked, which still happens the first
+/// time any table for it is made. It always uses the embedded table, ignoring the environment
+/// variables [`StockHashTable::new`] reads. Its lookups are in [`HashLookup`]. Example:
+///
+#[cfg_attr(feature = "switch-data", doc = "```")]
+#[cfg_attr(not(feature = "switch-data"), doc = "```ignore")]
+/// use botw_utils::hashes::{HashLookup, LazyHashTable, Platform};
+/// let table = LazyHashTable::new(Platform::Switch);
+/// assert!(table.contains("Pack/Bootup.pack"));
+/// assert!(table.is_file_modded("Pack/Bootup.pack", b"Random data", true));
+/// ```
+#[derive(Clone, Copy)]
+pub struct LazyHashTable {
+    platform: Platform,
+    data: &'static StockData,
+}
+
+impl LazyHashTable {
+    /// Gets the embedded stock hash table for a platform, unpacking it the first time any
+    /// table for the platform is made
+    ///
+    /// # Arguments
+    ///
+    /// * `platform` - Specifies whether to use the Wii U 1.5.0 or Switch 1.6.0 hash table
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table for the platform is not embedded, see
+    /// [`has_stock_hashes`](super::has_stock_hashes), or is corrupt.
+    pub fn new(platform: Platform) -> LazyHashTable {
+        LazyHashTable::try_new(platform).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Gets the embedded stock hash table for a platform like [`new`](LazyHashTable::new), or
+    /// a [`TableError`] if it is not embedded or is corrupt
+    ///
+    /// # Arguments
+    ///
+    /// * `platform` - Specifies whether to use the Wii U 1.5.0 or Switch 1.6.0 hash table
+    pub fn try_new(platform: Platform) -> Result<LazyHashTable, TableError> {
+        StockData::try_get(platform).map(|data| LazyHashTable { platform, data })
+    }
+
+    /// Gets the platform the table is for
+    pub fn platform(&self) -> Platform {
+        self.platform
+    }
+
+    /// Gets the number of files in the table
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Checks if the table has no files in it
+    pub fn is_empty(&self) -> bool {
+        self.data.len() == 0
+    }
+}
+
+impl fmt::Debug for LazyHashTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyHashTable")
+            .field("platform", &self.platform)
+            .field("files", &self.len())
+            .finish()
+    }
+}
+
+impl HashLookup for LazyHashTable {
+    fn get_hashes(&self, canon: &str) -> Option<&[u64]> {
+        self.data.find(canon)
+    }
+
+    fn check_file_with_buf(&self, canon: &str, data: &[u8], buf: &mut Vec<u8>) -> ModdedState {
+        match self.data.find(canon) {
+            Some(hashes) => {
+                check_data(hashes, Algorithm::Xxh64, data, buf).unwrap_or(ModdedState::InvalidYaz0)
+            }
+            None => ModdedState::NewFile,
+        }
+    }
+}
+
+/// Makes a full table sharing the handle's packed data, for when a tool turns out to need
+/// more than lookups
+impl From<LazyHashTable> for StockHashTable {
+    fn from(lazy: LazyHashTable) -> StockHashTable {
+        StockHashTable {
+            source: TableSource::Stock {
+                platform: lazy.platform,
+                version: GameVersion::latest(lazy.platform),
+            },
+            storage: Storage::Stock(lazy.data),
+            indexes: Indexes::default(),
+            case_insensitive: false,
+            algorithm: Algorithm::Xxh64,
+            metadata: embedded_metadata(lazy.platform).clone(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "wiiu-data"))]
+mod tests {
+    use super::*;
+    use crate::hashes::compute_file_hash;
+
+    fn states(table: &dyn HashLookup, files: &[(&str, &[u8])]) -> Vec<ModdedState> {
+        files
+            .iter()
+            .map(|(canon, data)| table.check_file(canon, data))
+            .collect()
+    }
+
+    #[test]
+    fn lazy_lookups() {
+        let lazy = LazyHashTable::new(Platform::WiiU);
+        let full = StockHashTable::new(Platform::WiiU);
+        assert_eq!(lazy.len(), full.len());
+        assert_eq!(lazy.platform(), Platform::WiiU);
+        assert_eq!(
+            format!("{:?}", lazy),
+            format!("LazyHashTable {{ platform: WiiU, files: {} }}", full.len())
+        );
+        let canon = "Actor/ModelList/DgnMrgPrt_Dungeon023.bmodellist";
+        assert_eq!(HashLookup::get_hashes(&lazy, canon), full.get_hashes(canon));
+        assert!(!lazy.is_hash_modded(canon, 3_305_211_212_481_695_363, true));
+        assert!(lazy.is_hash_modded("Hellow/Sweetie.tardis", 1, true));
+        let files: &[(&str, &[u8])] = &[
+            (canon, b"Random data"),
+            (canon, b""),
+            (canon, b"Yaz0 broken"),
+            ("Hellow/Sweetie.tardis", b"Random data"),
+        ];
+        assert_eq!(states(&lazy, files), states(&full, files));
+        assert_eq!(
+            states(&lazy, files)[..3],
+            [
+                ModdedState::Modified {
+                    hash: compute_file_hash(b"Random data").unwrap()
+                },
+                ModdedState::TooShort,
+                ModdedState::InvalidYaz0
+            ]
+        );
+        assert_eq!(StockHashTable::from(lazy), full);
+
+        let crcs = StockHashTable::from_json(
+            r#"{"algorithm": "crc32", "hashes": {"Pack/Bootup.pack": [639479525]}}"#,
+        )
+        .unwrap();
+        assert_eq!(HashLookup::algorithm(&crcs), Algorithm::Crc32);
+        assert_eq!(
+            states(&crcs, &[("Pack/Bootup.pack", b"1234567890")]),
+            [ModdedState::Vanilla]
+        );
+    }
+}
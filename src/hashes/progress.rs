@@ -0,0 +1,144 @@
+use super::ModdedState;
+use crate::CanonError;
+use std::path::Path;
+
+/// An observer of a long-running check, like
+#[cfg_attr(
+    feature = "walk",
+    doc = "[`scan_dir_with_progress`](super::StockHashTable::scan_dir_with_progress) or"
+)]
+#[cfg_attr(not(feature = "walk"), doc = "`scan_dir_with_progress` or")]
+#[cfg_attr(
+    all(feature = "walk", feature = "sarc"),
+    doc = "[`verify_game_dump_with_progress`](super::StockHashTable::verify_game_dump_with_progress),"
+)]
+#[cfg_attr(
+    not(all(feature = "walk", feature = "sarc")),
+    doc = "`verify_game_dump_with_progress`,"
+)]
+/// for showing progress in a frontend. Every method does nothing by default, so an observer
+/// only implements the events it shows.
+///
+/// The methods are called on the thread doing the check, the one which called it, one at a
+/// time and in order, so an observer needs no locking and does not have to be [`Send`]. For
+/// each file, [`on_file_start`](ScanProgress::on_file_start) comes first, then
+/// [`on_bytes`](ScanProgress::on_bytes) once its data has been read, then either
+/// [`on_file_done`](ScanProgress::on_file_done) or
+/// [`on_file_error`](ScanProgress::on_file_error). The check waits for each call to return,
+/// so a slow observer slows the check down.
+///
+/// `()` is the observer which ignores everything. The checks are generic over the observer, so
+/// with `()` the calls are inlined away and cost nothing. Wiring it up to an
+/// [`indicatif`](https://docs.rs/indicatif) progress bar:
+///
+/// ```ignore
+/// use botw_utils::hashes::{ModdedState, Platform, ScanProgress, StockHashTable};
+/// use indicatif::ProgressBar;
+/// use std::path::Path;
+///
+/// struct Bar(ProgressBar);
+///
+/// impl ScanProgress for Bar {
+///     fn on_total(&mut self, files: usize) {
+///         self.0.set_length(files as u64);
+///     }
+///     fn on_file_start(&mut self, _path: &Path, canon: &str) {
+///         self.0.set_message(canon.to_owned());
+///     }
+///     fn on_file_done(&mut self, _canon: &str, _state: ModdedState) {
+///         self.0.inc(1);
+///     }
+///     fn on_file_error(&mut self, _path: &Path, _error: &botw_utils::CanonError) {
+///         self.0.inc(1);
+///     }
+/// }
+///
+/// let table = StockHashTable::new(Platform::Switch);
+/// let mut bar = Bar(ProgressBar::new(0));
+/// let report =
+///     table.verify_game_dump_with_progress(Path::new("dump/romfs"), None, None, &mut bar);
+/// bar.0.finish_with_message(format!("{} files changed", report.mismatched.len()));
+/// ```
+pub trait ScanProgress {
+    /// Called once the number of files to check is known, before any are checked. Checks
+    /// which walk files as they go, like
+    #[cfg_attr(
+        feature = "walk",
+        doc = "[`scan_dir_with_progress`](super::StockHashTable::scan_dir_with_progress), do not"
+    )]
+    #[cfg_attr(not(feature = "walk"), doc = "`scan_dir_with_progress`, do not")]
+    /// know it and never call this.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The number of files which will be checked
+    fn on_total(&mut self, files: usize) {
+        let _ = files;
+    }
+
+    /// Called when a file is about to be read and checked
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file, or for files in an archive, its name in the archive
+    /// * `canon` - The canonical resource path of the file
+    fn on_file_start(&mut self, path: &Path, canon: &str) {
+        let _ = (path, canon);
+    }
+
+    /// Called when a file has been checked
+    ///
+    /// # Arguments
+    ///
+    /// * `canon` - The canonical resource path of the file
+    /// * `state` - How the file compares to stock
+    fn on_file_done(&mut self, canon: &str, state: ModdedState) {
+        let _ = (canon, state);
+    }
+
+    /// Called when a file could not be read, instead of
+    /// [`on_file_done`](ScanProgress::on_file_done)
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file
+    /// * `error` - Why it could not be read
+    fn on_file_error(&mut self, path: &Path, error: &CanonError) {
+        let _ = (path, error);
+    }
+
+    /// Called with the size of the data of a file once it has been read. For the files in an
+    /// archive, only the files directly inside it are counted, so the sizes add up to about
+    /// the size of the archive.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The number of bytes read
+    fn on_bytes(&mut self, bytes: u64) {
+        let _ = bytes;
+    }
+}
+
+impl ScanProgress for () {}
+
+impl<P: ScanProgress + ?Sized> ScanProgress for &mut P {
+    fn on_total(&mut self, files: usize) {
+        (**self).on_total(files)
+    }
+
+    fn on_file_start(&mut self, path: &Path, canon: &str) {
+        (**self).on_file_start(path, canon)
+    }
+
+    fn on_file_done(&mut self, canon: &str, state: ModdedState) {
+        (**self).on_file_done(canon, state)
+    }
+
+    fn on_file_error(&mut self, path: &Path, error: &CanonError) {
+        (**self).on_file_error(path, error)
+    }
+
+    fn on_bytes(&mut self, bytes: u64) {
+        (**self).on_bytes(bytes)
+    }
+}
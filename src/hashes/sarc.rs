@@ -0,0 +1,353 @@
+use super::{ModdedState, ScanProgress, StockHashTable};
+use crate::get_nested_file_canon;
+use std::convert::TryInto;
+use std::path::Path;
+
+/// How a SARC archive and the files nested in it compare to the stock game, as found by
+/// [`StockHashTable::check_sarc`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SarcCheck {
+    /// How the data of the archive as a whole compares to stock
+    pub state: ModdedState,
+    /// The files in the archive in the order they are stored, or `None` if the data is not a
+    /// SARC archive
+    pub entries: Option<Vec<SarcEntry>>,
+}
+
+/// A file in a SARC archive checked by [`StockHashTable::check_sarc`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SarcEntry {
+    /// The name of the file in the archive
+    pub name: String,
+    /// The canonical resource path of the file
+    pub canon: String,
+    /// How the file compares to stock, with the files in it if it is an archive too
+    pub check: SarcCheck,
+}
+
+impl SarcCheck {
+    /// Gets the files at any depth in the archive which are not vanilla, with each nested
+    /// archive before the files in it
+    pub fn changed(&self) -> Vec<&SarcEntry> {
+        let mut changed = Vec::new();
+        self.collect_changed(&mut changed);
+        changed
+    }
+
+    fn collect_changed<'a>(&'a self, changed: &mut Vec<&'a SarcEntry>) {
+        for entry in self.entries.iter().flatten() {
+            if entry.check.state != ModdedState::Vanilla {
+                changed.push(entry);
+            }
+            entry.check.collect_changed(changed);
+        }
+    }
+}
+
+impl StockHashTable {
+    /// Checks a SARC archive and every file in it against their stock hashes, descending into
+    /// nested archives, so that a changed pack can be narrowed down to the files in it which
+    /// were actually changed. The names of nested files are canonicalized like
+    /// [`get_nested_file_canon`] does, so files in DLC archives get the `Aoc/0010/` prefix.
+    /// The archive and nested files may be yaz0 compressed. Example:
+    ///
+    /// ```
+    /// use botw_utils::hashes::{ModdedState, Platform, StockHashTable};
+    /// let table = StockHashTable::new(Platform::Switch);
+    /// let check = table.check_sarc("Pack/Bootup.pack", b"Not an archive");
+    /// assert!(matches!(check.state, ModdedState::Modified { .. }));
+    /// assert_eq!(check.entries, None);
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `parent_canon` - The canonical resource name of the archive
+    /// * `data` - The binary data of the archive
+    pub fn check_sarc(&self, parent_canon: &str, data: &[u8]) -> SarcCheck {
+        self.check_sarc_with_progress(parent_canon, data, ())
+    }
+
+    /// Checks a SARC archive and the files in it like [`check_sarc`](StockHashTable::check_sarc),
+    /// telling an observer about each nested file as it is checked. Nested archives are told
+    /// about after the files in them, and only the files directly in the archive count towards
+    /// [`ScanProgress::on_bytes`]. [`ScanProgress::on_total`] is never called.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent_canon` - The canonical resource name of the archive
+    /// * `data` - The binary data of the archive
+    /// * `progress` - The observer to tell about each nested file, or `()` for none
+    pub fn check_sarc_with_progress<P: ScanProgress>(
+        &self,
+        parent_canon: &str,
+        data: &[u8],
+        mut progress: P,
+    ) -> SarcCheck {
+        self.check_sarc_at(parent_canon, data, &mut progress, true)
+    }
+
+    fn check_sarc_at<P: ScanProgress>(
+        &self,
+        parent_canon: &str,
+        data: &[u8],
+        progress: &mut P,
+        top: bool,
+    ) -> SarcCheck {
+        let decompressed;
+        let data = if data.starts_with(b"Yaz0") {
+            match roead::yaz0::decompress(data) {
+                Ok(data) => {
+                    decompressed = data;
+                    decompressed.as_slice()
+                }
+                Err(_) => {
+                    return SarcCheck {
+                        state: self.check_file(parent_canon, data),
+                        entries: None,
+                    }
+                }
+            }
+        } else {
+            data
+        };
+        let entries = read_sarc(data).map(|files| {
+            files
+                .into_iter()
+                .map(|(name, file)| {
+                    let canon = get_nested_file_canon(parent_canon, name);
+                    progress.on_file_start(Path::new(name), &canon);
+                    if top {
+                        progress.on_bytes(file.len() as u64);
+                    }
+                    let check = self.check_sarc_at(&canon, file, progress, false);
+                    progress.on_file_done(&canon, check.state);
+                    SarcEntry {
+                        name: name.to_owned(),
+                        canon,
+                        check,
+                    }
+                })
+                .collect()
+        });
+        SarcCheck {
+            state: self.check_file(parent_canon, data),
+            entries,
+        }
+    }
+}
+
+/// Reads the names and data of the files in a SARC archive, in either byte order. Gives `None`
+/// if the data is not a SARC archive or has a file without a name.
+pub(super) fn read_sarc(data: &[u8]) -> Option<Vec<(&str, &[u8])>> {
+    if data.get(..4)? != b"SARC" {
+        return None;
+    }
+    let big_endian = match data.get(6..8)? {
+        [0xFE, 0xFF] => true,
+        [0xFF, 0xFE] => false,
+        _ => return None,
+    };
+    let u16_at = |offset: usize| {
+        let bytes = data.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        } as usize)
+    };
+    let u32_at = |offset: usize| {
+        let bytes = data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        } as usize)
+    };
+    let data_start = u32_at(12)?;
+    let sfat = u16_at(4)?;
+    if data.get(sfat..sfat + 4)? != b"SFAT" {
+        return None;
+    }
+    let count = u16_at(sfat + 6)?;
+    let nodes = sfat + u16_at(sfat + 4)?;
+    let sfnt = nodes + count * 16;
+    if data.get(sfnt..sfnt + 4)? != b"SFNT" {
+        return None;
+    }
+    let names = sfnt + u16_at(sfnt + 4)?;
+    (0..count)
+        .map(|index| {
+            let node = nodes + index * 16;
+            let attributes = u32_at(node + 4)?;
+            if attributes >> 24 == 0 {
+                return None;
+            }
+            let name_start = names + (attributes & 0xFFFF) * 4;
+            let name_len = data.get(name_start..)?.iter().position(|byte| *byte == 0)?;
+            let name = std::str::from_utf8(&data[name_start..name_start + name_len]).ok()?;
+            let start = data_start.checked_add(u32_at(node + 8)?)?;
+            let end = data_start.checked_add(u32_at(node + 12)?)?;
+            Some((name, data.get(start..end)?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    /// Writes a SARC archive of files in the layout the game uses, without sorting them by hash
+    /// since nothing here looks files up by hash
+    pub(in crate::hashes) fn write_sarc(files: &[(&str, &[u8])], big_endian: bool) -> Vec<u8> {
+        let u16_bytes = |n: usize| {
+            if big_endian {
+                (n as u16).to_be_bytes()
+            } else {
+                (n as u16).to_le_bytes()
+            }
+        };
+        let u32_bytes = |n: usize| {
+            if big_endian {
+                (n as u32).to_be_bytes()
+            } else {
+                (n as u32).to_le_bytes()
+            }
+        };
+        let mut names = Vec::new();
+        let mut contents = Vec::new();
+        let mut nodes = Vec::new();
+        for (name, data) in files {
+            nodes.extend_from_slice(&u32_bytes(0));
+            nodes.extend_from_slice(&u32_bytes(0x0100_0000 | (names.len() / 4)));
+            nodes.extend_from_slice(&u32_bytes(contents.len()));
+            nodes.extend_from_slice(&u32_bytes(contents.len() + data.len()));
+            names.extend_from_slice(name.as_bytes());
+            names.resize((names.len() + 4) & !3, 0);
+            contents.extend_from_slice(data);
+            contents.resize((contents.len() + 7) & !7, 0);
+        }
+        let data_start = 0x14 + 0xC + nodes.len() + 8 + names.len();
+        let mut sarc = b"SARC".to_vec();
+        sarc.extend_from_slice(&u16_bytes(0x14));
+        sarc.extend_from_slice(&u16_bytes(0xFEFF));
+        sarc.extend_from_slice(&u32_bytes(data_start + contents.len()));
+        sarc.extend_from_slice(&u32_bytes(data_start));
+        sarc.extend_from_slice(&u16_bytes(0x100));
+        sarc.extend_from_slice(&[0, 0]);
+        sarc.extend_from_slice(b"SFAT");
+        sarc.extend_from_slice(&u16_bytes(0xC));
+        sarc.extend_from_slice(&u16_bytes(files.len()));
+        sarc.extend_from_slice(&u32_bytes(0x65));
+        sarc.extend_from_slice(&nodes);
+        sarc.extend_from_slice(b"SFNT");
+        sarc.extend_from_slice(&u16_bytes(8));
+        sarc.extend_from_slice(&[0, 0]);
+        sarc.extend_from_slice(&names);
+        sarc.extend_from_slice(&contents);
+        sarc
+    }
+
+    #[test]
+    #[cfg(feature = "switch-data")]
+    fn nested_archives() {
+        use super::*;
+        use crate::hashes::{compute_file_hash, Platform};
+
+        #[derive(Default)]
+        struct Done(Vec<String>, u64);
+
+        impl ScanProgress for Done {
+            fn on_file_done(&mut self, canon: &str, _state: ModdedState) {
+                self.0.push(canon.to_owned());
+            }
+
+            fn on_bytes(&mut self, bytes: u64) {
+                self.1 += bytes;
+            }
+        }
+
+        let ai_prog = b"Stock AI program".as_ref();
+        let model = b"Changed model list".as_ref();
+        let actor_pack = write_sarc(
+            &[
+                ("Actor/AIProgram/Npc_A.baiprog", ai_prog),
+                ("Actor/ModelList/Npc_A.bmodellist", model),
+            ],
+            true,
+        );
+        let compressed_pack = roead::yaz0::compress(&actor_pack);
+        let pack = write_sarc(
+            &[
+                ("Actor/Pack/Npc_A.sbactorpack", &compressed_pack),
+                ("Hellow/Sweetie.tardis", b"New file"),
+            ],
+            false,
+        );
+        let mut table = StockHashTable::new(Platform::Switch);
+        for (canon, data) in &[
+            ("Aoc/0010/Actor/AIProgram/Npc_A.baiprog", ai_prog),
+            (
+                "Aoc/0010/Actor/ModelList/Npc_A.bmodellist",
+                b"Stock".as_ref(),
+            ),
+            ("Aoc/0010/Actor/Pack/Npc_A.bactorpack", b"Stock".as_ref()),
+        ] {
+            table.add_hash(canon, compute_file_hash(data).unwrap());
+        }
+
+        let check = table.check_sarc("Aoc/0010/Pack/AocMainField.pack", &pack);
+        assert_eq!(
+            check.state,
+            table.check_file("Aoc/0010/Pack/AocMainField.pack", &pack)
+        );
+        let entries = check.entries.as_ref().unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.name.as_str(), entry.canon.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "Actor/Pack/Npc_A.sbactorpack",
+                    "Aoc/0010/Actor/Pack/Npc_A.bactorpack"
+                ),
+                ("Hellow/Sweetie.tardis", "Aoc/0010/Hellow/Sweetie.tardis")
+            ]
+        );
+        assert_eq!(entries[1].check.state, ModdedState::NewFile);
+        let nested = entries[0].check.entries.as_ref().unwrap();
+        assert_eq!(nested[0].canon, "Aoc/0010/Actor/AIProgram/Npc_A.baiprog");
+        assert_eq!(nested[0].check.state, ModdedState::Vanilla);
+        assert_eq!(nested[1].check.entries, None);
+        assert_eq!(
+            check
+                .changed()
+                .iter()
+                .map(|entry| entry.canon.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "Aoc/0010/Actor/Pack/Npc_A.bactorpack",
+                "Aoc/0010/Actor/ModelList/Npc_A.bmodellist",
+                "Aoc/0010/Hellow/Sweetie.tardis"
+            ]
+        );
+        let mut done = Done::default();
+        assert_eq!(
+            table.check_sarc_with_progress("Aoc/0010/Pack/AocMainField.pack", &pack, &mut done),
+            check
+        );
+        assert_eq!(
+            done.0,
+            [
+                "Aoc/0010/Actor/AIProgram/Npc_A.baiprog",
+                "Aoc/0010/Actor/ModelList/Npc_A.bmodellist",
+                "Aoc/0010/Actor/Pack/Npc_A.bactorpack",
+                "Aoc/0010/Hellow/Sweetie.tardis"
+            ]
+        );
+        assert_eq!(done.1, (compressed_pack.len() + b"New file".len()) as u64);
+        assert_eq!(read_sarc(b"SARC"), None);
+        assert_eq!(read_sarc(&pack[..40]), None);
+        let mut far = write_sarc(&[("A.txt", b"A")], false);
+        far[0x28..0x2C].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(read_sarc(&far), None);
+    }
+}
@@ -0,0 +1,423 @@
+use super::{HashError, ModdedState, ScanProgress, StockHashTable};
+use crate::walk::{error_path, io_error};
+use crate::{canonicalize_dir, is_auto_generated, CanonDir, CanonError};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What [`StockHashTable::scan_mod_dir`] found in a mod folder. Each file is listed with its
+/// path and, if it has one, its [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModScanReport {
+    /// Stock files which do not match any of their stock hashes, with how they differ, which
+    /// for most has the hash of their data. Stock files with data too short to be one or
+    /// corrupt yaz0 data are listed here too.
+    pub modified: Vec<(PathBuf, String, ModdedState)>,
+    /// Modified files which merge tools regenerate, like `Actor/ActorInfo.product.byml`, see
+    /// [`is_auto_generated`](crate::is_auto_generated). They are listed in `modified` instead
+    /// when [`ScanOptions::separate_auto_generated`] is off.
+    pub auto_generated: Vec<(PathBuf, String, ModdedState)>,
+    /// Stock files with yaz0 data which could not be decompressed, with the reason why, when
+    /// [`ScanOptions::invalid_yaz0`] is [`InvalidYaz0Policy::Warn`]. They are listed in
+    /// `modified` instead by default.
+    pub invalid_yaz0: Vec<(PathBuf, String, String)>,
+    /// Files which are not in the stock game
+    pub new: Vec<(PathBuf, String)>,
+    /// Stock files which match one of their stock hashes, so the mod does not need them
+    pub vanilla: Vec<(PathBuf, String)>,
+    /// Files which do not have canonical resource paths, like a graphic pack's `rules.txt`,
+    /// with the reason
+    pub uncanonical: Vec<(PathBuf, String)>,
+    /// Junk files like `Thumbs.db` and `.DS_Store` which were not checked because they match
+    /// an ignore pattern of the walk, see [`default_ignores`](crate::default_ignores)
+    pub junk: Vec<PathBuf>,
+}
+
+/// Options for how [`StockHashTable::scan_dir_with`] sorts the files of a mod. Example:
+///
+/// ```no_run
+/// use botw_utils::{canonicalize_dir, hashes::{Platform, ScanOptions, StockHashTable}};
+/// let table = StockHashTable::new(Platform::WiiU);
+/// let raw = ScanOptions::new().separate_auto_generated(false);
+/// let report = table.scan_dir_with(canonicalize_dir("mods/My Mod"), raw).unwrap();
+/// assert!(report.auto_generated.is_empty());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScanOptions {
+    separate_auto_generated: bool,
+    invalid_yaz0: InvalidYaz0Policy,
+}
+
+/// What [`StockHashTable::scan_dir_with`] does with stock files which start with a yaz0 header
+/// but cannot be decompressed, which are usually corrupt or truncated downloads rather than
+/// changes someone made on purpose
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum InvalidYaz0Policy {
+    /// List them in [`ModScanReport::modified`] as [`ModdedState::InvalidYaz0`]
+    #[default]
+    Modified,
+    /// List them in [`ModScanReport::invalid_yaz0`] with the reason they could not be
+    /// decompressed, and carry on
+    Warn,
+    /// Stop the scan with a [`CanonError::InvalidYaz0`] error with the reason they could not be
+    /// decompressed
+    Error,
+}
+
+impl ScanOptions {
+    /// Makes ScanOptions with the defaults, which list modified files that merge tools
+    /// regenerate apart from the other modified files, and files with invalid yaz0 data as
+    /// modified
+    pub const fn new() -> ScanOptions {
+        ScanOptions {
+            separate_auto_generated: true,
+            invalid_yaz0: InvalidYaz0Policy::Modified,
+        }
+    }
+
+    /// Sets whether modified files which merge tools regenerate are listed in
+    /// [`ModScanReport::auto_generated`] instead of with the other modified files
+    pub const fn separate_auto_generated(mut self, separate: bool) -> ScanOptions {
+        self.separate_auto_generated = separate;
+        self
+    }
+
+    /// Sets what is done with stock files whose yaz0 data cannot be decompressed
+    pub const fn invalid_yaz0(mut self, policy: InvalidYaz0Policy) -> ScanOptions {
+        self.invalid_yaz0 = policy;
+        self
+    }
+}
+
+impl Default for ScanOptions {
+    fn default() -> ScanOptions {
+        ScanOptions::new()
+    }
+}
+
+impl StockHashTable {
+    /// Walks a mod folder like [`canonicalize_dir`] and checks each file in it against the table
+    /// like [`check_file`](StockHashTable::check_file), sorting the files into a [`ModScanReport`] of which are modified, new, vanilla, and which have
+    /// no canonical resource path. Modified files which merge tools regenerate are listed
+    /// apart from the others. Junk files matching the [default
+    /// ignores](crate::default_ignores) are listed apart without being checked; use
+    /// [`scan_dir`](StockHashTable::scan_dir) to change which are ignored. Example:
+    ///
+    /// ```no_run
+    /// use botw_utils::hashes::{Platform, StockHashTable};
+    /// use std::path::Path;
+    /// let table = StockHashTable::new(Platform::WiiU);
+    /// let report = table.scan_mod_dir(Path::new("mods/My Mod")).unwrap();
+    /// for (path, canon) in &report.vanilla {
+    ///     println!("{} is unchanged {}", path.display(), canon);
+    /// }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The top folder of the mod to scan
+    ///
+    /// # Returns
+    ///
+    /// Returns a Result with the report, or a [`CanonError::Io`] error if a folder or file in
+    /// the mod could not be read.
+    pub fn scan_mod_dir(&self, root: &Path) -> Result<ModScanReport, CanonError> {
+        self.scan_dir(canonicalize_dir(root))
+    }
+
+    /// Checks the files of a walk of a mod folder against the table like
+    /// [`scan_mod_dir`](StockHashTable::scan_mod_dir), with the options of the walk, like which
+    /// files it ignores. Files which the walk skips as errors are not listed. Example:
+    ///
+    /// ```no_run
+    /// use botw_utils::{canonicalize_dir, hashes::{Platform, StockHashTable}};
+    /// let table = StockHashTable::new(Platform::WiiU);
+    /// let walk = canonicalize_dir("mods/My Mod").ignore("*.bak").ignore(".git/");
+    /// let report = table.scan_dir(walk).unwrap();
+    /// println!("{} junk files skipped", report.junk.len());
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `walk` - The walk of the mod folder to scan
+    ///
+    /// # Returns
+    ///
+    /// Returns a Result with the report, or a [`CanonError::Io`] error if a folder or file in
+    /// the mod could not be read.
+    pub fn scan_dir(&self, walk: CanonDir) -> Result<ModScanReport, CanonError> {
+        self.scan_dir_with(walk, ScanOptions::new())
+    }
+
+    /// Checks the files of a walk of a mod folder against the table like
+    /// [`scan_dir`](StockHashTable::scan_dir), with [`ScanOptions`] for how they are sorted
+    ///
+    /// # Arguments
+    ///
+    /// * `walk` - The walk of the mod folder to scan
+    /// * `options` - How the files are sorted into the report
+    ///
+    /// # Returns
+    ///
+    /// Returns a Result with the report, or a [`CanonError::Io`] error if a folder or file in
+    /// the mod could not be read, or a [`CanonError::InvalidYaz0`] error for a stock file with
+    /// invalid yaz0 data if `options` say to stop at one.
+    pub fn scan_dir_with(
+        &self,
+        walk: CanonDir,
+        options: ScanOptions,
+    ) -> Result<ModScanReport, CanonError> {
+        self.scan_dir_with_progress(walk, options, ())
+    }
+
+    /// Checks the files of a walk of a mod folder like
+    /// [`scan_dir_with`](StockHashTable::scan_dir_with), telling an observer about each file as
+    /// it is checked. The number of files is not known ahead of time, so
+    /// [`ScanProgress::on_total`] is never called. Example:
+    ///
+    /// ```no_run
+    /// use botw_utils::canonicalize_dir;
+    /// use botw_utils::hashes::{Platform, ScanOptions, ScanProgress, StockHashTable};
+    /// use std::path::Path;
+    /// struct Printer;
+    /// impl ScanProgress for Printer {
+    ///     fn on_file_start(&mut self, path: &Path, _canon: &str) {
+    ///         println!("Checking {}", path.display());
+    ///     }
+    /// }
+    /// let table = StockHashTable::new(Platform::WiiU);
+    /// let walk = canonicalize_dir("mods/My Mod");
+    /// let report = table.scan_dir_with_progress(walk, ScanOptions::new(), Printer).unwrap();
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `walk` - The walk of the mod folder to scan
+    /// * `options` - How the files are sorted into the report
+    /// * `progress` - The observer to tell about each file, or `()` for none
+    ///
+    /// # Returns
+    ///
+    /// Returns a Result with the report, or a [`CanonError::Io`] error if a folder or file in
+    /// the mod could not be read, or a [`CanonError::InvalidYaz0`] error for a stock file with
+    /// invalid yaz0 data if `options` say to stop at one.
+    pub fn scan_dir_with_progress<P: ScanProgress>(
+        &self,
+        mut walk: CanonDir,
+        options: ScanOptions,
+        mut progress: P,
+    ) -> Result<ModScanReport, CanonError> {
+        let mut report = ModScanReport::default();
+        let mut buf = Vec::new();
+        for result in walk.by_ref() {
+            let (path, canon) = match result {
+                Ok(file) => file,
+                Err(e @ CanonError::Io { .. }) => return Err(e),
+                Err(e) => {
+                    report.uncanonical.push((error_path(&e), e.to_string()));
+                    continue;
+                }
+            };
+            progress.on_file_start(&path, &canon);
+            let data = match fs::read(&path) {
+                Ok(data) => data,
+                Err(e) => {
+                    let error = io_error(&path, &e);
+                    progress.on_file_error(&path, &error);
+                    return Err(error);
+                }
+            };
+            progress.on_bytes(data.len() as u64);
+            let checked = self.try_check_file_with_buf(&canon, &data, &mut buf);
+            let state = checked
+                .as_ref()
+                .map_or(ModdedState::InvalidYaz0, |state| *state);
+            progress.on_file_done(&canon, state);
+            match checked {
+                Ok(ModdedState::Vanilla) => report.vanilla.push((path, canon)),
+                Ok(ModdedState::NewFile) => report.new.push((path, canon)),
+                Err(HashError::InvalidYaz0(reason))
+                    if options.invalid_yaz0 != InvalidYaz0Policy::Modified =>
+                {
+                    if options.invalid_yaz0 == InvalidYaz0Policy::Error {
+                        return Err(CanonError::InvalidYaz0 { path, reason });
+                    }
+                    report.invalid_yaz0.push((path, canon, reason));
+                }
+                _ if options.separate_auto_generated && is_auto_generated(&canon) => {
+                    report.auto_generated.push((path, canon, state))
+                }
+                _ => report.modified.push((path, canon, state)),
+            }
+        }
+        report.junk = walk.ignored().to_vec();
+        Ok(report)
+    }
+}
+
+#[cfg(all(test, feature = "wiiu-data"))]
+mod tests {
+    use super::*;
+    use crate::hashes::{compute_file_hash, Platform};
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn scan_mod_folder() {
+        let dir = TempDir::new("scan");
+        let resaved = b"Resaved stock physics".as_ref();
+        for (file, data) in &[
+            ("rules.txt", b"[Definition]".as_ref()),
+            ("Thumbs.db", b"Thumbnails"),
+            ("content/Pack/Bootup.pack", b"Changed pack"),
+            ("content/Pack/.DS_Store", b"Finder data"),
+            ("content/Pack/._Bootup.pack", b"Finder data"),
+            ("content/Hellow/Sweetie.tardis", b"New file"),
+            (
+                "content/Actor/Physics/FldObj_MountainSheikerWall_A_06.bphysics",
+                resaved,
+            ),
+            ("content/Actor/Physics/Short.bphysics", b""),
+            (
+                "content/Actor/ActorInfo.product.sbyml",
+                b"Merged actor info",
+            ),
+            ("__MACOSX/content/Pack/._Bootup.pack", b"Resource fork"),
+        ] {
+            dir.write(file, data);
+        }
+        let mut table = StockHashTable::new(Platform::WiiU);
+        table.add_hash(
+            "Actor/Physics/FldObj_MountainSheikerWall_A_06.bphysics",
+            compute_file_hash(resaved).unwrap(),
+        );
+        table.add_hash("Actor/Physics/Short.bphysics", 0);
+        let mut report = table.scan_mod_dir(&dir).unwrap();
+        report.modified.sort_by(|a, b| a.1.cmp(&b.1));
+        report.junk.sort();
+        let canon_names = |files: &[(PathBuf, String)]| {
+            files
+                .iter()
+                .map(|(path, canon)| {
+                    assert!(path.starts_with(&dir));
+                    canon.clone()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            report
+                .modified
+                .iter()
+                .map(|(_, canon, state)| (canon.as_str(), *state))
+                .collect::<Vec<_>>(),
+            [
+                ("Actor/Physics/Short.bphysics", ModdedState::TooShort),
+                (
+                    "Pack/Bootup.pack",
+                    ModdedState::Modified {
+                        hash: compute_file_hash(b"Changed pack").unwrap()
+                    }
+                )
+            ]
+        );
+        assert_eq!(
+            report
+                .auto_generated
+                .iter()
+                .map(|(_, canon, _)| canon.as_str())
+                .collect::<Vec<_>>(),
+            ["Actor/ActorInfo.product.byml"]
+        );
+        assert_eq!(canon_names(&report.new), ["Hellow/Sweetie.tardis"]);
+        assert_eq!(
+            canon_names(&report.vanilla),
+            ["Actor/Physics/FldObj_MountainSheikerWall_A_06.bphysics"]
+        );
+        assert_eq!(report.uncanonical.len(), 1);
+        assert!(report.uncanonical[0].0.ends_with("rules.txt"));
+        assert_eq!(
+            report.junk,
+            [
+                dir.join("Thumbs.db"),
+                dir.join("__MACOSX/content/Pack/._Bootup.pack"),
+                dir.join("content/Pack/.DS_Store"),
+                dir.join("content/Pack/._Bootup.pack")
+            ]
+        );
+        let all = table
+            .scan_dir(canonicalize_dir(&dir).use_default_ignores(false))
+            .unwrap();
+        assert!(all.junk.is_empty());
+        assert_eq!((all.new.len(), all.uncanonical.len()), (4, 2));
+        let raw = table
+            .scan_dir_with(
+                canonicalize_dir(&dir),
+                ScanOptions::new().separate_auto_generated(false),
+            )
+            .unwrap();
+        assert!(raw.auto_generated.is_empty());
+        assert_eq!(raw.modified.len(), 3);
+        struct Checked(usize);
+        impl ScanProgress for Checked {
+            fn on_file_done(&mut self, _canon: &str, _state: ModdedState) {
+                self.0 += 1;
+            }
+        }
+        let mut checked = Checked(0);
+        let observed = table
+            .scan_dir_with_progress(canonicalize_dir(&dir), ScanOptions::new(), &mut checked)
+            .unwrap();
+        assert_eq!(checked.0, 5);
+        assert_eq!(observed.new, report.new);
+        assert!(matches!(
+            table.scan_mod_dir(&dir.join("missing")),
+            Err(CanonError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn invalid_yaz0_policies() {
+        let dir = TempDir::new("yaz0");
+        let compressed = roead::yaz0::compress(b"Stock physics data, long enough to compress");
+        let truncated = &compressed[..compressed.len() / 2];
+        let path = dir.write("content/Actor/Physics/Truncated.sbphysics", truncated);
+        let mut table = StockHashTable::new(Platform::WiiU);
+        table.add_hash("Actor/Physics/Truncated.bphysics", 0);
+        let reason = match compute_file_hash(truncated) {
+            Err(HashError::InvalidYaz0(reason)) => reason,
+            Ok(hash) => panic!("truncated data hashed to {}", hash),
+        };
+        assert!(!reason.is_empty());
+
+        let scan = |policy| {
+            table.scan_dir_with(
+                canonicalize_dir(&dir),
+                ScanOptions::new().invalid_yaz0(policy),
+            )
+        };
+        let modified = scan(InvalidYaz0Policy::Modified).unwrap();
+        assert_eq!(
+            modified.modified,
+            [(
+                path.clone(),
+                "Actor/Physics/Truncated.bphysics".to_owned(),
+                ModdedState::InvalidYaz0
+            )]
+        );
+        assert!(modified.invalid_yaz0.is_empty());
+        assert_eq!(table.scan_mod_dir(&dir).unwrap(), modified);
+        let warned = scan(InvalidYaz0Policy::Warn).unwrap();
+        assert!(warned.modified.is_empty());
+        assert_eq!(
+            warned.invalid_yaz0,
+            [(
+                path.clone(),
+                "Actor/Physics/Truncated.bphysics".to_owned(),
+                reason.clone()
+            )]
+        );
+        assert_eq!(
+            scan(InvalidYaz0Policy::Error),
+            Err(CanonError::InvalidYaz0 { path, reason })
+        );
+    }
+}
@@ -0,0 +1,276 @@
+use alloc::string::String;
+use core::fmt;
+use core::str::FromStr;
+
+/// A language and region which BOTW ships localized resources for, named by the code the game
+/// uses in resource paths. The Wii U version has all but `EUen`, `KRko`, `CNzh`, and `TWzh`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Language {
+    /// English (North America)
+    USen,
+    /// Spanish (North America)
+    USes,
+    /// French (North America)
+    USfr,
+    /// English (Europe)
+    EUen,
+    /// Spanish (Europe)
+    EUes,
+    /// French (Europe)
+    EUfr,
+    /// German
+    EUde,
+    /// Italian
+    EUit,
+    /// Dutch
+    EUnl,
+    /// Russian
+    EUru,
+    /// Japanese
+    JPja,
+    /// Korean
+    KRko,
+    /// Chinese (Simplified)
+    CNzh,
+    /// Chinese (Traditional)
+    TWzh,
+}
+
+impl Language {
+    /// Every language, in the order the variants are declared
+    pub const ALL: [Language; 14] = [
+        Language::USen,
+        Language::USes,
+        Language::USfr,
+        Language::EUen,
+        Language::EUes,
+        Language::EUfr,
+        Language::EUde,
+        Language::EUit,
+        Language::EUnl,
+        Language::EUru,
+        Language::JPja,
+        Language::KRko,
+        Language::CNzh,
+        Language::TWzh,
+    ];
+
+    /// Gets the four letter code the game uses for the language, like `USen`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Language::USen => "USen",
+            Language::USes => "USes",
+            Language::USfr => "USfr",
+            Language::EUen => "EUen",
+            Language::EUes => "EUes",
+            Language::EUfr => "EUfr",
+            Language::EUde => "EUde",
+            Language::EUit => "EUit",
+            Language::EUnl => "EUnl",
+            Language::EUru => "EUru",
+            Language::JPja => "JPja",
+            Language::KRko => "KRko",
+            Language::CNzh => "CNzh",
+            Language::TWzh => "TWzh",
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error type for a string which is not the code of a [`Language`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseLanguageError {
+    /// The string which could not be parsed
+    pub input: String,
+}
+
+impl fmt::Display for ParseLanguageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unrecognized language `{}`, expected a code like `USen`",
+            self.input
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseLanguageError {}
+
+impl FromStr for Language {
+    type Err = ParseLanguageError;
+
+    fn from_str(code: &str) -> Result<Language, ParseLanguageError> {
+        Language::ALL
+            .iter()
+            .find(|lang| lang.as_str() == code)
+            .copied()
+            .ok_or_else(|| ParseLanguageError { input: code.into() })
+    }
+}
+
+/// The placeholder which stands in for the language code in a language neutral path
+const PLACEHOLDER: &str = "{lang}";
+
+/// Finds the language code in a canonical resource path, if it is in one of the places the game
+/// puts it: a whole folder name, like `Voice/USen/`, or a suffix at the end of the file name
+/// before its extensions, like `Bootup_USen.pack`. Returns the position of the code.
+fn find_language(canon: &str) -> Option<(usize, Language)> {
+    let name_start = canon.rfind('/').map_or(0, |i| i + 1);
+    let mut pos = 0;
+    for folder in canon[..name_start].split_terminator('/') {
+        if let Ok(lang) = folder.parse() {
+            return Some((pos, lang));
+        }
+        pos += folder.len() + 1;
+    }
+    let name = &canon[name_start..];
+    let stem = &name[..name.find('.')?];
+    let code_start = stem.len().checked_sub(4)?;
+    if stem.get(..code_start)?.ends_with('_') {
+        let lang = stem.get(code_start..)?.parse().ok()?;
+        return Some((name_start + code_start, lang));
+    }
+    None
+}
+
+/// Splits the language out of the [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path) of a localized resource, so that
+/// the same file in different languages can be treated as one. The language code is replaced
+/// with `{lang}` in the neutral path. Only whole folder names and file name suffixes count as
+/// language codes, so names which merely contain one are left alone. Example:
+///
+/// ```
+/// use botw_utils::{parse_language, Language};
+/// assert_eq!(
+///    parse_language("Pack/Bootup_EUen.pack"),
+///    Some(("Pack/Bootup_{lang}.pack".to_owned(), Language::EUen))
+/// );
+/// assert_eq!(
+///    parse_language("Aoc/0010/Voice/JPja/Stream_Demo660_0/Demo660_0_Text020.bfstm"),
+///    Some((
+///        "Aoc/0010/Voice/{lang}/Stream_Demo660_0/Demo660_0_Text020.bfstm".to_owned(),
+///        Language::JPja
+///    ))
+/// );
+/// assert_eq!(parse_language("Actor/Pack/Npc_USenSoldier.bactorpack"), None);
+/// ```
+///
+/// # Arguments
+///
+/// * `canon` - The canonical resource path of the file
+///
+/// # Returns
+///
+/// Returns an Option with the language neutral path and the language, or None if the path is
+/// not localized.
+pub fn parse_language(canon: &str) -> Option<(String, Language)> {
+    let (pos, lang) = find_language(canon)?;
+    Some((
+        [&canon[..pos], PLACEHOLDER, &canon[pos + 4..]].concat(),
+        lang,
+    ))
+}
+
+/// Puts a language back into a language neutral path made by [`parse_language`]. Example:
+///
+/// ```
+/// use botw_utils::{with_language, Language};
+/// assert_eq!(
+///    with_language("Message/Msg_{lang}.product.sarc", Language::USfr),
+///    "Message/Msg_USfr.product.sarc"
+/// );
+/// ```
+///
+/// # Arguments
+///
+/// * `neutral` - The language neutral path
+/// * `lang` - The language to use
+///
+/// # Returns
+///
+/// Returns the canonical resource path of the file in that language as a String. A path with no
+/// `{lang}` placeholder is returned unchanged.
+pub fn with_language(neutral: &str, lang: Language) -> String {
+    neutral.replacen(PLACEHOLDER, lang.as_str(), 1)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn localized_names() {
+        for (canon, neutral, lang) in &[
+            (
+                "Pack/Bootup_USen.pack",
+                "Pack/Bootup_{lang}.pack",
+                Language::USen,
+            ),
+            (
+                "Pack/TitleBG_USfr.pack",
+                "Pack/TitleBG_{lang}.pack",
+                Language::USfr,
+            ),
+            (
+                "Message/Msg_CNzh.product.sarc",
+                "Message/Msg_{lang}.product.sarc",
+                Language::CNzh,
+            ),
+            (
+                "Voice/EUru/Stream_Demo101_0/Demo101_0_Text000.bfstm",
+                "Voice/{lang}/Stream_Demo101_0/Demo101_0_Text000.bfstm",
+                Language::EUru,
+            ),
+            (
+                "Pack/Modé_JPja.pack",
+                "Pack/Modé_{lang}.pack",
+                Language::JPja,
+            ),
+        ] {
+            assert_eq!(parse_language(canon), Some((neutral.to_string(), *lang)));
+            assert_eq!(with_language(neutral, *lang), *canon);
+        }
+        for canon in &[
+            "Actor/Pack/Npc_USenSoldier.bactorpack",
+            "Actor/Pack/USen_Flag.bactorpack",
+            "Pack/BootupUSen.pack",
+            "Pack/Bootup_USEN.pack",
+            "Pack/Bootup_USen",
+            "Map/USenField/Static.mubin",
+            "Pack/Bootup.pack",
+            "Pack/ab_éUSe.pack",
+            "Pack/Bootup_USé.pack",
+        ] {
+            assert_eq!(parse_language(canon), None, "{}", canon);
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn stock_localized_names() {
+        use crate::hashes::{get_hash_table, Platform};
+        for &platform in &[Platform::WiiU, Platform::Switch] {
+            let mut found = 0;
+            for canon in get_hash_table(platform).keys() {
+                if let Some((neutral, lang)) = parse_language(canon) {
+                    assert_eq!(with_language(&neutral, lang), *canon);
+                    found += 1;
+                }
+            }
+            assert!(found > 1000);
+        }
+        assert_eq!("KRko".parse(), Ok(Language::KRko));
+        assert_eq!(
+            "krko".parse::<Language>(),
+            Err(ParseLanguageError {
+                input: "krko".to_owned()
+            })
+        );
+    }
+}
@@ -1,9 +1,831 @@
-use once_cell::sync::Lazy;
-use regex::Regex;
-use std::path::Path;
+#![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
 
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use hashes::Platform;
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "std")]
+mod cache;
+mod canon_path;
+mod canonicalizer;
+#[cfg(feature = "walk")]
+mod convert;
+mod crc;
 pub mod extensions;
+#[cfg(feature = "std")]
+mod generated;
+#[cfg(feature = "std")]
 pub mod hashes;
+mod language;
+#[cfg(feature = "walk")]
+mod lint;
+#[cfg(feature = "std")]
+mod related;
+mod roots;
+#[cfg(feature = "std")]
+mod targets;
+#[cfg(all(test, feature = "std"))]
+mod test_util;
+pub mod titles;
+#[cfg(feature = "walk")]
+mod walk;
+#[cfg(feature = "std")]
+mod xxh3;
+
+#[cfg(feature = "std")]
+pub use cache::CachedCanonicalizer;
+pub use canon_path::{is_valid_canon_path, validate_canon_path, CanonPath};
+pub use canonicalizer::Canonicalizer;
+#[cfg(feature = "walk")]
+pub use convert::{convert_mod_layout, ConversionReport};
+pub use crc::crc_of_canon;
+#[cfg(feature = "std")]
+pub use generated::is_auto_generated;
+#[cfg(feature = "std")]
+pub use hashes::data_versions;
+pub use language::{parse_language, with_language, Language, ParseLanguageError};
+#[cfg(feature = "walk")]
+pub use lint::{lint_mod_root, LintIssue, LintKind};
+#[cfg(feature = "std")]
+pub use related::related_files;
+pub use roots::{known_roots, RootPattern};
+#[cfg(feature = "std")]
+pub use targets::{build_output_path, ModTarget, WiiURegion};
+#[cfg(feature = "walk")]
+pub use walk::{canonicalize_dir, default_ignores, CanonDir};
+
+/// Finds the `s` which marks a yaz0 compressed file in the extension of the last path component,
+/// if what remains after removing it is a known BOTW file extension. Dots elsewhere in the path
+/// are left alone.
+fn yaz0_prefix_index(path: &str) -> Option<usize> {
+    let name_start = path.rfind('/').map(|i| i + 1).unwrap_or(0);
+    let ext_start = name_start + path[name_start..].rfind('.')? + 1;
+    path[ext_start..]
+        .strip_prefix('s')
+        .filter(|ext| extensions::is_known_ext(ext))
+        .map(|_| ext_start)
+}
+
+/// Removes the `s` which marks a yaz0 compressed file from the extension of the last path
+/// component, as found by [`yaz0_prefix_index`]
+#[cfg(feature = "std")]
+fn strip_yaz0_prefix(path: &str) -> String {
+    match yaz0_prefix_index(path) {
+        Some(i) => [&path[..i], &path[i + 1..]].concat(),
+        None => path.to_owned(),
+    }
+}
+
+/// Device prefixes used by Switch homebrew tools, which are stripped before looking for a root
+const DEVICE_PREFIXES: &[&str] = &["sdmc:", "sd:", "nand:"];
+
+/// Removes a leading device prefix like `sdmc:/` from a normalized path. Windows drive letters
+/// are not device prefixes and are left alone.
+fn strip_device_prefix(path: &str) -> &str {
+    DEVICE_PREFIXES
+        .iter()
+        .find_map(|prefix| {
+            path.get(..prefix.len())
+                .filter(|start| start.eq_ignore_ascii_case(prefix))
+                .map(|_| path[prefix.len()..].trim_start_matches('/'))
+        })
+        .unwrap_or(path)
+}
+
+/// Removes a leading Windows drive letter like `C:/` from a normalized path
+fn strip_drive_prefix(path: &str) -> &str {
+    match path.as_bytes() {
+        [letter, b':', ..] if letter.is_ascii_alphabetic() => path[2..].trim_start_matches('/'),
+        _ => path,
+    }
+}
+
+/// Removes a Windows verbatim prefix like `\\?\` or `\\.\` and the server and share of a UNC
+/// path like `\\server\share` from a path before it is normalized, so that long paths from
+/// Windows APIs canonicalize the same as plain ones. The separator after a UNC share is kept, so
+/// the rest of the path stays absolute and `..` segments cannot escape above the share.
+fn strip_windows_prefix(path: &str) -> &str {
+    let is_separator = |c: char| c == '/' || c == '\\';
+    let verbatim = ["\\\\?\\", "\\\\.\\", "//?/", "//./"]
+        .iter()
+        .find_map(|prefix| path.strip_prefix(prefix));
+    let unc = match verbatim {
+        Some(rest) => match rest.get(..3) {
+            Some(unc) if unc.eq_ignore_ascii_case("UNC") && rest[3..].starts_with(is_separator) => {
+                &rest[4..]
+            }
+            _ => return rest,
+        },
+        None => match path.strip_prefix("\\\\") {
+            Some(rest) => rest,
+            None => return path,
+        },
+    };
+    let mut separators = unc.match_indices(is_separator).map(|(i, _)| i);
+    match separators.nth(1) {
+        Some(i) => &unc[i..],
+        None => "",
+    }
+}
+
+/// Error type describing why a path could not be converted into a canonical resource path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CanonError {
+    /// The path is not valid UTF-8
+    #[cfg(feature = "std")]
+    NonUtf8Path(PathBuf),
+    /// None of the known BOTW root folders could be found in the path
+    UnrecognizedRoot {
+        path: String,
+        tried: Vec<&'static str>,
+    },
+    /// A root folder was found, but the path does not start at it or does not resolve to a file
+    /// under it
+    NotUnderContentRoot { path: String },
+    /// A string used as a canonical resource path is not one
+    InvalidCanonPath { path: String, reason: &'static str },
+    /// The path is in a folder of a Switch title which does not hold romfs files, like `exefs` or
+    /// `cheats`
+    NotRomfs { path: String, folder: &'static str },
+    /// The path is not validly percent-encoded, or it decodes to a NUL or a `.` or `..` segment
+    InvalidEncoding { path: String },
+    /// The path ends with a separator, so it is a folder rather than a file
+    IsDirectory { path: String },
+    /// A folder could not be read while walking it
+    #[cfg(feature = "std")]
+    Io {
+        path: PathBuf,
+        kind: std::io::ErrorKind,
+    },
+    /// A stock file's yaz0 data could not be decompressed while scanning it, with the reason
+    /// why, when the scan is set to stop at invalid yaz0 data
+    #[cfg(feature = "std")]
+    InvalidYaz0 { path: PathBuf, reason: String },
+}
+
+impl fmt::Display for CanonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            CanonError::NonUtf8Path(path) => {
+                write!(f, "Path {} is not valid UTF-8", path.display())
+            }
+            CanonError::UnrecognizedRoot { path, tried } => write!(
+                f,
+                "Path {} has no recognized BOTW root folder (tried {})",
+                path,
+                tried.join(", ")
+            ),
+            CanonError::NotUnderContentRoot { path } => {
+                write!(f, "Path {} is not under a BOTW content or DLC root", path)
+            }
+            CanonError::InvalidCanonPath { path, reason } => {
+                write!(f, "{} is not a canonical resource path: {}", path, reason)
+            }
+            CanonError::NotRomfs { path, folder } => {
+                write!(
+                    f,
+                    "Path {} is in a title {} folder, not romfs",
+                    path, folder
+                )
+            }
+            CanonError::InvalidEncoding { path } => {
+                write!(f, "Path {} is not validly percent-encoded", path)
+            }
+            CanonError::IsDirectory { path } => {
+                write!(f, "Path {} is a folder, not a file", path)
+            }
+            #[cfg(feature = "std")]
+            CanonError::Io { path, kind } => {
+                write!(f, "Could not read {}: {}", path.display(), kind)
+            }
+            #[cfg(feature = "std")]
+            CanonError::InvalidYaz0 { path, reason } => {
+                write!(
+                    f,
+                    "Could not decompress yaz0 data in {}: {}",
+                    path.display(),
+                    reason
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CanonError {}
+
+/// Convert a path relative to a BOTW content root into a [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path), with an error explaining the
+/// failure if it cannot be done. Example:
+///
+/// ```
+/// use botw_utils::{try_get_canon_name, CanonError};
+/// assert_eq!(
+///    try_get_canon_name("content\\Actor\\Pack\\Enemy_Lizalfos_Senior.sbactorpack").unwrap(),
+///    "Actor/Pack/Enemy_Lizalfos_Senior.bactorpack"
+/// );
+/// assert!(matches!(
+///    try_get_canon_name("Hellow/Sweetie.tardis"),
+///    Err(CanonError::UnrecognizedRoot { .. })
+/// ));
+/// ```
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the BOTW game file relative to the root folder
+///
+/// # Returns
+///
+/// Returns a Result with the canonical resource path as a String or a [`CanonError`] describing
+/// why the path does not appear valid
+#[cfg(feature = "std")]
+pub fn try_get_canon_name<P: AsRef<Path>>(file_path: P) -> Result<String, CanonError> {
+    classify(file_path.as_ref()).map(|(_, canon)| canon)
+}
+
+/// Convert a path given as raw bytes into a [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path), like [`try_get_canon_name`]. Mod
+/// archives made with old tools sometimes store names in latin-1 rather than UTF-8, so if the
+/// bytes are not valid UTF-8 they are decoded as latin-1 instead. Example:
+///
+/// ```
+/// use botw_utils::try_get_canon_name_bytes;
+/// assert_eq!(
+///    try_get_canon_name_bytes(b"content/Pack/Bootup.pack").unwrap(),
+///    "Pack/Bootup.pack"
+/// );
+/// assert_eq!(
+///    try_get_canon_name_bytes(b"content/Mod/Caf\xe9.sarc").unwrap(),
+///    "Mod/Caf\u{e9}.sarc"
+/// );
+/// ```
+///
+/// # Arguments
+///
+/// * `file_path` - The bytes of the path of the BOTW game file relative to the root folder
+///
+/// # Returns
+///
+/// Returns a Result with the canonical resource path as a String or a [`CanonError`] describing
+/// why the path does not appear valid. If the path is not UTF-8 and does not canonicalize as
+/// latin-1 either, the error is [`CanonError::NonUtf8Path`].
+#[cfg(all(unix, feature = "std"))]
+pub fn try_get_canon_name_bytes(file_path: &[u8]) -> Result<String, CanonError> {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    if let Ok(path) = std::str::from_utf8(file_path) {
+        return try_get_canon_name(path);
+    }
+    let latin1: String = file_path.iter().map(|&b| char::from(b)).collect();
+    try_get_canon_name(latin1)
+        .map_err(|_| CanonError::NonUtf8Path(PathBuf::from(OsStr::from_bytes(file_path))))
+}
+
+/// Why [`find_canon`] rejected a path, before the path is attached to make a [`CanonError`]
+#[derive(Debug, PartialEq)]
+enum Rejection {
+    UnrecognizedRoot,
+    NotUnderContentRoot,
+    NotRomfs(&'static str),
+    IsDirectory,
+}
+
+impl Rejection {
+    fn into_error(self, path: &str) -> CanonError {
+        match self {
+            Rejection::UnrecognizedRoot => CanonError::UnrecognizedRoot {
+                path: path.to_owned(),
+                tried: roots::root_patterns(),
+            },
+            Rejection::NotUnderContentRoot => CanonError::NotUnderContentRoot {
+                path: path.to_owned(),
+            },
+            Rejection::NotRomfs(folder) => CanonError::NotRomfs {
+                path: path.to_owned(),
+                folder,
+            },
+            Rejection::IsDirectory => CanonError::IsDirectory {
+                path: path.to_owned(),
+            },
+        }
+    }
+}
+
+/// A canonical resource path found by [`find_canon`], kept as the pieces it is made of so that
+/// callers only allocate when the canonical path is not a plain slice of the input
+struct CanonParts<'a> {
+    kind: RootKind,
+    /// The rest of the path after the root folder
+    rest: &'a str,
+    /// The index in `rest` of the `s` to remove from a yaz0 compressed extension
+    yaz0_prefix: Option<usize>,
+}
+
+impl<'a> CanonParts<'a> {
+    /// The root prefix of the canonical resource path
+    fn prefix(&self) -> &'static str {
+        match self.kind {
+            RootKind::Dlc => "Aoc/0010/",
+            RootKind::Base | RootKind::Update => "",
+        }
+    }
+
+    /// Gets the canonical resource path as a slice of the input if it is one
+    #[cfg(feature = "std")]
+    fn as_borrowed(&self) -> Option<&'a str> {
+        if self.prefix().is_empty() && self.yaz0_prefix.is_none() {
+            Some(self.rest)
+        } else {
+            None
+        }
+    }
+
+    /// Builds the canonical resource path as an owned String
+    fn build(&self) -> String {
+        let prefix = self.prefix();
+        let mut canon = String::with_capacity(prefix.len() + self.rest.len());
+        canon.push_str(prefix);
+        match self.yaz0_prefix {
+            Some(i) => {
+                canon.push_str(&self.rest[..i]);
+                canon.push_str(&self.rest[i + 1..]);
+            }
+            None => canon.push_str(self.rest),
+        }
+        canon
+    }
+}
+
+/// Splits the part of a path after its root folder from the root, checking that it is a file
+/// under a root of the given kind. Only an `aoc` folder right under a non-DLC root means the
+/// path is DLC content in the wrong place, since stock files like
+/// `EventFlow/aocResident.bfevfl` and mod folders can have `aoc` in their names too. A path
+/// which ends with a separator is a folder, so it has no canonical resource path.
+fn canon_parts(kind: RootKind, rest: &str) -> Result<CanonParts<'_>, Rejection> {
+    if rest.is_empty() || (kind != RootKind::Dlc && folders_at(rest, 0, &["aoc"]).is_some()) {
+        Err(Rejection::NotUnderContentRoot)
+    } else if rest.ends_with('/') {
+        Err(Rejection::IsDirectory)
+    } else {
+        Ok(CanonParts {
+            kind,
+            rest,
+            yaz0_prefix: yaz0_prefix_index(rest),
+        })
+    }
+}
+
+/// Takes the path segment starting at `pos`, if it is followed by a slash, along with the position
+/// after the slash
+fn next_segment(path: &str, pos: usize) -> Option<(&str, usize)> {
+    let len = path.get(pos..)?.find('/')?;
+    Some((&path[pos..pos + len], pos + len + 1))
+}
+
+/// Iterates the positions where path segments start
+fn segment_starts(path: &str) -> impl DoubleEndedIterator<Item = usize> + '_ {
+    core::iter::once(0).chain(path.match_indices('/').map(|(i, _)| i + 1))
+}
+
+/// Matches a Wii U BOTW title ID folder at `pos`, either joined like `00050000101C9400` or split
+/// into `00050000/101C9400`, returning the kind of content in it and the position after it
+fn wiiu_title_at(path: &str, pos: usize) -> Option<(RootKind, usize)> {
+    let (high, next) = next_segment(path, pos)?;
+    if let Some(kind) = titles::wiiu_title_kind(high) {
+        return Some((kind, next));
+    }
+    let (low, next) = next_segment(path, next)?;
+    Some((titles::split_wiiu_title_kind(high, low)?, next))
+}
+
+/// Checks for a DLC index folder like `0010`. Some dumps and old mods use the per-region `0011`
+/// and `0012` indices, but the game's canonical paths always use `Aoc/0010/`.
+fn is_aoc_index(name: &str) -> bool {
+    matches!(name.as_bytes(), [b'0', b'0', a, b] if a.is_ascii_digit() && b.is_ascii_digit())
+}
+
+/// Skips a DLC index folder, as checked by [`is_aoc_index`], if there is one at `pos`
+fn skip_aoc_index(path: &str, pos: usize) -> usize {
+    match next_segment(path, pos) {
+        Some((index, next)) if is_aoc_index(index) => next,
+        _ => pos,
+    }
+}
+
+/// Matches a series of folder names at `pos`, returning the position after them
+fn folders_at(path: &str, mut pos: usize, names: &[&str]) -> Option<usize> {
+    for name in names {
+        let (segment, next) = next_segment(path, pos)?;
+        if !segment.eq_ignore_ascii_case(name) {
+            return None;
+        }
+        pos = next;
+    }
+    Some(pos)
+}
+
+/// Matches a mod folder layout which has a free-form mod name folder above the usual root, like a
+/// Cemu graphic pack, an SDCafiine mod, or a Loadiine game folder (which is named like
+/// `The Legend of Zelda Breath of the Wild [ALZE01]`), returning the position after the name
+fn mod_pack_at(path: &str, pos: usize) -> Option<usize> {
+    let next = match folders_at(path, pos, &["graphicPacks"])
+        .or_else(|| folders_at(path, pos, &["wiiu", "games"]))
+    {
+        Some(next) => next,
+        None => wiiu_title_at(path, folders_at(path, pos, &["sdcafiine"])?)?.1,
+    };
+    match next_segment(path, next)? {
+        ("", _) => None,
+        (_, next) => Some(next),
+    }
+}
+
+/// Gets the kind of content for a DLC volume folder named like `aoc0005000c101c9400`, as ftpiiu
+/// dumps copied off a console have
+fn vol_aoc_kind(name: &str) -> Option<RootKind> {
+    let title_id = name
+        .get(..3)
+        .filter(|aoc| aoc.eq_ignore_ascii_case("aoc"))
+        .and_then(|_| name.get(3..))?;
+    titles::wiiu_title_kind(title_id).filter(|kind| *kind == RootKind::Dlc)
+}
+
+/// Matches a Wii U title folder, either bare like in a dump, inside a Cemu mlc01 folder, or as a
+/// DLC volume folder under `vol`, returning the kind of content in it and the position after its
+/// `content` folder
+fn wiiu_title_root_at(path: &str, pos: usize) -> Option<(RootKind, usize)> {
+    if let Some((name, next)) =
+        folders_at(path, pos, &["vol"]).and_then(|next| next_segment(path, next))
+    {
+        if let Some(kind) = vol_aoc_kind(name) {
+            return Some((kind, folders_at(path, next, &["content"])?));
+        }
+    }
+    let pos = folders_at(path, pos, &["mlc01", "usr", "title"]).unwrap_or(pos);
+    let (kind, next) = wiiu_title_at(path, pos)?;
+    Some((kind, folders_at(path, next, &["content"])?))
+}
+
+/// Matches a Yuzu or Ryujinx mod folder, which has a mod name folder between the title ID and
+/// `romfs`, returning the kind of content in the title and the position after `romfs`
+fn emu_mod_at(path: &str, pos: usize) -> Option<(RootKind, usize)> {
+    let next = folders_at(path, pos, &["load"])
+        .or_else(|| folders_at(path, pos, &["mods", "contents"]))?;
+    let (title_id, next) = next_segment(path, next)?;
+    let kind = titles::switch_title_kind(title_id)?;
+    match next_segment(path, next)? {
+        ("", _) => None,
+        (_, next) => Some((kind, folders_at(path, next, &["romfs"])?)),
+    }
+}
+
+/// Folders of Switch title and mod layouts which hold something other than romfs files
+const NON_ROMFS_FOLDERS: &[&str] = &["exefs", "exefs_patches", "cheats", "legalinfo"];
+
+/// Matches a folder of a Switch title at `pos` which does not hold romfs files, either directly
+/// under the title ID or under a mod name folder inside it, returning the folder name. The
+/// atmosphere `exefs_patches` folder, which is not under any title, also counts.
+fn non_romfs_at(path: &str, pos: usize) -> Option<&'static str> {
+    if folders_at(path, pos, &["atmosphere", "exefs_patches"]).is_some() {
+        return Some("exefs_patches");
+    }
+    let folder_at = |pos| {
+        let (segment, _) = next_segment(path, pos)?;
+        NON_ROMFS_FOLDERS
+            .iter()
+            .find(|folder| segment.eq_ignore_ascii_case(folder))
+            .copied()
+    };
+    let (title_id, next) = next_segment(path, pos)?;
+    titles::switch_folder_kind(title_id)?;
+    folder_at(next).or_else(|| match next_segment(path, next)? {
+        ("", _) => None,
+        (_, next) => folder_at(next),
+    })
+}
+
+/// Folders of Switch custom firmware which hold title ID folders for LayeredFS mods
+const CFW_TITLE_FOLDERS: &[&[&str]] = &[
+    &["atmosphere", "titles"],
+    &["atmosphere", "contents"],
+    &["sxos", "titles"],
+    &["reinx", "titles"],
+];
+
+/// Matches a plain root folder at `pos`: `content`, `aoc` (optionally with `0010` or another DLC
+/// index), or a Switch title ID `romfs` folder optionally inside a custom firmware folder like
+/// `atmosphere/contents`, returning the kind of root and the position after it
+fn root_at(path: &str, pos: usize) -> Option<(RootKind, usize)> {
+    if let Some(next) = folders_at(path, pos, &["content"]) {
+        return Some((RootKind::Base, next));
+    }
+    if let Some(next) = folders_at(path, pos, &["aoc"]) {
+        return Some((RootKind::Dlc, skip_aoc_index(path, next)));
+    }
+    let pos = CFW_TITLE_FOLDERS
+        .iter()
+        .find_map(|folders| folders_at(path, pos, folders))
+        .unwrap_or(pos);
+    let (title_id, next) = next_segment(path, pos)?;
+    let kind = titles::switch_folder_kind(title_id)?;
+    Some((kind, folders_at(path, next, &["romfs"])?))
+}
+
+/// Finds the deepest plain root folder in a path, as matched by [`root_at`]. Below another root,
+/// an `aoc` folder only counts when it is right under it, like the `romfs/Aoc/0010` of Switch
+/// mods, since deeper in it is just a folder of base game or mod files.
+fn deepest_root(path: &str) -> Option<(RootKind, usize)> {
+    let mut deepest: Option<(RootKind, usize)> = None;
+    for pos in segment_starts(path) {
+        if let Some(root) = root_at(path, pos) {
+            let nested_aoc = folders_at(path, pos, &["aoc"]).is_some()
+                && deepest.is_some_and(|(_, root_end)| root_end != pos);
+            if !nested_aoc {
+                deepest = Some(root);
+            }
+        }
+    }
+    deepest
+}
+
+/// Finds the root folder and canonical resource path in a path which already uses forward
+/// slashes. This is the shared workings of all the canonicalization functions, and it only
+/// borrows from the path. Known mod layouts are anchored on their first occurrence, then paths
+/// into non-romfs folders of Switch titles are rejected, and otherwise the deepest plain root
+/// folder wins. As a last resort, a bare `romfs` folder at the start of the path, like tools
+/// extracting a single title produce, is treated as base game content, since it does not say
+/// which title it came from.
+#[cfg(feature = "std")]
+fn find_canon(path: &str) -> Result<CanonParts<'_>, Rejection> {
+    find_canon_with(path, &Canonicalizer::new())
+}
+
+/// Does the work of [`find_canon`] with the root folder options of a [`Canonicalizer`]
+fn find_canon_with<'a>(
+    path: &'a str,
+    options: &Canonicalizer,
+) -> Result<CanonParts<'a>, Rejection> {
+    let path = strip_drive_prefix(strip_device_prefix(path));
+    let (kind, rest) = if let Some(next) =
+        segment_starts(path).find_map(|pos| mod_pack_at(path, pos))
+    {
+        let (kind, root_end) = root_at(path, next).ok_or(Rejection::UnrecognizedRoot)?;
+        (kind, &path[root_end..])
+    } else if let Some((kind, next)) =
+        segment_starts(path).find_map(|pos| wiiu_title_root_at(path, pos))
+    {
+        match kind {
+            RootKind::Dlc => (kind, &path[skip_aoc_index(path, next)..]),
+            _ => (kind, &path[next..]),
+        }
+    } else if let Some((kind, next)) = segment_starts(path).find_map(|pos| emu_mod_at(path, pos)) {
+        (kind, &path[next..])
+    } else if let Some(folder) = segment_starts(path).find_map(|pos| non_romfs_at(path, pos)) {
+        return Err(Rejection::NotRomfs(folder));
+    } else {
+        let root = deepest_root(path).or_else(|| match options.allow_bare_romfs {
+            true => Some((RootKind::Base, folders_at(path, 0, &["romfs"])?)),
+            false => None,
+        });
+        match root {
+            Some((kind, root_end)) => (kind, &path[root_end..]),
+            None if options.assume_content_when_unrooted => {
+                (RootKind::Base, path.trim_start_matches('/'))
+            }
+            None => return Err(Rejection::UnrecognizedRoot),
+        }
+    };
+    canon_parts(kind, rest)
+}
+
+/// Checks whether a path has backslashes, repeated slashes, or `.` or `..` segments which have
+/// to be normalized before looking for a root folder
+fn needs_normalizing(path: &str) -> bool {
+    path.contains('\\')
+        || path.trim_start_matches('/').contains("//")
+        || path
+            .split('/')
+            .any(|segment| segment == "." || segment == "..")
+}
+
+/// Lexically normalizes a path into `out`, without touching the file system. Separators become
+/// forward slashes, repeated separators and `.` segments are removed, and `..` segments remove
+/// the segment before them. A `..` which would go above the start of the path is kept, but it can
+/// never go above a drive or device prefix or the root of an absolute path, so a path which
+/// escapes above its root folder fails to canonicalize instead of giving a bogus name.
+fn normalize_path(path: &str, out: &mut String) {
+    let is_separator = |c| c == '/' || c == '\\';
+    out.clear();
+    let absolute = path.starts_with(is_separator);
+    if absolute {
+        out.push('/');
+    }
+    let base = out.len();
+    for segment in path.split(is_separator) {
+        match segment {
+            "" | "." => continue,
+            ".." => {
+                let start = out.rfind('/').map_or(0, |i| i + 1).max(base);
+                let last = &out[start..];
+                if last.is_empty() && absolute {
+                    continue;
+                }
+                if !(last.is_empty() || last == ".." || last.ends_with(':')) {
+                    out.truncate(if start > base { start - 1 } else { base });
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        if out.len() > base {
+            out.push('/');
+        }
+        out.push_str(segment);
+    }
+    if path.ends_with(is_separator) && out.len() > base {
+        out.push('/');
+    }
+}
+
+/// Finds the root folder kind and canonical resource path for a path, which are the shared
+/// workings of [`try_get_canon_name`] and [`classify_path`]
+#[cfg(feature = "std")]
+fn classify(file_path: &Path) -> Result<(RootKind, String), CanonError> {
+    classify_with(file_path, &mut String::new())
+}
+
+/// Does the work of [`classify`], normalizing separators into a scratch buffer which can be
+/// reused between calls
+#[cfg(feature = "std")]
+fn classify_with(file_path: &Path, scratch: &mut String) -> Result<(RootKind, String), CanonError> {
+    let path = file_path
+        .to_str()
+        .ok_or_else(|| CanonError::NonUtf8Path(file_path.to_path_buf()))?;
+    classify_str(path, scratch)
+}
+
+/// Does the work of [`classify`] for a path which is already a string, which needs nothing
+/// beyond `alloc`
+fn classify_str(path: &str, scratch: &mut String) -> Result<(RootKind, String), CanonError> {
+    Canonicalizer::new().classify(path, scratch)
+}
+
+/// Convert a path string relative to a BOTW content root into a [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path), with an error explaining the
+/// failure if it cannot be done. This is the same as [`try_get_canon_name`], but it works on
+/// strings rather than `Path`s, so it is available without the `std` feature. Example:
+///
+/// ```
+/// use botw_utils::try_get_canon_name_str;
+/// assert_eq!(
+///    try_get_canon_name_str("content\\Actor\\Pack\\Enemy_Lizalfos_Senior.sbactorpack").unwrap(),
+///    "Actor/Pack/Enemy_Lizalfos_Senior.bactorpack"
+/// );
+/// ```
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the BOTW game file relative to the root folder
+///
+/// # Returns
+///
+/// Returns a Result with the canonical resource path as a String or a [`CanonError`] describing
+/// why the path does not appear valid
+pub fn try_get_canon_name_str(file_path: &str) -> Result<String, CanonError> {
+    classify_str(file_path, &mut String::new()).map(|(_, canon)| canon)
+}
+
+/// Convert a path string relative to a BOTW content root into a [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path). This is the same as
+/// [`get_canon_name`], but it works on strings rather than `Path`s, so it is available without
+/// the `std` feature.
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the BOTW game file relative to the root folder
+///
+/// # Returns
+///
+/// Returns an Option with the canonical resource path as a String or None if the path does not
+/// appear valid
+pub fn get_canon_name_str(file_path: &str) -> Option<String> {
+    try_get_canon_name_str(file_path).ok()
+}
+
+/// Decodes the percent-encoded bytes in a path, like `%20` or `%2F`, exactly once, so a double
+/// encoded `%2520` becomes a literal `%20`. Returns None for a malformed escape, bytes which are
+/// not UTF-8, a NUL, or a `.` or `..` segment in the decoded path, since decoded separators could
+/// otherwise splice relative segments into it.
+fn percent_decode(path: &str) -> Option<String> {
+    let hex = |byte: u8| (byte as char).to_digit(16).map(|digit| digit as u8);
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut iter = path.bytes();
+    while let Some(byte) = iter.next() {
+        bytes.push(match byte {
+            b'%' => hex(iter.next()?)? << 4 | hex(iter.next()?)?,
+            _ => byte,
+        });
+    }
+    let decoded = String::from_utf8(bytes).ok()?;
+    if decoded.contains('\0')
+        || decoded
+            .split(['/', '\\'])
+            .any(|segment| segment == "." || segment == "..")
+    {
+        return None;
+    }
+    Some(decoded)
+}
+
+/// Convert a percent-encoded path string relative to a BOTW content root, like web tools and
+/// some archive extractors give, into a [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path). The path is decoded once before it
+/// is canonicalized, and encoded separators like `%2F` and `%5C` count as separators. Example:
+///
+/// ```
+/// use botw_utils::get_canon_name_from_url;
+/// assert_eq!(
+///    get_canon_name_from_url("content%2FActor%2FPack%2FEnemy%20Test.sbactorpack").unwrap(),
+///    "Actor/Pack/Enemy Test.bactorpack"
+/// );
+/// assert!(get_canon_name_from_url("content/Pack/Bootup.pack%00").is_none());
+/// ```
+///
+/// # Arguments
+///
+/// * `file_path` - The percent-encoded path of the BOTW game file relative to the root folder
+///
+/// # Returns
+///
+/// Returns an Option with the canonical resource path as a String or None if the path is not
+/// validly encoded, decodes to a NUL or a `.` or `..` segment, or does not appear valid
+pub fn get_canon_name_from_url(file_path: &str) -> Option<String> {
+    Canonicalizer::new()
+        .decode_percent_encoding(true)
+        .canonicalize(file_path)
+        .ok()
+}
+
+/// Convert the components of a path relative to a BOTW content root, like virtual file systems
+/// for zip or SARC archives give, into a [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path). The result is the same as for the
+/// components joined into a path string, including an empty last component meaning a folder,
+/// but no separators have to be replaced, and a component which itself contains a separator is
+/// rejected instead of being split.
+/// Example:
+///
+/// ```
+/// use botw_utils::canon_from_segments;
+/// assert_eq!(
+///    canon_from_segments(["content", "Actor", "Pack", "Enemy_Lizalfos_Senior.sbactorpack"])
+///        .unwrap(),
+///    "Actor/Pack/Enemy_Lizalfos_Senior.bactorpack"
+/// );
+/// assert!(
+///     canon_from_segments(["content", "Actor/Pack", "Enemy_Lizalfos_Senior.sbactorpack"])
+///         .is_none()
+/// );
+/// ```
+///
+/// # Arguments
+///
+/// * `segments` - The components of the path of the BOTW game file relative to the root folder
+///
+/// # Returns
+///
+/// Returns an Option with the canonical resource path as a String or None if the path does not
+/// appear valid
+pub fn canon_from_segments<'a, I: IntoIterator<Item = &'a str>>(segments: I) -> Option<String> {
+    let mut path = String::new();
+    for (i, segment) in segments.into_iter().enumerate() {
+        if segment.contains(['/', '\\']) {
+            return None;
+        }
+        if i > 0 {
+            path.push('/');
+        }
+        path.push_str(segment);
+    }
+    let mut normalized = String::new();
+    let path = if needs_normalizing(&path) {
+        normalize_path(&path, &mut normalized);
+        &normalized
+    } else {
+        &path
+    };
+    let parts = find_canon_with(path, &Canonicalizer::new()).ok()?;
+    Some(parts.build())
+}
 
 /// Convert a path relative to a BOTW content root into a [canonical resource
 /// path](https://zeldamods.org/wiki/Canonical_resource_path). Example:
@@ -23,99 +845,2949 @@ pub mod hashes;
 /// # Returns
 ///
 /// Returns an Option with the canonical resource path as a String or None if the path does not
-/// appear valid
+/// appear valid. Use [`try_get_canon_name`] to find out why a path was rejected.
+#[cfg(feature = "std")]
 pub fn get_canon_name<P: AsRef<Path>>(file_path: P) -> Option<String> {
-    static RE: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(
-            "(?i)(((Content|(atmosphere/(titles|contents)/)?01007EF00011E000/romfs)/)|\
-        ((Aoc(/0010)?|(atmosphere/(titles|contents)/)?01007EF00011[ef]00[0-2]/romfs)/))",
-        )
-        .unwrap()
-    });
-    let mut normalized = file_path
-        .as_ref()
-        .to_string_lossy()
-        .replace('\\', "/")
-        .replace(".s", ".");
-    normalized = RE
-        .replace_all(&normalized, |caps: &regex::Captures| {
-            if caps[0].starts_with("content") || caps[0].contains("01007EF00011E000") {
-                "content/"
-            } else {
-                "aoc/0010/"
-            }
-        })
-        .to_string();
-    if normalized.starts_with("aoc/") {
-        Some(
-            normalized
-                .replace("aoc/content", "Aoc")
-                .replace("aoc", "Aoc"),
-        )
-    } else if normalized.starts_with("content") && !normalized.contains("/aoc") {
-        Some(normalized.replace("content/", ""))
-    } else {
-        None
+    try_get_canon_name(file_path).ok()
+}
+
+/// Convert either a path relative to a BOTW content root or an already canonical resource path
+/// into a [canonical resource path](https://zeldamods.org/wiki/Canonical_resource_path). Strings
+/// which are already canonical, as checked by [`is_valid_canon_path`], are returned unchanged,
+/// so this is idempotent and safe to use when it is not known whether a path has been
+/// canonicalized yet. Example:
+///
+/// ```
+/// use botw_utils::canonicalize;
+/// let canon = canonicalize("content\\Actor\\Pack\\Enemy_Lizalfos_Senior.sbactorpack").unwrap();
+/// assert_eq!(canon, "Actor/Pack/Enemy_Lizalfos_Senior.bactorpack");
+/// assert_eq!(canonicalize(&canon).unwrap(), canon);
+/// ```
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the BOTW game file relative to the root folder, or its canonical
+///   resource path
+///
+/// # Returns
+///
+/// Returns an Option with the canonical resource path as a String or None if the path does not
+/// appear valid
+#[cfg(feature = "std")]
+pub fn canonicalize<P: AsRef<Path>>(file_path: P) -> Option<String> {
+    let path = file_path.as_ref().to_str()?;
+    match try_get_canon_name(path) {
+        Ok(canon) => Some(canon).filter(|canon| is_valid_canon_path(canon)),
+        Err(_) if is_valid_canon_path(path) => Some(path.to_owned()),
+        Err(_) => None,
     }
 }
 
-/// Convert a BOTW game resource path without a root folder into a [canonical resource
-/// path](https://zeldamods.org/wiki/Canonical_resource_path). Most useful for normalizing paths
-/// to resources inside of SARC archives. Example:
+/// Convert a path relative to a BOTW content root into a [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path), borrowing from the input where
+/// possible. The result is always the same as [`get_canon_name`], but when the path already
+/// uses single forward slashes with no `.` or `..` segments, is in a base game or update root,
+/// and does not need the yaz0 `s` removed from its extension, the canonical path is just a
+/// slice of the input and nothing is allocated. Otherwise a single String is allocated.
+/// Example:
 ///
 /// ```
-/// use botw_utils::get_canon_name_without_root;
+/// use botw_utils::get_canon_name_cow;
+/// use std::borrow::Cow;
+/// assert!(matches!(
+///    get_canon_name_cow("content/Pack/Bootup.pack"),
+///    Some(Cow::Borrowed("Pack/Bootup.pack"))
+/// ));
 /// assert_eq!(
-///    get_canon_name_without_root("Actor/Pack/GameROMPlayer.sbactorpack"),
-///    "Actor/Pack/GameROMPlayer.bactorpack"
+///    get_canon_name_cow("content\\Actor\\ActorInfo.product.sbyml").unwrap(),
+///    "Actor/ActorInfo.product.byml"
 /// );
 /// ```
 ///
 /// # Arguments
 ///
-/// * `file_path` - The path of the BOTW game file, not relative to a root folder but bare. In most
-///   cases this would only come from a path inside of a SARC.
+/// * `file_path` - The path of the BOTW game file relative to the root folder
 ///
 /// # Returns
 ///
-/// Returns the canonical resource path as a String.
-pub fn get_canon_name_without_root<P: AsRef<Path>>(file_path: P) -> String {
-    file_path
-        .as_ref()
-        .to_string_lossy()
-        .replace('\\', "/")
-        .replace(".s", ".")
+/// Returns an Option with the canonical resource path or None if the path does not appear valid
+#[cfg(feature = "std")]
+pub fn get_canon_name_cow<P: AsRef<Path> + ?Sized>(file_path: &P) -> Option<Cow<'_, str>> {
+    let path = strip_windows_prefix(file_path.as_ref().to_str()?);
+    if !needs_normalizing(path) {
+        let parts = find_canon(path).ok()?;
+        return Some(match parts.as_borrowed() {
+            Some(canon) => Cow::Borrowed(canon),
+            None => Cow::Owned(parts.build()),
+        });
+    }
+    let mut normalized = String::with_capacity(path.len());
+    normalize_path(path, &mut normalized);
+    let (prefix, start, yaz0_prefix) = {
+        let parts = find_canon(&normalized).ok()?;
+        let start = parts.rest.as_ptr() as usize - normalized.as_ptr() as usize;
+        (parts.prefix(), start, parts.yaz0_prefix)
+    };
+    if let Some(i) = yaz0_prefix {
+        normalized.remove(start + i);
+    }
+    normalized.replace_range(..start, prefix);
+    Some(Cow::Owned(normalized))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    #[test]
-    fn canon_names() {
-        assert_eq!(
-            get_canon_name("content\\Actor\\Pack\\Enemy_Lizal_Senior.sbactorpack",).unwrap(),
-            "Actor/Pack/Enemy_Lizal_Senior.bactorpack"
-        );
-        assert_eq!(
-            get_canon_name("aoc/0010/Map/MainField/A-1/A-1_Dynamic.smubin",).unwrap(),
-            "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin"
-        );
-        assert_eq!(
-            get_canon_name(
-                "atmosphere/contents/01007EF00011E000/romfs/Actor/ActorInfo.product.sbyml",
-            )
-            .unwrap(),
+/// Convert a path relative to a BOTW content root into a [`CanonPath`]. This is the same as
+/// [`get_canon_name`], but the result carries the guarantee that it has been canonicalized.
+/// Example:
+///
+/// ```
+/// use botw_utils::get_canon_path;
+/// let canon = get_canon_path("aoc/0010/Pack/AocMainField.pack").unwrap();
+/// assert_eq!(&*canon, "Aoc/0010/Pack/AocMainField.pack");
+/// ```
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the BOTW game file relative to the root folder
+///
+/// # Returns
+///
+/// Returns an Option with the CanonPath or None if the path does not appear valid
+#[cfg(feature = "std")]
+pub fn get_canon_path<P: AsRef<Path>>(file_path: P) -> Option<CanonPath> {
+    get_canon_name(file_path).map(CanonPath::new_unchecked)
+}
+
+/// Convert many paths relative to BOTW content roots into [canonical resource
+/// paths](https://zeldamods.org/wiki/Canonical_resource_path) at once. This gives the same
+/// results as calling [`get_canon_name`] on each path, but reuses one scratch buffer for all of
+/// them. Example:
+///
+/// ```
+/// use botw_utils::get_canon_names;
+/// let names = get_canon_names(&["content/Pack/Bootup.pack", "Hellow/Sweetie.tardis"]);
+/// assert_eq!(names[0].1.as_deref(), Some("Pack/Bootup.pack"));
+/// assert_eq!(names[1].1, None);
+/// ```
+///
+/// # Arguments
+///
+/// * `paths` - The paths of the BOTW game files relative to their root folders
+///
+/// # Returns
+///
+/// Returns a Vec of each path paired with an Option of its canonical resource path, in the same
+/// order as the input.
+#[cfg(feature = "std")]
+pub fn get_canon_names<I, P>(paths: I) -> Vec<(PathBuf, Option<String>)>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let mut scratch = String::new();
+    paths
+        .into_iter()
+        .map(|path| {
+            let path = path.as_ref();
+            let canon = classify_with(path, &mut scratch)
+                .ok()
+                .map(|(_, canon)| canon);
+            (path.to_path_buf(), canon)
+        })
+        .collect()
+}
+
+/// Convert many paths relative to BOTW content roots into [canonical resource
+/// paths](https://zeldamods.org/wiki/Canonical_resource_path) at once, splitting the work across
+/// one thread per available CPU. Each thread reuses its own scratch buffer. The results are the
+/// same and in the same order as [`get_canon_names`].
+///
+/// Requires the `parallel` feature.
+///
+/// # Arguments
+///
+/// * `paths` - The paths of the BOTW game files relative to their root folders
+///
+/// # Returns
+///
+/// Returns a Vec of each path paired with an Option of its canonical resource path, in the same
+/// order as the input.
+#[cfg(feature = "parallel")]
+pub fn par_get_canon_names<P: AsRef<Path> + Sync>(paths: &[P]) -> Vec<(PathBuf, Option<String>)> {
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let chunk_size = paths.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = paths
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || get_canon_names(chunk)))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    })
+}
+
+/// Convert a path relative to a BOTW content root into a [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path), along with the kind of root folder
+/// the file lives under. The canonical resource path is always the same as the one returned by
+/// [`get_canon_name`]. Like it, this works purely on the path string. Example:
+///
+/// ```
+/// use botw_utils::{classify_path, RootKind};
+/// assert_eq!(
+///    classify_path("mlc01/usr/title/0005000E/101C9400/content/Pack/Bootup.pack").unwrap(),
+///    (RootKind::Update, "Pack/Bootup.pack".to_owned())
+/// );
+/// ```
+///
+/// On Switch the base game and its update share the `01007EF00011E000` title ID, so both
+/// classify as [`RootKind::Base`]. Callers who know that they are looking at an update dump have
+/// to account for that themselves.
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the BOTW game file relative to the root folder
+///
+/// # Returns
+///
+/// Returns an Option with the root kind and the canonical resource path as a String or None if
+/// the path does not appear valid
+#[cfg(feature = "std")]
+pub fn classify_path<P: AsRef<Path>>(file_path: P) -> Option<(RootKind, String)> {
+    classify(file_path.as_ref()).ok()
+}
+
+/// Checks whether the folder at `pos` only appears in the mod or dump layouts of one platform
+#[cfg(feature = "std")]
+fn platform_at(path: &str, pos: usize) -> Option<Platform> {
+    let (name, _) = next_segment(path, pos)?;
+    let is_any = |names: &[&str]| names.iter().any(|n| name.eq_ignore_ascii_case(n));
+    if is_any(&["sdmc:", "atmosphere", "sxos", "reinx", "romfs", "exefs"])
+        || titles::switch_folder_kind(name).is_some()
+    {
+        Some(Platform::Switch)
+    } else if is_any(&["mlc01", "graphicPacks", "sdcafiine"])
+        || folders_at(path, pos, &["wiiu", "games"]).is_some()
+        || folders_at(path, pos, &["vol", "content"]).is_some()
+        || vol_aoc_kind(name).is_some()
+        || wiiu_title_at(path, pos).is_some()
+    {
+        Some(Platform::WiiU)
+    } else {
+        None
+    }
+}
+
+/// Guesses which platform a mod or dump is for from the folders in a path to it or to a file in
+/// it, like `atmosphere` or a Switch title ID for Switch and `graphicPacks` or a Wii U title ID
+/// for Wii U. Only the path itself is looked at, so it works for paths inside of zip archives
+/// too. Layouts used on both platforms, like a bare `content` or `aoc` folder, give no hint.
+/// Example:
+///
+/// ```
+/// use botw_utils::{hashes::Platform, path_platform_hint};
+/// assert_eq!(
+///    path_platform_hint("sdmc:/atmosphere/contents/01007EF00011E000"),
+///    Some(Platform::Switch)
+/// );
+/// assert_eq!(
+///    path_platform_hint("graphicPacks/BreathOfTheWild_Mod/content/Pack/Bootup.pack"),
+///    Some(Platform::WiiU)
+/// );
+/// assert_eq!(path_platform_hint("My Mod/content/Pack/Bootup.pack"), None);
+/// ```
+///
+/// # Arguments
+///
+/// * `path` - The path to a mod or dump folder, or to a file in one
+///
+/// # Returns
+///
+/// Returns an Option with the platform or None if the layout does not say. The outermost folder
+/// which only one platform uses wins.
+#[cfg(feature = "std")]
+pub fn path_platform_hint<P: AsRef<Path>>(path: P) -> Option<Platform> {
+    let mut normalized = String::new();
+    normalize_path(
+        strip_windows_prefix(path.as_ref().to_str()?),
+        &mut normalized,
+    );
+    normalized.push('/');
+    let platform = segment_starts(&normalized).find_map(|pos| platform_at(&normalized, pos));
+    platform
+}
+
+/// Gets the folder names at the end of a dump or mod root which can say what its platform is: the
+/// root's own name and, when it is a `content` or `romfs` folder, the title folder above it. The
+/// folders further up are outside the dump, so a parent named like `load` or `atmosphere` says
+/// nothing about it.
+#[cfg(feature = "std")]
+pub(crate) fn root_folder_names(root: &Path) -> PathBuf {
+    let names: Vec<&str> = root
+        .components()
+        .rev()
+        .filter_map(|component| match component {
+            std::path::Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .take(3)
+        .collect();
+    let mut keep = names.len().min(1);
+    if names.first().is_some_and(|name| {
+        name.eq_ignore_ascii_case("content") || name.eq_ignore_ascii_case("romfs")
+    }) {
+        keep = names.len().min(2);
+    }
+    if keep > 0
+        && keep < names.len()
+        && titles::split_wiiu_title_kind(names[keep], names[keep - 1]).is_some()
+    {
+        keep += 1;
+    }
+    names[..keep].iter().rev().collect()
+}
+
+/// Guesses which platform a game dump is for from the dump itself, by looking at a handful of
+/// files in it rather than walking it. The hints used are the dump folder's own name and its
+/// title folder, checked like [`path_platform_hint`] does, the console's own files next to the
+/// game content, like the Wii U `code/U-King.rpx` and `meta/meta.xml` or the Switch
+/// `exefs/main`, the byte order of `Pack/Bootup.pack`, which is big endian on Wii U and little
+/// endian on Switch, and how the textures of `Model/Link` are split, into `Tex1` and `Tex2`
+/// files on Wii U or one `Tex` file on Switch. Example:
+///
+/// ```no_run
+/// use botw_utils::{guess_platform_from_dump, hashes::{Platform, StockHashTable}};
+/// if let Some(platform) = guess_platform_from_dump("dump/content") {
+///     let table = StockHashTable::new(platform);
+///     println!("Checking a {} dump", platform);
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `root` - The dump's title folder or its `content` or `romfs` folder
+///
+/// # Returns
+///
+/// Returns an Option with the platform, or None if no hint was found or the hints disagree.
+#[cfg(feature = "std")]
+pub fn guess_platform_from_dump<P: AsRef<Path>>(root: P) -> Option<Platform> {
+    let root = root.as_ref();
+    let root = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let mut hints = Vec::new();
+    hints.extend(path_platform_hint(root_folder_names(&root)));
+    for dir in std::iter::once(root.as_path()).chain(root.parent()) {
+        if dir.join("code/U-King.rpx").is_file() || dir.join("meta/meta.xml").is_file() {
+            hints.push(Platform::WiiU);
+        }
+        if dir.join("exefs/main").is_file() || dir.join("exefs/main.npdm").is_file() {
+            hints.push(Platform::Switch);
+        }
+    }
+    let content = [root.clone(), root.join("content"), root.join("romfs")]
+        .iter()
+        .find(|dir| dir.join("Pack").is_dir() || dir.join("Model").is_dir())
+        .cloned();
+    if let Some(content) = content {
+        let mut header = [0; 8];
+        let read = std::fs::File::open(content.join("Pack/Bootup.pack"))
+            .and_then(|mut file| std::io::Read::read_exact(&mut file, &mut header));
+        if read.is_ok() && header.starts_with(b"SARC") {
+            match header[6..8] {
+                [0xFE, 0xFF] => hints.push(Platform::WiiU),
+                [0xFF, 0xFE] => hints.push(Platform::Switch),
+                _ => (),
+            }
+        }
+        let model = content.join("Model");
+        if model.join("Link.Tex1.sbfres").is_file() || model.join("Link.Tex2.sbfres").is_file() {
+            hints.push(Platform::WiiU);
+        }
+        if model.join("Link.Tex.sbfres").is_file() {
+            hints.push(Platform::Switch);
+        }
+    }
+    let first = *hints.first()?;
+    hints.iter().all(|hint| *hint == first).then_some(first)
+}
+
+/// Convert a BOTW game resource path without a root folder into a [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path). Most useful for normalizing paths
+/// to resources inside of SARC archives. Names from SARC listings in the wild are cleaned up
+/// first: leading `./` segments are removed, repeated slashes are collapsed, and a leading slash
+/// is removed unless the name has no folders, since the stock game data archives really do use
+/// names like `/bool_data_0.bgdata`. That means a composite `archive//inner` path is treated as
+/// a single name; use [`get_nested_canon_name`] to split those. Example:
+///
+/// ```
+/// use botw_utils::get_canon_name_without_root;
+/// assert_eq!(
+///    get_canon_name_without_root("Actor/Pack/GameROMPlayer.sbactorpack"),
+///    "Actor/Pack/GameROMPlayer.bactorpack"
+/// );
+/// assert_eq!(
+///    get_canon_name_without_root("./Event//EventInfo.product.sbyml"),
+///    "Event/EventInfo.product.byml"
+/// );
+/// ```
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the BOTW game file, not relative to a root folder but bare. In most
+///   cases this would only come from a path inside of a SARC.
+///
+/// # Returns
+///
+/// Returns the canonical resource path as a String. Unlike the other canonicalization functions,
+/// this does not reject paths which are not valid UTF-8, but replaces the invalid bytes with
+/// U+FFFD, so check [`Path::to_str`] first if that matters.
+#[cfg(feature = "std")]
+pub fn get_canon_name_without_root<P: AsRef<Path>>(file_path: P) -> String {
+    let path = file_path.as_ref().to_string_lossy().replace('\\', "/");
+    let segments: Vec<&str> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .skip_while(|segment| *segment == ".")
+        .collect();
+    let name = match segments.as_slice() {
+        [name] if path.starts_with('/') => ["/", name].concat(),
+        _ => segments.join("/"),
+    };
+    strip_yaz0_prefix(&name)
+}
+
+/// A resource nested inside of one or more archives, as found by [`get_nested_canon_name`]
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct NestedCanon {
+    /// The canonical resource path of the outermost archive
+    pub parent: String,
+    /// The canonical resource paths of each nested file, starting with the one directly inside of
+    /// the parent archive. Files nested inside of DLC archives get the `Aoc/0010/` prefix too.
+    pub inner: Vec<String>,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for NestedCanon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.parent)?;
+        for inner in &self.inner {
+            write!(f, "//{}", inner)?;
+        }
+        Ok(())
+    }
+}
+
+/// Convert a path to a resource nested inside of archives, in the `<archive>//<inner path>`
+/// notation used by BCML logs and other tools, into the canonical resource paths of the archive
+/// and each nested file. The archive may be given either as a path under a root folder or as a
+/// canonical resource path already. Example:
+///
+/// ```
+/// use botw_utils::get_nested_canon_name;
+/// let nested = get_nested_canon_name(concat!(
+///     "aoc/0010/Pack/AocMainField.pack//Actor/Pack/Npc_A.sbactorpack",
+///     "//Actor/AIProgram/Npc_A.baiprog",
+/// ))
+/// .unwrap();
+/// assert_eq!(nested.parent, "Aoc/0010/Pack/AocMainField.pack");
+/// assert_eq!(
+///    nested.inner,
+///    ["Aoc/0010/Actor/Pack/Npc_A.bactorpack", "Aoc/0010/Actor/AIProgram/Npc_A.baiprog"]
+/// );
+/// ```
+///
+/// # Arguments
+///
+/// * `file_path` - The nested path, with each level separated by `//`
+///
+/// # Returns
+///
+/// Returns an Option with the canonical resource paths of the archive and its nested files, or
+/// None if the archive path does not appear valid or a nested path is empty. A path with no `//`
+/// marker gives just the parent with no nested files.
+#[cfg(feature = "std")]
+pub fn get_nested_canon_name<P: AsRef<Path>>(file_path: P) -> Option<NestedCanon> {
+    let path = strip_windows_prefix(file_path.as_ref().to_str()?).replace('\\', "/");
+    let mut levels = path.split("//");
+    let parent = levels.next()?;
+    let parent = match find_canon(parent) {
+        Ok(parts) => parts.build(),
+        Err(Rejection::UnrecognizedRoot) if !parent.is_empty() => strip_yaz0_prefix(parent),
+        Err(_) => return None,
+    };
+    let inner = levels
+        .map(|inner| match inner {
+            "" => None,
+            _ => Some(get_nested_file_canon(&parent, inner)),
+        })
+        .collect::<Option<_>>()?;
+    Some(NestedCanon { parent, inner })
+}
+
+/// Gets the [canonical resource path](https://zeldamods.org/wiki/Canonical_resource_path) of a
+/// file inside of an archive from the canonical path of the archive and the name of the file in
+/// its file table. The name is cleaned up like with [`get_canon_name_without_root`], and files in
+/// DLC archives get the `Aoc/0010/` prefix of the archive. Otherwise the archive's own path is
+/// not part of the result, even though the stock hash tables also list the files of packs like
+/// `Pack/Bootup.pack` and `Pack/TitleBG.pack` under `Pack/Bootup/` and `Pack/TitleBG/`. Example:
+///
+/// ```
+/// use botw_utils::get_nested_file_canon;
+/// assert_eq!(
+///    get_nested_file_canon("Pack/Bootup.pack", "Event/EventInfo.product.sbyml"),
+///    "Event/EventInfo.product.byml"
+/// );
+/// assert_eq!(
+///    get_nested_file_canon(
+///        "Aoc/0010/Pack/RemainsWind.pack",
+///        "Actor/Pack/Enemy_Guardian_A.sbactorpack"
+///    ),
+///    "Aoc/0010/Actor/Pack/Enemy_Guardian_A.bactorpack"
+/// );
+/// ```
+///
+/// # Arguments
+///
+/// * `parent_canon` - The canonical resource path of the archive
+/// * `inner_path` - The name of the file inside of the archive
+///
+/// # Returns
+///
+/// Returns the canonical resource path of the nested file as a String
+#[cfg(feature = "std")]
+pub fn get_nested_file_canon(parent_canon: &str, inner_path: &str) -> String {
+    let canon = get_canon_name_without_root(inner_path);
+    let prefixed = folders_at(&canon, 0, &["aoc"]).map(|next| skip_aoc_index(&canon, next));
+    match prefixed {
+        Some(next) => ["Aoc/0010/", &canon[next..]].concat(),
+        // Bare names like the game data archive's `/bool_data_0.bgdata` never get a prefix
+        None if parent_canon.starts_with("Aoc/0010/") && !canon.starts_with('/') => {
+            ["Aoc/0010/", &canon].concat()
+        }
+        None => canon,
+    }
+}
+
+/// Splits a texture file name like `Model/Enemy_Dragon.Tex1.bfres` into the path before the
+/// texture suffix, the suffix, like `Tex1` or `Tex1.1`, and the extension, which may be yaz0
+/// compressed
+#[cfg(feature = "std")]
+fn split_texture_name(name: &str) -> Option<(&str, &str, &str)> {
+    let (rest, ext) = name.rsplit_once('.')?;
+    if ext != "bfres" && ext != "sbfres" {
+        return None;
+    }
+    let (stem, tex) = rest.rsplit_once('.')?;
+    if matches!(tex, "Tex" | "Tex1" | "Tex2") {
+        return Some((stem, tex, ext));
+    }
+    // Some large Wii U models split their textures further, like `Enemy_Dragon.Tex1.1.bfres`
+    let (stem, tex1) = stem.rsplit_once('.')?;
+    if tex1 == "Tex1" && !tex.is_empty() && tex.bytes().all(|b| b.is_ascii_digit()) {
+        Some((stem, &rest[stem.len() + 1..], ext))
+    } else {
+        None
+    }
+}
+
+/// Checks whether a canonical resource path or file name is for the textures of a model, like
+/// `Model/Enemy_Dragon.Tex.bfres` on Switch or `Model/Enemy_Dragon.Tex1.sbfres` on Wii U, rather
+/// than for the model itself. Example:
+///
+/// ```
+/// use botw_utils::is_texture_pack_name;
+/// assert!(is_texture_pack_name("Model/Enemy_Dragon.Tex2.bfres"));
+/// assert!(!is_texture_pack_name("Model/Enemy_Dragon.bfres"));
+/// ```
+#[cfg(feature = "std")]
+pub fn is_texture_pack_name(name: &str) -> bool {
+    split_texture_name(name).is_some()
+}
+
+/// Translate the name of a model texture file to the naming of another platform. The Wii U
+/// version splits each model's textures between a `.Tex1.bfres` file and a `.Tex2.bfres` file
+/// with the larger mipmaps, and sometimes more `.Tex1.1.bfres` style files, while the Switch
+/// version has a single `.Tex.bfres` file. The Wii U `Tex1` file and the Switch `Tex` file are
+/// treated as counterparts, so the other Wii U files have no Switch counterpart of their own.
+/// Names which are already in the target platform's naming, and names of files which are not
+/// textures, are returned unchanged. Example:
+///
+/// ```
+/// use botw_utils::{hashes::Platform, texture_name_for_platform};
+/// assert_eq!(
+///    texture_name_for_platform("Model/Enemy_Dragon.Tex1.bfres", Platform::Switch).unwrap(),
+///    "Model/Enemy_Dragon.Tex.bfres"
+/// );
+/// assert_eq!(
+///    texture_name_for_platform("Model/Enemy_Dragon.Tex.sbfres", Platform::WiiU).unwrap(),
+///    "Model/Enemy_Dragon.Tex1.sbfres"
+/// );
+/// assert_eq!(texture_name_for_platform("Model/Enemy_Dragon.Tex2.bfres", Platform::Switch), None);
+/// ```
+///
+/// # Arguments
+///
+/// * `canon` - The canonical resource path or physical path of the file
+/// * `target` - The platform whose naming to use
+///
+/// # Returns
+///
+/// Returns an Option with the name on the target platform as a String, or None if the file has
+/// no counterpart there.
+#[cfg(feature = "std")]
+pub fn texture_name_for_platform(canon: &str, target: Platform) -> Option<String> {
+    let (stem, tex, ext) = match split_texture_name(canon) {
+        Some(parts) => parts,
+        None => return Some(canon.to_owned()),
+    };
+    let tex = match (target, tex) {
+        (Platform::Switch, "Tex") | (Platform::WiiU, "Tex1") => return Some(canon.to_owned()),
+        (Platform::Switch, "Tex1") => "Tex",
+        (Platform::Switch, _) => return None,
+        (Platform::WiiU, "Tex") => "Tex1",
+        (Platform::WiiU, _) => return Some(canon.to_owned()),
+    };
+    Some([stem, ".", tex, ".", ext].concat())
+}
+
+/// Convert a [canonical resource path](https://zeldamods.org/wiki/Canonical_resource_path) back
+/// into the physical path of the file relative to a mod or dump root folder. This re-adds the `s`
+/// to the extensions of files the game stores yaz0 compressed and places the file under the
+/// content or DLC root folder used on the given platform. Example:
+///
+/// ```
+/// use botw_utils::{canon_to_physical, hashes::Platform};
+/// use std::path::Path;
+/// assert_eq!(
+///    canon_to_physical("Actor/Pack/Enemy_Lizalfos_Senior.bactorpack", Platform::WiiU),
+///    Path::new("content/Actor/Pack/Enemy_Lizalfos_Senior.sbactorpack")
+/// );
+/// assert_eq!(
+///    canon_to_physical("Aoc/0010/Pack/AocMainField.pack", Platform::Switch),
+///    Path::new("01007EF00011F001/romfs/Pack/AocMainField.pack")
+/// );
+/// ```
+///
+/// # Arguments
+///
+/// * `canon` - The canonical resource path of the file
+/// * `platform` - Whether to use the Wii U or Switch root folder layout
+///
+/// # Returns
+///
+/// Returns the physical path of the file as a PathBuf.
+#[cfg(feature = "std")]
+pub fn canon_to_physical(canon: &str, platform: Platform) -> PathBuf {
+    let (root, path) = match canon.strip_prefix("Aoc/0010/") {
+        Some(path) => (
+            match platform {
+                Platform::WiiU => "aoc/0010",
+                Platform::Switch => "01007EF00011F001/romfs",
+            },
+            path,
+        ),
+        None => (
+            match platform {
+                Platform::WiiU => "content",
+                Platform::Switch => "01007EF00011E000/romfs",
+            },
+            canon,
+        ),
+    };
+    Path::new(root).join(physical_name(path))
+}
+
+/// Re-adds the `s` to the extension of a file the game stores yaz0 compressed
+#[cfg(feature = "std")]
+pub(crate) fn physical_name(path: &str) -> String {
+    let name_start = path.rfind('/').map(|i| i + 1).unwrap_or(0);
+    match path[name_start..].rfind('.') {
+        Some(dot) if extensions::is_compressible_ext(&path[name_start + dot + 1..]) => {
+            let ext_start = name_start + dot + 1;
+            [&path[..ext_start], "s", &path[ext_start..]].concat()
+        }
+        _ => path.to_owned(),
+    }
+}
+
+/// Extensions of files which have no entry in the resource size table, either because the game
+/// does not load them through the resource system or because they are only mod source files
+const RSTB_EXEMPT_EXTS: &[&str] = &[
+    "pack", "bgdata", "bgsvdata", "bfstm", "msbt", "txt", "yml", "json", "ini", "png",
+];
+
+/// Convert a [canonical resource path](https://zeldamods.org/wiki/Canonical_resource_path) into
+/// the name of its entry in the resource size table (RSTB). The `Aoc/0010/` prefix is dropped
+/// from DLC resources, except for DLC map files, which keep it to stay distinct from the base
+/// game map files of the same names. Example:
+///
+/// ```
+/// use botw_utils::canon_to_rstb_name;
+/// assert_eq!(
+///    canon_to_rstb_name("Aoc/0010/Actor/Pack/Enemy_Guardian_A_Mini.bactorpack").unwrap(),
+///    "Actor/Pack/Enemy_Guardian_A_Mini.bactorpack"
+/// );
+/// assert_eq!(
+///    canon_to_rstb_name("Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin").unwrap(),
+///    "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin"
+/// );
+/// assert_eq!(canon_to_rstb_name("Pack/Bootup.pack"), None);
+/// ```
+///
+/// # Arguments
+///
+/// * `canon` - The canonical resource path of the file
+///
+/// # Returns
+///
+/// Returns an Option with the RSTB entry name as a String or None if the file is exempt from
+/// the RSTB.
+pub fn canon_to_rstb_name(canon: &str) -> Option<String> {
+    let name_start = canon.rfind('/').map(|i| i + 1).unwrap_or(0);
+    if let Some(dot) = canon[name_start..].rfind('.') {
+        if RSTB_EXEMPT_EXTS.contains(&&canon[name_start + dot + 1..]) {
+            return None;
+        }
+    }
+    Some(match canon.strip_prefix("Aoc/0010/") {
+        Some(rest) if !rest.starts_with("Map/") => rest.to_owned(),
+        _ => canon.to_owned(),
+    })
+}
+
+/// Convert the name of a resource size table (RSTB) entry back into a [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path). Since most DLC resources lose their
+/// `Aoc/0010/` prefix in the RSTB, the kind of content the entry is for has to be given. Example:
+///
+/// ```
+/// use botw_utils::{rstb_name_to_canon, RootKind};
+/// assert_eq!(
+///    rstb_name_to_canon("Actor/Pack/Enemy_Guardian_A_Mini.bactorpack", RootKind::Dlc).unwrap(),
+///    "Aoc/0010/Actor/Pack/Enemy_Guardian_A_Mini.bactorpack"
+/// );
+/// assert_eq!(rstb_name_to_canon("Map/MainField/A-1/A-1_Dynamic.mubin", RootKind::Dlc), None);
+/// ```
+///
+/// # Arguments
+///
+/// * `name` - The RSTB entry name
+/// * `kind` - Whether the entry is for base game, update, or DLC content
+///
+/// # Returns
+///
+/// Returns an Option with the canonical resource path as a String or None if the name cannot be
+/// an entry for that kind of content.
+pub fn rstb_name_to_canon(name: &str, kind: RootKind) -> Option<String> {
+    match (kind, name.strip_prefix("Aoc/0010/")) {
+        (RootKind::Dlc, Some(rest)) if rest.starts_with("Map/") => Some(name.to_owned()),
+        (RootKind::Dlc, None) if !name.starts_with("Map/") => Some(["Aoc/0010/", name].concat()),
+        (RootKind::Base, None) | (RootKind::Update, None) => Some(name.to_owned()),
+        _ => None,
+    }
+}
+
+/// The prefix of every DLC resource's canonical resource path
+pub(crate) const AOC_PREFIX: &str = "Aoc/0010/";
+
+/// Gets the base game form of a DLC [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path) by dropping its `Aoc/0010/` prefix.
+/// This does not check that the base game has a file of that name. Example:
+///
+/// ```
+/// use botw_utils::strip_aoc_prefix;
+/// assert_eq!(
+///    strip_aoc_prefix("Aoc/0010/Map/MainField/A-1/A-1_Static.mubin"),
+///    Some("Map/MainField/A-1/A-1_Static.mubin")
+/// );
+/// assert_eq!(strip_aoc_prefix("Pack/Bootup.pack"), None);
+/// ```
+///
+/// # Arguments
+///
+/// * `canon` - The canonical resource path of the file
+///
+/// # Returns
+///
+/// Returns an Option with the path after the prefix, or None if the path is not for DLC content.
+pub fn strip_aoc_prefix(canon: &str) -> Option<&str> {
+    canon.strip_prefix(AOC_PREFIX)
+}
+
+/// Gets the DLC form of a [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path) by adding the `Aoc/0010/` prefix.
+/// Paths which already have it, and bare names like `/bool_data_0.bgdata` which only exist inside
+/// archives, are returned unchanged. Example:
+///
+/// ```
+/// use botw_utils::with_aoc_prefix;
+/// assert_eq!(
+///    with_aoc_prefix("Pack/RemainsWind.pack"),
+///    "Aoc/0010/Pack/RemainsWind.pack"
+/// );
+/// assert_eq!(
+///    with_aoc_prefix("Aoc/0010/Pack/RemainsWind.pack"),
+///    "Aoc/0010/Pack/RemainsWind.pack"
+/// );
+/// ```
+///
+/// # Arguments
+///
+/// * `canon` - The canonical resource path of the file
+///
+/// # Returns
+///
+/// Returns the canonical resource path with the DLC prefix as a String.
+pub fn with_aoc_prefix(canon: &str) -> String {
+    if canon.starts_with(AOC_PREFIX) || canon.starts_with('/') {
+        canon.to_owned()
+    } else {
+        [AOC_PREFIX, canon].concat()
+    }
+}
+
+/// Gets the form a [canonical resource path](https://zeldamods.org/wiki/Canonical_resource_path)
+/// would have under the other content root: the base game form of a DLC path, or the DLC form of
+/// a base game or update path. Some files, like the field map sections which the DLC replaces,
+/// really do exist in both, but many names only exist in one, so check the result against the
+/// stock files before relying on it. Example:
+///
+#[cfg_attr(all(feature = "wiiu-data", feature = "switch-data"), doc = "```")]
+#[cfg_attr(
+    not(all(feature = "wiiu-data", feature = "switch-data")),
+    doc = "```ignore"
+)]
+/// use botw_utils::{hashes::{Platform, StockHashTable}, sibling_variants};
+/// for &platform in &[Platform::WiiU, Platform::Switch] {
+///     let table = StockHashTable::new(platform);
+///     let canon = "Map/MainField/A-1/A-1_Static.mubin";
+///     assert!(!table.is_file_new(canon));
+///     for sibling in sibling_variants(canon) {
+///         assert_eq!(sibling, "Aoc/0010/Map/MainField/A-1/A-1_Static.mubin");
+///         assert!(!table.is_file_new(&sibling));
+///     }
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `canon` - The canonical resource path of the file
+///
+/// # Returns
+///
+/// Returns an iterator over the other forms of the path, which is empty for bare names like
+/// `/bool_data_0.bgdata` that belong to no content root.
+pub fn sibling_variants(canon: &str) -> impl Iterator<Item = String> {
+    let sibling = match strip_aoc_prefix(canon) {
+        Some(base) => Some(base.to_owned()),
+        None if canon.starts_with('/') => None,
+        None => Some(with_aoc_prefix(canon)),
+    };
+    sibling.into_iter()
+}
+
+/// Why [`suggest_root_for_canon`] suggested a content root
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum RootReason {
+    /// The name has the `Aoc/0010/` prefix, so it can only be DLC content
+    Prefix,
+    /// The name, or its form under the other root, is a stock file
+    Stock,
+    /// The name is not a stock file, so the root was guessed from the stock files in the nearest
+    /// folder above it which has any
+    Heuristic,
+}
+
+/// A content root suggested by [`suggest_root_for_canon`], and why
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct RootSuggestion {
+    /// The root the file should be shipped under. This is never [`RootKind::Update`], since mods
+    /// ship update files under the base game root.
+    pub kind: RootKind,
+    /// Whether the suggestion is certain or a guess
+    pub reason: RootReason,
+}
+
+/// Suggests whether a file should be shipped under the base game root, like `content`, or the
+/// DLC root, like `aoc/0010`, from its [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path). Names with the `Aoc/0010/` prefix
+/// are DLC, and names which are stock files in either form go where the stock file is. Other
+/// names are guessed from the folders they are in, like `Map/AocField/`, which only the DLC has
+/// files in, so check [`RootSuggestion::reason`] before trusting the result. Example:
+///
+#[cfg_attr(feature = "wiiu-data", doc = "```")]
+#[cfg_attr(not(feature = "wiiu-data"), doc = "```ignore")]
+/// use botw_utils::{hashes::Platform, suggest_root_for_canon, RootKind, RootReason};
+/// let suggestion = suggest_root_for_canon(
+///     "Map/AocField/A-1/A-1.00_Clustering.blwp",
+///     Platform::WiiU,
+/// );
+/// assert_eq!(suggestion.kind, RootKind::Dlc);
+/// assert_eq!(suggestion.reason, RootReason::Stock);
+/// ```
+///
+/// # Arguments
+///
+/// * `canon` - The canonical resource path of the file
+/// * `platform` - Whether to check the Wii U or Switch stock files
+///
+/// # Returns
+///
+/// Returns a [`RootSuggestion`] with the suggested root and the reason for it.
+#[cfg(feature = "std")]
+pub fn suggest_root_for_canon(canon: &str, platform: Platform) -> RootSuggestion {
+    let suggestion = |kind, reason| RootSuggestion { kind, reason };
+    if strip_aoc_prefix(canon).is_some() {
+        return suggestion(RootKind::Dlc, RootReason::Prefix);
+    }
+    let table = hashes::StockHashTable::get(platform);
+    if table.contains(canon) {
+        return suggestion(RootKind::Base, RootReason::Stock);
+    }
+    let dlc = with_aoc_prefix(canon);
+    if table.contains(&dlc) {
+        return suggestion(RootKind::Dlc, RootReason::Stock);
+    }
+    for (end, _) in canon.rmatch_indices('/') {
+        let folder = &canon[..=end];
+        let dlc_folder = [AOC_PREFIX, folder].concat();
+        let (base, dlc) = table.get_stock_files().fold((0, 0), |(base, dlc), name| {
+            (
+                base + usize::from(name.starts_with(folder)),
+                dlc + usize::from(name.starts_with(&dlc_folder)),
+            )
+        });
+        if base > 0 || dlc > 0 {
+            let kind = if dlc > base {
+                RootKind::Dlc
+            } else {
+                RootKind::Base
+            };
+            return suggestion(kind, RootReason::Heuristic);
+        }
+    }
+    suggestion(RootKind::Base, RootReason::Heuristic)
+}
+
+/// The kind of game content stored under a root folder
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum RootKind {
+    /// Base game content
+    Base,
+    /// Update content, which overlays the base game
+    Update,
+    /// DLC content, which has canonical resource paths under `Aoc/0010/`
+    Dlc,
+}
+
+/// A root folder of BOTW content found by [`find_content_root`]
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ContentRoot {
+    /// The root directory. Files under it have canonical resource paths relative to it, with an
+    /// `Aoc/0010/` prefix for DLC roots.
+    pub path: PathBuf,
+    /// Whether the root holds base, update, or DLC content
+    pub kind: RootKind,
+}
+
+/// Gets the title ID a directory belongs to from its own name or, for the split Wii U
+/// `00050000/101C9400` form, the names of it and its parent
+#[cfg(feature = "std")]
+fn dir_title_kind(dir: &Path) -> Option<RootKind> {
+    let name = dir.file_name()?.to_str()?;
+    titles::wiiu_title_kind(name)
+        .or_else(|| titles::switch_folder_kind(name))
+        .or_else(|| vol_aoc_kind(name))
+        .or_else(|| {
+            let high = dir.parent()?.file_name()?.to_str()?;
+            titles::title_kind(&[high, name].concat())
+        })
+}
+
+/// Walks up from a file or folder to find the BOTW content root folder it belongs to. The
+/// recognized roots are `content` folders (including those inside Wii U title folders and Cemu
+/// graphic packs), `aoc/0010` and Wii U DLC `content/0010` folders (or the `0011` and `0012`
+/// DLC indices), bare `aoc` folders without the `0010` layer like BCML exports, and `romfs`
+/// folders under a known Switch title ID (directly or with a mod name folder between them, like
+/// Yuzu and Ryujinx use). The nearest root wins. Example:
+///
+/// ```
+/// use botw_utils::{find_content_root, RootKind};
+/// use std::path::Path;
+/// let root = find_content_root("mods/My Mod/aoc/0010/Map/MainField/A-1/A-1_Dynamic.smubin")
+///     .unwrap();
+/// assert_eq!(root.path, Path::new("mods/My Mod/aoc/0010"));
+/// assert_eq!(root.kind, RootKind::Dlc);
+/// ```
+///
+/// # Arguments
+///
+/// * `path` - The path to a file or folder inside of a mod or game dump. If it exists, symlinks
+///   and relative components are resolved first.
+///
+/// # Returns
+///
+/// Returns an Option with the [`ContentRoot`] or None if no root folder was found.
+#[cfg(feature = "std")]
+pub fn find_content_root<P: AsRef<Path>>(path: P) -> Option<ContentRoot> {
+    let path = path.as_ref();
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    path.ancestors().find_map(|dir| {
+        Some(ContentRoot {
+            path: dir.to_path_buf(),
+            kind: dir_root_kind(dir)?,
+        })
+    })
+}
+
+/// Checks whether a directory is a content root, as described for [`find_content_root`], from
+/// its own name and the names of its parents, returning the kind of content in it
+#[cfg(feature = "std")]
+fn dir_root_kind(dir: &Path) -> Option<RootKind> {
+    let name = dir.file_name()?.to_str()?;
+    let parent = dir.parent();
+    if name.eq_ignore_ascii_case("content") {
+        match parent.and_then(dir_title_kind) {
+            Some(RootKind::Dlc) => None,
+            Some(kind) => Some(kind),
+            None => Some(RootKind::Base),
+        }
+    } else if is_aoc_index(name) {
+        let parent = parent?;
+        let parent_name = parent.file_name()?.to_str()?;
+        if parent_name.eq_ignore_ascii_case("aoc")
+            || (parent_name.eq_ignore_ascii_case("content")
+                && parent.parent().and_then(dir_title_kind) == Some(RootKind::Dlc))
+        {
+            Some(RootKind::Dlc)
+        } else {
+            None
+        }
+    } else if name.eq_ignore_ascii_case("aoc") {
+        Some(RootKind::Dlc)
+    } else if name.eq_ignore_ascii_case("romfs") {
+        let parent = parent?;
+        dir_title_kind(parent).or_else(|| parent.parent().and_then(dir_title_kind))
+    } else {
+        None
+    }
+}
+
+/// Convert a path under a known BOTW content root into a [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path), for when the root is already known,
+/// like from [`find_content_root`] or a folder picked by the user. Only the part of the path
+/// after the root is canonicalized, so the root folder can have any name, even one which looks
+/// like another root. Example:
+///
+/// ```
+/// use botw_utils::{get_canon_name_from_root, RootKind};
+/// use std::path::Path;
+/// let root = Path::new("mods/content/My DLC Mod");
+/// assert_eq!(
+///     get_canon_name_from_root(
+///         root,
+///         &root.join("Map/MainField/A-1/A-1_Dynamic.smubin"),
+///         RootKind::Dlc
+///     )
+///     .unwrap(),
+///     "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin"
+/// );
+/// ```
+///
+/// # Arguments
+///
+/// * `root` - The content root folder
+/// * `file` - The path of the BOTW game file, including the root folder
+/// * `kind` - Whether the root holds base, update, or DLC content
+///
+/// # Returns
+///
+/// Returns an Option with the canonical resource path as a String or None if the file is not
+/// under the root or ends with a separator
+#[cfg(feature = "std")]
+pub fn get_canon_name_from_root(root: &Path, file: &Path, kind: RootKind) -> Option<String> {
+    if file.to_str()?.ends_with(['/', '\\']) {
+        return None;
+    }
+    canon_under_root(file.strip_prefix(root).ok()?.to_str()?, kind).ok()
+}
+
+/// Canonicalizes the part of a path after a known root folder. A DLC index folder at the start
+/// is skipped, since it means the root given was the `aoc` folder above it.
+#[cfg(feature = "std")]
+fn canon_under_root(rest: &str, kind: RootKind) -> Result<String, Rejection> {
+    let mut normalized = String::new();
+    let mut rest = if needs_normalizing(rest) {
+        normalize_path(rest, &mut normalized);
+        normalized.as_str()
+    } else {
+        rest
+    };
+    if rest.starts_with('/') || rest.split('/').next() == Some("..") {
+        return Err(Rejection::NotUnderContentRoot);
+    }
+    if kind == RootKind::Dlc {
+        rest = &rest[skip_aoc_index(rest, 0)..];
+    }
+    canon_parts(kind, rest).map(|parts| parts.build())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    /// The regex based root matching which [`find_canon`] replaced, with Switch update titles
+    /// fixed to not be DLC, kept to check that both give the same results
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn regex_find_canon(path: &str) -> Result<CanonParts<'_>, Rejection> {
+        use once_cell::sync::Lazy;
+        use regex::Regex;
+
+        static MOD_PACK: Lazy<Regex> = Lazy::new(|| {
+            Regex::new("(?i)^(?:.*?/)?(?:graphicPacks|sdcafiine/0005000[0ce]/?101C9[345]00)/[^/]+/")
+                .unwrap()
+        });
+        static MLC: Lazy<Regex> = Lazy::new(|| {
+            Regex::new("(?i)^(?:.*?/)?mlc01/usr/title/0005000([0ce])/?101C9[345]00/content/")
+                .unwrap()
+        });
+        static EMU_MOD: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(
+                "(?i)^(?:.*?/)?(?:load|mods/contents)/01007EF00011([ef])00([0-2])/[^/]+/romfs/",
+            )
+            .unwrap()
+        });
+        static RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(
+                "(?i)^(?:content|(?:atmosphere/(?:titles|contents)/)?01007EF00011E000/romfs|\
+            aoc(?:/0010)?|(?:atmosphere/(?:titles|contents)/)?01007EF00011[ef]00[0-2]/romfs)/",
+            )
+            .unwrap()
+        });
+        static CONTENT_ROOT: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(
+                "(?i)^(?:content|(?:atmosphere/(?:titles|contents)/)?01007EF00011E000/romfs)/",
+            )
+            .unwrap()
+        });
+        static UPDATE_ROOT: Lazy<Regex> = Lazy::new(|| {
+            Regex::new("(?i)^(?:atmosphere/(?:titles|contents)/)?01007EF00011E00[12]/romfs/")
+                .unwrap()
+        });
+        let path = strip_drive_prefix(strip_device_prefix(path));
+        // Only ask for captures once a layout is known to match, since they have to be allocated
+        let rooted = if let Some(pack) = MOD_PACK.find(path) {
+            &path[pack.end()..]
+        } else if MLC.is_match(path) {
+            let caps = MLC.captures(path).unwrap();
+            let rest = &path[caps[0].len()..];
+            return match caps[1].to_ascii_lowercase().as_str() {
+                "c" => canon_parts(RootKind::Dlc, rest.strip_prefix("0010/").unwrap_or(rest)),
+                "e" => canon_parts(RootKind::Update, rest),
+                _ => canon_parts(RootKind::Base, rest),
+            };
+        } else if EMU_MOD.is_match(path) {
+            let caps = EMU_MOD.captures(path).unwrap();
+            let rest = &path[caps[0].len()..];
+            return if caps[1].eq_ignore_ascii_case("f") {
+                canon_parts(RootKind::Dlc, rest)
+            } else if &caps[2] == "0" {
+                canon_parts(RootKind::Base, rest)
+            } else {
+                canon_parts(RootKind::Update, rest)
+            };
+        } else {
+            (0..path.len())
+                .rev()
+                .filter(|&i| i == 0 || path.as_bytes()[i - 1] == b'/')
+                .find(|&i| RE.is_match(&path[i..]))
+                .map(|start| &path[start..])
+                .unwrap_or(path)
+        };
+        let root = RE.find(rooted).ok_or(Rejection::UnrecognizedRoot)?;
+        let kind = if CONTENT_ROOT.is_match(rooted) {
+            RootKind::Base
+        } else if UPDATE_ROOT.is_match(rooted) {
+            RootKind::Update
+        } else {
+            RootKind::Dlc
+        };
+        canon_parts(kind, &rooted[root.end()..])
+    }
+    #[test]
+    fn canon_names() {
+        assert_eq!(
+            get_canon_name("content\\Actor\\Pack\\Enemy_Lizal_Senior.sbactorpack",).unwrap(),
+            "Actor/Pack/Enemy_Lizal_Senior.bactorpack"
+        );
+        assert_eq!(
+            get_canon_name("aoc/0010/Map/MainField/A-1/A-1_Dynamic.smubin",).unwrap(),
+            "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin"
+        );
+        assert_eq!(
+            get_canon_name(
+                "atmosphere/contents/01007EF00011E000/romfs/Actor/ActorInfo.product.sbyml",
+            )
+            .unwrap(),
+            "Actor/ActorInfo.product.byml"
+        );
+        assert_eq!(
+            get_canon_name("atmosphere/contents/01007EF00011F001/romfs/Pack/AocMainField.pack",)
+                .unwrap(),
+            "Aoc/0010/Pack/AocMainField.pack"
+        );
+        assert_eq!(get_canon_name("Hellow/Sweetie.tardis"), None);
+        assert_eq!(
+            get_canon_name_without_root("Event/EventInfo.product.sbyml"),
+            "Event/EventInfo.product.byml"
+        )
+    }
+
+    #[test]
+    fn str_canon_names() {
+        for (path, canon) in &[
+            (
+                "content\\Actor\\Pack\\Enemy_Lizal_Senior.sbactorpack",
+                Some("Actor/Pack/Enemy_Lizal_Senior.bactorpack"),
+            ),
+            (
+                "aoc/0010/Map/MainField/A-1/A-1_Dynamic.smubin",
+                Some("Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin"),
+            ),
+            (
+                "atmosphere/contents/01007EF00011E000/romfs/Actor/ActorInfo.product.sbyml",
+                Some("Actor/ActorInfo.product.byml"),
+            ),
+            (
+                "atmosphere/contents/01007EF00011F001/romfs/Pack/AocMainField.pack",
+                Some("Aoc/0010/Pack/AocMainField.pack"),
+            ),
+            ("Hellow/Sweetie.tardis", None),
+        ] {
+            assert_eq!(get_canon_name_str(path).as_deref(), *canon);
+            assert_eq!(try_get_canon_name_str(path), try_get_canon_name(path));
+        }
+    }
+
+    #[test]
+    fn yaz0_prefix() {
+        assert_eq!(
+            get_canon_name("content/Actor/ActorInfo.product.sbyml").unwrap(),
+            "Actor/ActorInfo.product.byml"
+        );
+        assert_eq!(
+            get_canon_name("aoc/0010/Map/MainField/A-1/A-1_Dynamic.smubin").unwrap(),
+            "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin"
+        );
+        assert_eq!(
+            get_canon_name("content/Actor/Pack/Npc_King.statue.sbactorpack").unwrap(),
+            "Actor/Pack/Npc_King.statue.bactorpack"
+        );
+        assert_eq!(
+            get_canon_name("content/Foo.settings/Bar.sbyml").unwrap(),
+            "Foo.settings/Bar.byml"
+        );
+        assert_eq!(
+            get_canon_name("content/Terrain/A/MainField/5700001B58.grass.extm.sstera").unwrap(),
+            "Terrain/A/MainField/5700001B58.grass.extm.stera"
+        );
+        assert_eq!(
+            get_canon_name("content/Game/Stats/archive/A-8.11.stats").unwrap(),
+            "Game/Stats/archive/A-8.11.stats"
+        );
+        assert_eq!(
+            get_canon_name_without_root("Model/Animal_Fox.Tex1.sbfres"),
+            "Model/Animal_Fox.Tex1.bfres"
+        );
+        assert_eq!(
+            get_canon_name_without_root("Message/Msg_USen.product.sarc"),
+            "Message/Msg_USen.product.sarc"
+        );
+        assert_eq!(
+            get_canon_name_without_root("Actor/Pack/Foo.sunknown"),
+            "Actor/Pack/Foo.sunknown"
+        );
+    }
+
+    #[test]
+    fn canon_errors() {
+        assert_eq!(
+            try_get_canon_name("Hellow/Sweetie.tardis"),
+            Err(CanonError::UnrecognizedRoot {
+                path: "Hellow/Sweetie.tardis".to_owned(),
+                tried: roots::root_patterns()
+            })
+        );
+        assert_eq!(
+            try_get_canon_name("content/"),
+            Err(CanonError::NotUnderContentRoot {
+                path: "content/".to_owned()
+            })
+        );
+        assert!(try_get_canon_name("Hellow/Sweetie.tardis")
+            .unwrap_err()
+            .to_string()
+            .contains("Hellow/Sweetie.tardis"));
+        #[cfg(unix)]
+        {
+            use std::ffi::OsStr;
+            use std::os::unix::ffi::OsStrExt;
+            let path = Path::new(OsStr::from_bytes(b"content/Actor/\xff.sbactorpack"));
+            assert_eq!(
+                try_get_canon_name(path),
+                Err(CanonError::NonUtf8Path(path.to_path_buf()))
+            );
+        }
+    }
+
+    #[test]
+    fn physical_paths() {
+        for (canon, wiiu, switch) in [
+            (
+                "Actor/Pack/Enemy_Lizal_Senior.bactorpack",
+                "content/Actor/Pack/Enemy_Lizal_Senior.sbactorpack",
+                "01007EF00011E000/romfs/Actor/Pack/Enemy_Lizal_Senior.sbactorpack",
+            ),
+            (
+                "Actor/ActorInfo.product.byml",
+                "content/Actor/ActorInfo.product.sbyml",
+                "01007EF00011E000/romfs/Actor/ActorInfo.product.sbyml",
+            ),
+            (
+                "Pack/Bootup.pack",
+                "content/Pack/Bootup.pack",
+                "01007EF00011E000/romfs/Pack/Bootup.pack",
+            ),
+            (
+                "Aoc/0010/Pack/AocMainField.pack",
+                "aoc/0010/Pack/AocMainField.pack",
+                "01007EF00011F001/romfs/Pack/AocMainField.pack",
+            ),
+            (
+                "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin",
+                "aoc/0010/Map/MainField/A-1/A-1_Dynamic.smubin",
+                "01007EF00011F001/romfs/Map/MainField/A-1/A-1_Dynamic.smubin",
+            ),
+            (
+                "Movie/Demo101_0.mp4",
+                "content/Movie/Demo101_0.mp4",
+                "01007EF00011E000/romfs/Movie/Demo101_0.mp4",
+            ),
+        ] {
+            assert_eq!(canon_to_physical(canon, Platform::WiiU), Path::new(wiiu));
+            assert_eq!(
+                canon_to_physical(canon, Platform::Switch),
+                Path::new(switch)
+            );
+            assert_eq!(get_canon_name(wiiu).unwrap(), canon);
+            assert_eq!(get_canon_name(switch).unwrap(), canon);
+        }
+    }
+
+    #[test]
+    fn graphic_packs() {
+        assert_eq!(
+            get_canon_name(
+                "graphicPacks/BreathOfTheWild_Mod/content/Actor/ActorInfo.product.sbyml"
+            )
+            .unwrap(),
+            "Actor/ActorInfo.product.byml"
+        );
+        assert_eq!(
+            get_canon_name(
+                "cemu/graphicPacks/BreathOfTheWild_Mod/aoc/0010/Map/MainField/A-1/A-1_Dynamic.smubin"
+            )
+            .unwrap(),
+            "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin"
+        );
+        assert_eq!(
+            get_canon_name("graphicPacks\\My Cool Mod\\content\\Pack\\Bootup.pack").unwrap(),
+            "Pack/Bootup.pack"
+        );
+        assert_eq!(
+            get_canon_name("graphicPacks/New Content/content/Actor/Pack/Npc_A.sbactorpack")
+                .unwrap(),
+            "Actor/Pack/Npc_A.bactorpack"
+        );
+        assert_eq!(
+            get_canon_name("graphicPacks/content/content/Model/Animal_Fox.Tex1.sbfres").unwrap(),
+            "Model/Animal_Fox.Tex1.bfres"
+        );
+        assert_eq!(
+            get_canon_name("graphicPacks/BotW_aoc/aoc/0010/Pack/AocMainField.pack").unwrap(),
+            "Aoc/0010/Pack/AocMainField.pack"
+        );
+        assert_eq!(
+            get_canon_name("graphicPacks/ゼルダ Mod ✨/content/Actor/Pack/Npc_A.sbactorpack")
+                .unwrap(),
+            "Actor/Pack/Npc_A.bactorpack"
+        );
+        assert_eq!(
+            get_canon_name("graphicPacks/BreathOfTheWild_Mod/rules.txt"),
+            None
+        );
+    }
+
+    #[test]
+    fn mlc_titles() {
+        for game_id in ["101C9400", "101c9500", "101C9300"] {
+            assert_eq!(
+                get_canon_name(format!(
+                    "mlc01/usr/title/00050000/{}/content/Actor/Pack/Enemy_Lizal_Senior.sbactorpack",
+                    game_id
+                ))
+                .unwrap(),
+                "Actor/Pack/Enemy_Lizal_Senior.bactorpack"
+            );
+            assert_eq!(
+                get_canon_name(format!(
+                    "C:\\Cemu\\mlc01\\usr\\title\\00050000{}\\content\\Pack\\Bootup.pack",
+                    game_id
+                ))
+                .unwrap(),
+                "Pack/Bootup.pack"
+            );
+            assert_eq!(
+                get_canon_name(format!(
+                    "mlc01/usr/title/0005000e/{}/content/Actor/ActorInfo.product.sbyml",
+                    game_id
+                ))
+                .unwrap(),
+                "Actor/ActorInfo.product.byml"
+            );
+            assert_eq!(
+                get_canon_name(format!(
+                    "mlc01/usr/title/0005000c{}/content/0010/Map/MainField/A-1/A-1_Dynamic.smubin",
+                    game_id
+                ))
+                .unwrap(),
+                "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin"
+            );
+        }
+        assert_eq!(
+            get_canon_name("mlc01/usr/title/00050000/101C9400/code/U-King.rpx"),
+            None
+        );
+    }
+
+    #[test]
+    fn sdcafiine() {
+        assert_eq!(
+            get_canon_name(
+                "sdcafiine/00050000101C9400/MyMod/content/Actor/Pack/Enemy_Lizal_Senior.sbactorpack"
+            )
+            .unwrap(),
+            "Actor/Pack/Enemy_Lizal_Senior.bactorpack"
+        );
+        assert_eq!(
+            get_canon_name("sdcafiine/00050000101c9400/content/content/Pack/Bootup.pack").unwrap(),
+            "Pack/Bootup.pack"
+        );
+        assert_eq!(
+            get_canon_name(
+                "sdcafiine/00050000101C9400/MyMod/aoc/0010/Map/MainField/A-1/A-1_Dynamic.smubin"
+            )
+            .unwrap(),
+            "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin"
+        );
+    }
+
+    #[test]
+    fn yuzu_load() {
+        assert_eq!(
+            get_canon_name("load/01007EF00011E000/My Mod/romfs/Actor/ActorInfo.product.sbyml")
+                .unwrap(),
+            "Actor/ActorInfo.product.byml"
+        );
+        assert_eq!(
+            get_canon_name("yuzu/load/01007ef00011e000/Mod v1.2.3/romfs/Pack/Bootup.pack").unwrap(),
+            "Pack/Bootup.pack"
+        );
+        assert_eq!(
+            get_canon_name("load/01007EF00011E000/romfs/romfs/Pack/TitleBG.pack").unwrap(),
+            "Pack/TitleBG.pack"
+        );
+        assert_eq!(
+            get_canon_name(
+                "load/01007EF00011F001/Better romfs DLC/romfs/Map/MainField/A-1/A-1_Dynamic.smubin"
+            )
+            .unwrap(),
+            "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin"
+        );
+    }
+
+    #[test]
+    fn ryujinx_mods() {
+        assert_eq!(
+            get_canon_name(
+                "mods/contents/01007ef00011f001/MyDLCMod/romfs/Map/MainField/A-1/A-1_Dynamic.smubin"
+            )
+            .unwrap(),
+            "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin"
+        );
+        assert_eq!(
+            get_canon_name(
+                "Ryujinx/mods/contents/01007ef00011e000/MyMod/romfs/Actor/Pack/Npc_A.sbactorpack"
+            )
+            .unwrap(),
+            "Actor/Pack/Npc_A.bactorpack"
+        );
+    }
+
+    #[test]
+    fn device_prefixes() {
+        assert_eq!(
+            get_canon_name("sdmc:/atmosphere/contents/01007EF00011E000/romfs/Pack/Bootup.pack")
+                .unwrap(),
+            "Pack/Bootup.pack"
+        );
+        assert_eq!(
+            get_canon_name("sd:/atmosphere/titles/01007EF00011F001/romfs/Pack/AocMainField.pack")
+                .unwrap(),
+            "Aoc/0010/Pack/AocMainField.pack"
+        );
+        assert_eq!(
+            get_canon_name("NAND:/atmosphere/contents/01007EF00011E000/romfs/Pack/Bootup.pack")
+                .unwrap(),
+            "Pack/Bootup.pack"
+        );
+        assert_eq!(
+            strip_device_prefix("C:/content/Pack/Bootup.pack"),
+            "C:/content/Pack/Bootup.pack"
+        );
+        assert_eq!(
+            strip_device_prefix("sdcafiine/content"),
+            "sdcafiine/content"
+        );
+    }
+
+    #[test]
+    fn absolute_paths() {
+        assert_eq!(
+            get_canon_name(
+                "D:\\cemu\\graphicPacks\\BreathOfTheWild_Mod\\content\\Actor\\ActorInfo.product.sbyml"
+            )
+            .unwrap(),
             "Actor/ActorInfo.product.byml"
         );
         assert_eq!(
-            get_canon_name("atmosphere/contents/01007EF00011F001/romfs/Pack/AocMainField.pack",)
+            get_canon_name("E:\\content\\Actor\\Pack\\Enemy_Lizal_Senior.sbactorpack").unwrap(),
+            "Actor/Pack/Enemy_Lizal_Senior.bactorpack"
+        );
+        assert_eq!(
+            get_canon_name("e:/aoc/0010/Pack/AocMainField.pack").unwrap(),
+            "Aoc/0010/Pack/AocMainField.pack"
+        );
+        assert_eq!(
+            get_canon_name("C:\\Users\\Link/Mods\\My Mod/content\\Pack/Bootup.pack").unwrap(),
+            "Pack/Bootup.pack"
+        );
+        assert_eq!(
+            get_canon_name("C:/content/Mods/Other Mod/content/Pack/TitleBG.pack").unwrap(),
+            "Pack/TitleBG.pack"
+        );
+        assert_eq!(
+            get_canon_name("/home/link/mods/My Mod/aoc/0010/Map/MainField/A-1/A-1_Static.smubin")
                 .unwrap(),
+            "Aoc/0010/Map/MainField/A-1/A-1_Static.mubin"
+        );
+        assert_eq!(get_canon_name("C:/Users/Link/Pack/Bootup.pack"), None);
+    }
+
+    #[test]
+    fn content_roots() {
+        for (path, root, kind) in [
+            (
+                "mods/My Mod/content/Actor/Pack/Npc_A.sbactorpack",
+                "mods/My Mod/content",
+                RootKind::Base,
+            ),
+            (
+                "graphicPacks/BreathOfTheWild_Mod/aoc/0010/Pack/AocMainField.pack",
+                "graphicPacks/BreathOfTheWild_Mod/aoc/0010",
+                RootKind::Dlc,
+            ),
+            (
+                "mlc01/usr/title/0005000E/101C9400/content/Pack/Bootup.pack",
+                "mlc01/usr/title/0005000E/101C9400/content",
+                RootKind::Update,
+            ),
+            (
+                "mlc01/usr/title/0005000c101c9400/content/0010/Pack/AocMainField.pack",
+                "mlc01/usr/title/0005000c101c9400/content/0010",
+                RootKind::Dlc,
+            ),
+            (
+                "atmosphere/contents/01007EF00011E000/romfs/Pack/Bootup.pack",
+                "atmosphere/contents/01007EF00011E000/romfs",
+                RootKind::Base,
+            ),
+            (
+                "load/01007EF00011F001/My DLC Mod/romfs/Pack/AocMainField.pack",
+                "load/01007EF00011F001/My DLC Mod/romfs",
+                RootKind::Dlc,
+            ),
+            (
+                "content/Mods/Other Mod/content/Pack/Bootup.pack",
+                "content/Mods/Other Mod/content",
+                RootKind::Base,
+            ),
+        ] {
+            assert_eq!(
+                find_content_root(path),
+                Some(ContentRoot {
+                    path: PathBuf::from(root),
+                    kind
+                })
+            );
+        }
+        assert_eq!(
+            find_content_root("mods/My Mod/romfs/Pack/Bootup.pack"),
+            None
+        );
+        assert_eq!(find_content_root("Pack/Bootup.pack"), None);
+    }
+
+    #[test]
+    fn classify_paths() {
+        for (path, kind) in [
+            ("content/Pack/Bootup.pack", RootKind::Base),
+            ("aoc/0010/Pack/AocMainField.pack", RootKind::Dlc),
+            (
+                "mlc01/usr/title/00050000/101C9400/content/Pack/Bootup.pack",
+                RootKind::Base,
+            ),
+            (
+                "mlc01/usr/title/0005000E/101C9400/content/Pack/Bootup.pack",
+                RootKind::Update,
+            ),
+            (
+                "mlc01/usr/title/0005000C/101C9400/content/0010/Pack/AocMainField.pack",
+                RootKind::Dlc,
+            ),
+            (
+                "atmosphere/contents/01007EF00011E000/romfs/Pack/Bootup.pack",
+                RootKind::Base,
+            ),
+            (
+                "atmosphere/contents/01007EF00011F001/romfs/Pack/AocMainField.pack",
+                RootKind::Dlc,
+            ),
+            (
+                "load/01007EF00011E000/My Mod/romfs/Pack/Bootup.pack",
+                RootKind::Base,
+            ),
+            (
+                "graphicPacks/BreathOfTheWild_Mod/aoc/0010/Pack/AocMainField.pack",
+                RootKind::Dlc,
+            ),
+        ] {
+            let (found, canon) = classify_path(path).unwrap();
+            assert_eq!(found, kind);
+            assert_eq!(Some(canon), get_canon_name(path));
+        }
+        assert_eq!(classify_path("Hellow/Sweetie.tardis"), None);
+    }
+
+    #[test]
+    fn batch_canon_names() {
+        let paths = [
+            "content/Actor/Pack/Enemy_Lizal_Senior.sbactorpack",
+            "aoc/0010/Pack/AocMainField.pack",
+            "Hellow/Sweetie.tardis",
+            "atmosphere/contents/01007EF00011E000/romfs/Pack/Bootup.pack",
+        ];
+        let names = get_canon_names(paths);
+        assert_eq!(names.len(), paths.len());
+        for ((path, canon), expected) in names.iter().zip(paths.iter()) {
+            assert_eq!(path, Path::new(expected));
+            assert_eq!(canon, &get_canon_name(expected));
+        }
+        #[cfg(feature = "parallel")]
+        assert_eq!(par_get_canon_names(&paths), names);
+    }
+
+    #[test]
+    fn canon_name_cow() {
+        for path in [
+            "content/Pack/Bootup.pack",
+            "content\\Actor\\Pack\\Enemy_Lizal_Senior.sbactorpack",
+            "aoc/0010/Map/MainField/A-1/A-1_Dynamic.smubin",
+            "aoc\\0010\\Pack\\AocMainField.pack",
+            "graphicPacks/BreathOfTheWild_Mod/content/Actor/ActorInfo.product.sbyml",
+            "Hellow/Sweetie.tardis",
+        ] {
+            assert_eq!(
+                get_canon_name_cow(path).map(Cow::into_owned),
+                get_canon_name(path)
+            );
+        }
+        assert!(matches!(
+            get_canon_name_cow("atmosphere/contents/01007EF00011E000/romfs/Pack/Bootup.pack"),
+            Some(Cow::Borrowed("Pack/Bootup.pack"))
+        ));
+        assert!(matches!(
+            get_canon_name_cow("aoc/0010/Pack/AocMainField.pack"),
+            Some(Cow::Owned(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn canon_name_bytes() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        assert_eq!(
+            try_get_canon_name_bytes(b"aoc\\0010\\Pack\\AocMainField.pack").unwrap(),
             "Aoc/0010/Pack/AocMainField.pack"
         );
-        assert_eq!(get_canon_name("Hellow/Sweetie.tardis"), None);
         assert_eq!(
-            get_canon_name_without_root("Event/EventInfo.product.sbyml"),
-            "Event/EventInfo.product.byml"
+            try_get_canon_name_bytes(b"content/Actor/Pack/Npc_R\xe9sidence.sbactorpack").unwrap(),
+            "Actor/Pack/Npc_R\u{e9}sidence.bactorpack"
+        );
+        assert_eq!(
+            try_get_canon_name_bytes(b"Mods/\xff/Sweetie.tardis"),
+            Err(CanonError::NonUtf8Path(PathBuf::from(OsStr::from_bytes(
+                b"Mods/\xff/Sweetie.tardis"
+            ))))
+        );
+        assert!(matches!(
+            try_get_canon_name_bytes(b"Hellow/Sweetie.tardis"),
+            Err(CanonError::UnrecognizedRoot { .. })
+        ));
+    }
+
+    #[test]
+    fn wiiu_regions() {
+        for (high, kind) in &[
+            ("00050000", "Base"),
+            ("0005000E", "Update"),
+            ("0005000C", "Dlc"),
+        ] {
+            for region in &["101C9300", "101C9400", "101C9500"] {
+                assert_eq!(
+                    format!(
+                        "{:?}",
+                        titles::title_kind(&[high, *region].concat()).unwrap()
+                    ),
+                    *kind
+                );
+            }
+        }
+        assert_eq!(
+            get_canon_name("0005000C/101C9500/content/0010/Pack/AocMainField.pack").unwrap(),
+            "Aoc/0010/Pack/AocMainField.pack"
+        );
+        assert_eq!(
+            classify_path(
+                "mlc01/usr/title/0005000e/101c9300/content/Actor/ActorInfo.product.sbyml"
+            ),
+            Some((RootKind::Update, "Actor/ActorInfo.product.byml".into()))
+        );
+        assert_eq!(
+            classify_path("0005000E101C9300/content/Pack/Bootup.pack"),
+            Some((RootKind::Update, "Pack/Bootup.pack".into()))
+        );
+        assert_eq!(titles::title_kind("0005000E101C9600"), None);
+        assert_eq!(
+            get_canon_name("0005000E101C9600/content/Pack/Bootup.pack").unwrap(),
+            "Pack/Bootup.pack"
+        );
+    }
+
+    #[test]
+    fn wiiu_update_titles() {
+        for root in &[
+            "0005000e101c9400",
+            "0005000e/101c9400",
+            "0005000E101C9400",
+            "E:/Dumps/0005000E/101C9400",
+        ] {
+            assert_eq!(
+                get_canon_name(format!("{}/content/Actor/ActorInfo.product.sbyml", root)).unwrap(),
+                "Actor/ActorInfo.product.byml"
+            );
+            assert_eq!(
+                classify_path(format!("{}\\content\\Pack\\Bootup.pack", root)),
+                Some((RootKind::Update, "Pack/Bootup.pack".into()))
+            );
+        }
+        assert_eq!(
+            classify_path("00050000101C9400/content/Pack/Bootup.pack"),
+            Some((RootKind::Base, "Pack/Bootup.pack".into()))
+        );
+    }
+
+    #[test]
+    fn wiiu_dlc_titles() {
+        for root in &[
+            "0005000c101c9400",
+            "0005000c/101c9400",
+            "0005000C101C9400",
+            "dumps/0005000C/101C9400",
+        ] {
+            assert_eq!(
+                get_canon_name(format!("{}/content/0010/Pack/AocMainField.pack", root)).unwrap(),
+                "Aoc/0010/Pack/AocMainField.pack"
+            );
+            assert_eq!(
+                classify_path(format!(
+                    "{}\\content\\0010\\Map\\MainField\\A-1\\A-1_Dynamic.smubin",
+                    root
+                )),
+                Some((
+                    RootKind::Dlc,
+                    "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin".into()
+                ))
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn idempotent_canonicalization() {
+        for &platform in &[Platform::WiiU, Platform::Switch] {
+            let table = hashes::StockHashTable::new(platform);
+            for (i, canon) in table.get_stock_files().enumerate() {
+                assert_eq!(canonicalize(canon).as_deref(), Some(canon));
+                // Bare names only exist inside archives, so they have no physical path
+                if canon.starts_with('/') {
+                    continue;
+                }
+                let physical = canon_to_physical(canon, platform);
+                let once = canonicalize(&physical);
+                assert_eq!(once.as_deref(), Some(canon), "{}", physical.display());
+                if i % 10 == 0 {
+                    assert_eq!(canonicalize(once.as_ref().unwrap()), once);
+                }
+            }
+        }
+        assert_eq!(
+            canonicalize("Aoc/0010/Pack/AocMainField.pack").unwrap(),
+            "Aoc/0010/Pack/AocMainField.pack"
+        );
+        assert_eq!(
+            canonicalize("Hellow/Sweetie.tardis").unwrap(),
+            "Hellow/Sweetie.tardis"
+        );
+        assert_eq!(canonicalize("C:\\Hellow\\Sweetie.tardis"), None);
+        for path in &[
+            "graphicPacks/Mod/content/Pack/Bootup.pack",
+            "sdmc:/atmosphere/contents/01007EF00011E000/romfs/Pack/Bootup.pack",
+        ] {
+            assert_eq!(canonicalize(path).unwrap(), "Pack/Bootup.pack", "{}", path);
+            assert!(!is_valid_canon_path(path), "{}", path);
+        }
+        assert_eq!(canonicalize("content/atmosphere"), None);
+        assert_eq!(canonicalize("atmosphere"), None);
+        assert_eq!(
+            canonicalize("graphicPacks/Mod/content/Foo/content/Bar.bfres"),
+            None
+        );
+    }
+
+    #[test]
+    fn windows_prefixes() {
+        assert_eq!(
+            get_canon_name("\\\\?\\D:\\mods\\content\\Actor\\Pack\\Enemy_Lizal_Senior.sbactorpack")
+                .unwrap(),
+            "Actor/Pack/Enemy_Lizal_Senior.bactorpack"
+        );
+        for path in &[
+            "\\\\server\\share\\mods\\content\\Pack\\Bootup.pack",
+            "\\\\?\\UNC\\server\\share\\mods\\content\\Pack\\Bootup.pack",
+            "\\\\.\\D:\\mods\\content\\Pack\\Bootup.pack",
+            "//?/D:/mods/content/Pack/Bootup.pack",
+        ] {
+            assert_eq!(
+                get_canon_name(path).unwrap(),
+                "Pack/Bootup.pack",
+                "{}",
+                path
+            );
+            assert_eq!(get_canon_name_cow(*path).unwrap(), "Pack/Bootup.pack");
+        }
+        assert_eq!(
+            get_canon_name("\\\\server\\content\\..\\Pack\\Bootup.pack"),
+            None
+        );
+        let mut long = String::from("\\\\?\\C:\\Users\\Link\\AppData\\Roaming\\mods");
+        while long.len() <= 260 {
+            long.push_str("\\A Very Long Mod Folder Name");
+        }
+        long.push_str("\\aoc\\0010\\Map\\MainField\\A-1\\A-1_Dynamic.smubin");
+        assert_eq!(
+            get_canon_name(&long).unwrap(),
+            "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin"
+        );
+        assert_eq!(get_canon_name(&long), get_canon_name(&long[4..]));
+        assert_eq!(
+            get_nested_canon_name(
+                "\\\\?\\D:\\content\\Pack\\Bootup.pack//Actor/ActorInfo.product.sbyml"
+            )
+            .unwrap()
+            .to_string(),
+            "Pack/Bootup.pack//Actor/ActorInfo.product.byml"
+        );
+    }
+
+    #[test]
+    fn explicit_roots() {
+        let root = Path::new("/home/link/content/mods/romfs");
+        assert_eq!(
+            get_canon_name_from_root(
+                root,
+                &root.join("Actor/Pack/Enemy_Lizal_Senior.sbactorpack"),
+                RootKind::Update
+            )
+            .unwrap(),
+            "Actor/Pack/Enemy_Lizal_Senior.bactorpack"
+        );
+        assert_eq!(
+            get_canon_name_from_root(
+                Path::new("aoc"),
+                Path::new("aoc/Pack/./AocMainField.pack"),
+                RootKind::Dlc
+            )
+            .unwrap(),
+            "Aoc/0010/Pack/AocMainField.pack"
+        );
+        for file in &[
+            "/home/link/content/mods/romfs",
+            "/home/link/content/mods/Pack/Bootup.pack",
+            "/home/link/content/mods/romfs/../Pack/Bootup.pack",
+            "/home/link/content/mods/romfs/aoc/0010/Pack/AocMainField.pack",
+            "Pack/Bootup.pack",
+        ] {
+            assert_eq!(
+                get_canon_name_from_root(root, Path::new(file), RootKind::Base),
+                None,
+                "{}",
+                file
+            );
+        }
+    }
+
+    #[test]
+    fn url_encoded_paths() {
+        for (path, canon) in &[
+            (
+                "content/Actor/Pack/Enemy%20Test.sbactorpack",
+                "Actor/Pack/Enemy Test.bactorpack",
+            ),
+            (
+                "content%5CActor%5cPack%5CEnemy_Lizal_Senior.sbactorpack",
+                "Actor/Pack/Enemy_Lizal_Senior.bactorpack",
+            ),
+            (
+                "aoc%2F0010%2FPack%2FAocMainField.pack",
+                "Aoc/0010/Pack/AocMainField.pack",
+            ),
+            (
+                "content/Pack/Bootup%2520Copy.pack",
+                "Pack/Bootup%20Copy.pack",
+            ),
+            (
+                "content/Pack/%E3%83%86%E3%82%B9%E3%83%88.pack",
+                "Pack/テスト.pack",
+            ),
+        ] {
+            assert_eq!(get_canon_name_from_url(path).unwrap(), *canon, "{}", path);
+        }
+        for path in &[
+            "content%252FPack%252FBootup.pack",
+            "content/Pack/Bootup.pack%00",
+            "content/Pack/Bootup.pack%2",
+            "content/Pack/Bootup.pack%zz",
+            "content/Pack/%FF.pack",
+            "content/Actor/..%2F..%2FPack/Bootup.pack",
+            "content%5C.%5CPack/Bootup.pack",
+        ] {
+            assert_eq!(get_canon_name_from_url(path), None, "{}", path);
+        }
+    }
+
+    #[test]
+    fn loadiine_games() {
+        for (path, canon) in &[
+            (
+                "sd:/wiiu/games/The Legend of Zelda Breath of the Wild [ALZE01]/content/Actor/Pack/Enemy_Lizal_Senior.sbactorpack",
+                "Actor/Pack/Enemy_Lizal_Senior.bactorpack",
+            ),
+            (
+                "wiiu\\games\\The Legend of Zelda Breath of the Wild [ALZE01]\\aoc\\0010\\Pack\\AocMainField.pack",
+                "Aoc/0010/Pack/AocMainField.pack",
+            ),
+            (
+                "wiiu/games/Zelda BOTW (all content) [ALZP01]/content/Pack/Bootup.pack",
+                "Pack/Bootup.pack",
+            ),
+            (
+                "wiiu/games/content [ALZJ01]/content/Pack/Bootup.pack",
+                "Pack/Bootup.pack",
+            ),
+        ] {
+            assert_eq!(get_canon_name(path).unwrap(), *canon, "{}", path);
+        }
+        assert!(matches!(
+            try_get_canon_name(
+                "wiiu/games/The Legend of Zelda Breath of the Wild [ALZE01]/code/U-King.rpx"
+            ),
+            Err(CanonError::UnrecognizedRoot { .. })
+        ));
+    }
+
+    #[test]
+    fn dumper_folder_names() {
+        for (path, canon, kind) in &[
+            (
+                "01007EF00011E000 - The Legend of Zelda Breath of the Wild v0 (BASE)/romfs/Pack/Bootup.pack",
+                "Pack/Bootup.pack",
+                RootKind::Base,
+            ),
+            (
+                "dumps\\01007EF00011E800 - The Legend of Zelda Breath of the Wild v208 (UPD)\\romfs\\Actor\\Pack\\Enemy_Lizal_Senior.sbactorpack",
+                "Actor/Pack/Enemy_Lizal_Senior.bactorpack",
+                RootKind::Update,
+            ),
+            (
+                "01007EF00011F001 - The Legend of Zelda Breath of the Wild [DLC]/romfs/Pack/AocMainField.pack",
+                "Aoc/0010/Pack/AocMainField.pack",
+                RootKind::Dlc,
+            ),
+            (
+                "01007EF00011F002 (DLC)/romfs/Pack/AocMainField.pack",
+                "Aoc/0010/Pack/AocMainField.pack",
+                RootKind::Dlc,
+            ),
+        ] {
+            assert_eq!(
+                classify_path(path),
+                Some((*kind, canon.to_string())),
+                "{}",
+                path
+            );
+            let root = find_content_root(path.replace('\\', "/")).unwrap();
+            assert_eq!(root.kind, *kind);
+        }
+        assert!(get_canon_name("01007EF00011E0001/romfs/Pack/Bootup.pack").is_none());
+        assert!(matches!(
+            try_get_canon_name("01007EF00011E000 - BotW v0 (BASE)/exefs/main.npdm"),
+            Err(CanonError::NotRomfs {
+                folder: "exefs",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn aoc_in_names() {
+        for path in &[
+            "content/Sound/Resource/Stream/Demo_aoc_Intro.bfstm",
+            "content/EventFlow/aocResident.bfevfl",
+            "content/aocOld/Pack/Bootup.pack",
+            "graphicPacks/BotW_aoc_Fix/content/Mods/my_aoc/Pack/Bootup.pack",
+            "01007EF00011E000/romfs/Sound/aoc_Intro/Demo.bfstm",
+            "content/Actor/aoc/Foo.bfstm",
+            "mods/content/Actor/aoc/0010/Foo.bfstm",
+            "01007EF00011E000/romfs/Actor/aoc/Foo.bfres",
+        ] {
+            let canon = get_canon_name(path).unwrap();
+            assert!(!canon.starts_with("Aoc/"), "{}", path);
+            assert!(path.ends_with(&canon), "{}", path);
+        }
+        assert!(matches!(
+            try_get_canon_name(
+                "graphicPacks/BreathOfTheWild_Mod/content/aoc/0010/Pack/AocMainField.pack"
+            ),
+            Err(CanonError::NotUnderContentRoot { .. })
+        ));
+        assert_eq!(
+            get_canon_name("content/aoc/0010/Pack/AocMainField.pack").unwrap(),
+            "Aoc/0010/Pack/AocMainField.pack"
+        );
+        assert_eq!(
+            get_canon_name("aoc/0010/Actor/aoc/Foo.bfstm").unwrap(),
+            "Aoc/0010/Actor/aoc/Foo.bfstm"
+        );
+    }
+
+    #[test]
+    fn ftp_volumes() {
+        for (path, canon) in &[
+            (
+                "/vol/content/Actor/Pack/Enemy_Lizal_Senior.sbactorpack",
+                "Actor/Pack/Enemy_Lizal_Senior.bactorpack",
+            ),
+            ("VOL/Content/Pack/Bootup.pack", "Pack/Bootup.pack"),
+            (
+                "/vol/aoc0005000c101c9400/content/0010/Pack/AocMainField.pack",
+                "Aoc/0010/Pack/AocMainField.pack",
+            ),
+            (
+                "dump\\vol\\AOC0005000C101C9300\\content\\0010\\Map\\MainField\\A-1\\A-1_Dynamic.smubin",
+                "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin",
+            ),
+            (
+                "vol/aoc0005000C101C9500/content/0010/Pack/AocMainField.pack",
+                "Aoc/0010/Pack/AocMainField.pack",
+            ),
+        ] {
+            assert_eq!(get_canon_name(path).unwrap(), *canon, "{}", path);
+        }
+        assert_eq!(
+            find_content_root("/vol/aoc0005000c101c9400/content/0010/Pack/AocMainField.pack"),
+            Some(ContentRoot {
+                path: PathBuf::from("/vol/aoc0005000c101c9400/content/0010"),
+                kind: RootKind::Dlc,
+            })
+        );
+        assert!(
+            !get_canon_name("vol/aoc00050000101c9400/content/0010/Pack/AocMainField.pack")
+                .unwrap_or_default()
+                .starts_with("Aoc/")
+        );
+    }
+
+    #[test]
+    fn cfw_title_folders() {
+        for prefix in &[
+            "atmosphere/titles",
+            "atmosphere/contents",
+            "sxos/titles",
+            "SXOS\\titles",
+            "reinx/titles",
+        ] {
+            for (title_id, canon, kind) in &[
+                ("01007EF00011E000", "Pack/Bootup.pack", RootKind::Base),
+                (
+                    "01007EF00011F001",
+                    "Aoc/0010/Pack/Bootup.pack",
+                    RootKind::Dlc,
+                ),
+                (
+                    "01007EF00011F002",
+                    "Aoc/0010/Pack/Bootup.pack",
+                    RootKind::Dlc,
+                ),
+            ] {
+                let path = format!("sdmc:/{}/{}/romfs/Pack/Bootup.pack", prefix, title_id);
+                assert_eq!(
+                    classify_path(&path),
+                    Some((*kind, canon.to_string())),
+                    "{}",
+                    path
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "wiiu-data")]
+    fn nested_file_canons() {
+        let table = hashes::get_hash_table(Platform::WiiU);
+        let mut bootup = 0;
+        for name in table.keys() {
+            if let Some(inner) = name.strip_prefix("Pack/Bootup/") {
+                let physical = canon_to_physical(inner, Platform::WiiU);
+                let physical = physical.strip_prefix("content").unwrap().to_str().unwrap();
+                let canon = get_nested_file_canon("Pack/Bootup.pack", physical);
+                assert_eq!(canon, inner);
+                assert!(table.contains_key(canon.as_str()));
+                bootup += 1;
+            }
+        }
+        assert!(bootup > 100);
+        let player =
+            get_nested_file_canon("Pack/TitleBG.pack", "Actor/Pack/GameROMPlayer.sbactorpack");
+        assert_eq!(player, "Actor/Pack/GameROMPlayer.bactorpack");
+        assert_eq!(
+            get_nested_file_canon(&player, "Actor/AS/Player_FaceSerious.bas"),
+            "Actor/AS/Player_FaceSerious.bas"
+        );
+        for (inner, canon) in &[
+            (
+                "Map/MainField/A-1/A-1_TeraTree.sblwp",
+                "Aoc/0010/Map/MainField/A-1/A-1_TeraTree.blwp",
+            ),
+            (
+                "Aoc/0010/Terrain/A/AocField/5300000034.hght.stera",
+                "Aoc/0010/Terrain/A/AocField/5300000034.hght.stera",
+            ),
+            ("/bool_data_0.bgdata", "/bool_data_0.bgdata"),
+        ] {
+            assert_eq!(
+                get_nested_file_canon("Aoc/0010/Pack/AocMainField.pack", inner),
+                *canon
+            );
+        }
+    }
+
+    #[test]
+    fn dump_platforms() {
+        use crate::test_util::TempDir;
+        let dir = TempDir::new("dump-platform");
+        let wiiu_pack = b"SARC\x00\x14\xFE\xFF".as_ref();
+        let switch_pack = b"SARC\x14\x00\xFF\xFE".as_ref();
+        for (file, data) in &[
+            ("wiiu/code/U-King.rpx", b"RPX".as_ref()),
+            ("wiiu/meta/meta.xml", b"<menu />"),
+            ("wiiu/content/Pack/Bootup.pack", wiiu_pack),
+            ("wiiu/content/Model/Link.Tex2.sbfres", b"Yaz0"),
+            ("switch/exefs/main", b"NSO0"),
+            ("switch/romfs/Pack/Bootup.pack", switch_pack),
+            ("bare/Pack/Bootup.pack", switch_pack),
+            ("textures/Model/Link.Tex1.sbfres", b"Yaz0"),
+            ("mixed/Pack/Bootup.pack", wiiu_pack),
+            ("mixed/Model/Link.Tex.sbfres", b"Yaz0"),
+            ("empty/Pack/Bootup.pack", b""),
+            ("atmosphere/load/wiiu/content/Pack/Bootup.pack", wiiu_pack),
+            ("mlc01/switch/romfs/Pack/Bootup.pack", switch_pack),
+            ("00050000/101C9400/content/Pack/Bootup.pack", b""),
+        ] {
+            dir.write(file, data);
+        }
+        for (root, platform) in &[
+            ("wiiu", Some(Platform::WiiU)),
+            ("wiiu/content", Some(Platform::WiiU)),
+            ("switch", Some(Platform::Switch)),
+            ("switch/romfs", Some(Platform::Switch)),
+            ("bare", Some(Platform::Switch)),
+            ("textures", Some(Platform::WiiU)),
+            ("mixed", None),
+            ("empty", None),
+            ("missing", None),
+            ("atmosphere/load/wiiu/content", Some(Platform::WiiU)),
+            ("mlc01/switch/romfs", Some(Platform::Switch)),
+            ("00050000/101C9400/content", Some(Platform::WiiU)),
+        ] {
+            assert_eq!(
+                guess_platform_from_dump(dir.join(root)),
+                *platform,
+                "{}",
+                root
+            );
+        }
+    }
+
+    #[test]
+    fn platform_hints() {
+        for (path, platform) in &[
+            (
+                "sdmc:/atmosphere/contents/01007EF00011E000/romfs",
+                Some(Platform::Switch),
+            ),
+            (
+                "My Mod/atmosphere/titles/01007EF00011F001/romfs/Pack/AocMainField.pack",
+                Some(Platform::Switch),
+            ),
+            (
+                "yuzu/load/01007EF00011E000/My Mod/romfs/Pack/Bootup.pack",
+                Some(Platform::Switch),
+            ),
+            (
+                "Ryujinx\\mods\\contents\\01007ef00011e000\\My Mod",
+                Some(Platform::Switch),
+            ),
+            ("sxos/titles/01007EF00011E000", Some(Platform::Switch)),
+            ("dump/romfs/Pack/Bootup.pack", Some(Platform::Switch)),
+            (
+                "cemu/mlc01/usr/title/00050000/101C9400/content/Pack/Bootup.pack",
+                Some(Platform::WiiU),
+            ),
+            (
+                "C:\\Cemu\\graphicPacks\\BreathOfTheWild_Mod",
+                Some(Platform::WiiU),
+            ),
+            (
+                "sdcafiine/00050000101C9400/My Mod/content/Pack/Bootup.pack",
+                Some(Platform::WiiU),
+            ),
+            ("/vol/content/Pack/Bootup.pack", Some(Platform::WiiU)),
+            (
+                "/vol/aoc0005000c101c9400/content/0010",
+                Some(Platform::WiiU),
+            ),
+            (
+                "dump/0005000E101C9400/content/Pack/Bootup.pack",
+                Some(Platform::WiiU),
+            ),
+            (
+                "sd:/wiiu/games/The Legend of Zelda Breath of the Wild [ALZE01]",
+                Some(Platform::WiiU),
+            ),
+            ("My Mod/content/Pack/Bootup.pack", None),
+            ("My Mod/aoc/0010/Pack/AocMainField.pack", None),
+            ("Pack/Bootup.pack", None),
+        ] {
+            assert_eq!(path_platform_hint(path), *platform, "{}", path);
+        }
+    }
+
+    #[test]
+    fn aoc_casing() {
+        for path in &[
+            "AOC/0010/Pack/AocMainField.pack",
+            "aoc/0010/Pack/AocMainField.pack",
+            "Aoc/0010/Pack/AocMainField.pack",
+            "aOc\\0011\\Pack\\AocMainField.pack",
+            "My Mod/AOC/Pack/AocMainField.pack",
+            "graphicPacks/BreathOfTheWild_Mod/AoC/0010/Pack/AocMainField.pack",
+            "mlc01/usr/title/0005000C/101C9400/Content/0010/Pack/AocMainField.pack",
+        ] {
+            assert_eq!(
+                get_canon_name(path).unwrap(),
+                "Aoc/0010/Pack/AocMainField.pack",
+                "{}",
+                path
+            );
+        }
+        for path in &[
+            "AOC/0010/Pack/AocMainField.pack",
+            "aoc/0010/Pack/AocMainField.pack",
+        ] {
+            assert_eq!(
+                canonicalize(path).unwrap(),
+                "Aoc/0010/Pack/AocMainField.pack"
+            );
+        }
+        assert!(matches!(
+            try_get_canon_name(
+                "graphicPacks/BreathOfTheWild_Mod/content/AOC/0010/Pack/AocMainField.pack"
+            ),
+            Err(CanonError::NotUnderContentRoot { .. })
+        ));
+        for inner in &[
+            "Pack/AocMainField.pack",
+            "AOC/0010/Pack/AocMainField.pack",
+            "aoc/0010/Pack/AocMainField.pack",
+        ] {
+            assert_eq!(
+                get_nested_file_canon("Aoc/0010/Pack/RemainsWind.pack", inner),
+                "Aoc/0010/Pack/AocMainField.pack"
+            );
+        }
+    }
+
+    #[test]
+    fn directory_paths() {
+        for path in &[
+            "content/Actor/Pack/",
+            "content\\Actor\\Pack\\",
+            "aoc/0010/Map/MainField/",
+            "aoc\\0010\\Map\\MainField\\",
+            "content/Pack/Bootup.pack/",
+        ] {
+            assert_eq!(
+                try_get_canon_name(path),
+                Err(CanonError::IsDirectory {
+                    path: path.to_string()
+                })
+            );
+            assert_eq!(get_canon_name_cow(*path), None);
+            assert_eq!(canonicalize(path), None);
+        }
+        assert_eq!(
+            get_canon_name_from_root(
+                Path::new("content"),
+                Path::new("content/Actor/Pack/"),
+                RootKind::Base
+            ),
+            None
+        );
+        let as_file = Canonicalizer::new().treat_as_file(true);
+        assert_eq!(
+            as_file.canonicalize("content/Actor/Pack/").unwrap(),
+            "Actor/Pack"
+        );
+        assert_eq!(
+            as_file
+                .canonicalize("aoc\\0010\\Pack\\AocMainField.pack\\")
+                .unwrap(),
+            "Aoc/0010/Pack/AocMainField.pack"
+        );
+        assert!(as_file.canonicalize("content/").is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn segment_canon_names() {
+        for &platform in &[Platform::WiiU, Platform::Switch] {
+            for canon in hashes::get_hash_table(platform).keys().step_by(3) {
+                let physical = canon_to_physical(canon, platform);
+                let physical = physical.to_str().unwrap();
+                assert_eq!(
+                    canon_from_segments(physical.split('/')),
+                    get_canon_name(physical),
+                    "{}",
+                    physical
+                );
+            }
+        }
+        for path in &[
+            "graphicPacks/BreathOfTheWild_Mod/aoc/0010/Pack/AocMainField.pack",
+            "content/Actor/../Pack/Bootup.pack",
+            "content/Pack/",
+            "Hellow/Sweetie.tardis",
+        ] {
+            assert_eq!(
+                canon_from_segments(path.split('/')),
+                get_canon_name(path),
+                "{}",
+                path
+            );
+        }
+        assert_eq!(canon_from_segments(["content", "Pack\\Bootup.pack"]), None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn aoc_variants() {
+        for &platform in &[Platform::WiiU, Platform::Switch] {
+            let table = hashes::StockHashTable::new(platform);
+            for canon in &[
+                "Map/MainField/A-1/A-1_Static.mubin",
+                "Pack/RemainsWind.pack",
+            ] {
+                let dlc = with_aoc_prefix(canon);
+                assert!(!table.is_file_new(&dlc), "{}", dlc);
+                assert_eq!(strip_aoc_prefix(&dlc), Some(*canon));
+                assert_eq!(sibling_variants(&dlc).collect::<Vec<_>>(), [*canon]);
+                assert_eq!(sibling_variants(canon).collect::<Vec<_>>(), [dlc]);
+            }
+            let dlc_only = "Aoc/0010/Pack/AocMainField.pack";
+            for sibling in sibling_variants(dlc_only) {
+                assert_eq!(sibling, "Pack/AocMainField.pack");
+                assert!(table.is_file_new(&sibling));
+            }
+        }
+        assert_eq!(
+            with_aoc_prefix("Aoc/0010/Pack/AocMainField.pack"),
+            "Aoc/0010/Pack/AocMainField.pack"
+        );
+        assert_eq!(
+            with_aoc_prefix("/bool_data_0.bgdata"),
+            "/bool_data_0.bgdata"
+        );
+        assert_eq!(sibling_variants("/bool_data_0.bgdata").count(), 0);
+        assert_eq!(strip_aoc_prefix("Aoc/0010"), None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn suggested_roots() {
+        for &platform in &[Platform::WiiU, Platform::Switch] {
+            for (canon, kind, reason) in &[
+                (
+                    "Map/MainField/A-1/A-1_Static.mubin",
+                    RootKind::Base,
+                    RootReason::Stock,
+                ),
+                (
+                    "Aoc/0010/Map/MainField/A-1/A-1_Static.mubin",
+                    RootKind::Dlc,
+                    RootReason::Prefix,
+                ),
+                (
+                    "Aoc/0010/Actor/Pack/MyNewActor.bactorpack",
+                    RootKind::Dlc,
+                    RootReason::Prefix,
+                ),
+                (
+                    "Map/AocField/A-1/A-1.00_Clustering.blwp",
+                    RootKind::Dlc,
+                    RootReason::Stock,
+                ),
+                (
+                    "Map/AocField/Z-9/Z-9_Dynamic.mubin",
+                    RootKind::Dlc,
+                    RootReason::Heuristic,
+                ),
+                (
+                    "Actor/Pack/MyNewActor.bactorpack",
+                    RootKind::Base,
+                    RootReason::Heuristic,
+                ),
+                ("MyMod/Readme.txt", RootKind::Base, RootReason::Heuristic),
+            ] {
+                assert_eq!(
+                    suggest_root_for_canon(canon, platform),
+                    RootSuggestion {
+                        kind: *kind,
+                        reason: *reason
+                    },
+                    "{}",
+                    canon
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn texture_names() {
+        for (canon, switch, wiiu) in &[
+            (
+                "Model/Enemy_Dragon.Tex1.bfres",
+                Some("Model/Enemy_Dragon.Tex.bfres"),
+                Some("Model/Enemy_Dragon.Tex1.bfres"),
+            ),
+            (
+                "Model/Enemy_Dragon.Tex2.bfres",
+                None,
+                Some("Model/Enemy_Dragon.Tex2.bfres"),
+            ),
+            (
+                "Model/Enemy_Dragon.Tex1.1.bfres",
+                None,
+                Some("Model/Enemy_Dragon.Tex1.1.bfres"),
+            ),
+            (
+                "Aoc/0010/Model/DgnMrgPrt_Dungeon136.Tex.sbfres",
+                Some("Aoc/0010/Model/DgnMrgPrt_Dungeon136.Tex.sbfres"),
+                Some("Aoc/0010/Model/DgnMrgPrt_Dungeon136.Tex1.sbfres"),
+            ),
+            (
+                "Model/Enemy_Dragon.sbfres",
+                Some("Model/Enemy_Dragon.sbfres"),
+                Some("Model/Enemy_Dragon.sbfres"),
+            ),
+            (
+                "Model/Texture.bfres",
+                Some("Model/Texture.bfres"),
+                Some("Model/Texture.bfres"),
+            ),
+        ] {
+            assert_eq!(
+                texture_name_for_platform(canon, Platform::Switch).as_deref(),
+                *switch
+            );
+            assert_eq!(
+                texture_name_for_platform(canon, Platform::WiiU).as_deref(),
+                *wiiu
+            );
+            assert_eq!(is_texture_pack_name(canon), canon.contains(".Tex"));
+        }
+        let switch = hashes::get_hash_table(Platform::Switch);
+        let wiiu = hashes::get_hash_table(Platform::WiiU);
+        let (mut total, mut found) = (0, 0);
+        for canon in wiiu.keys().filter(|canon| canon.ends_with(".Tex1.bfres")) {
+            let name = texture_name_for_platform(canon, Platform::Switch).unwrap();
+            total += 1;
+            if switch.contains_key(name.as_str()) {
+                found += 1;
+                let back = texture_name_for_platform(&name, Platform::WiiU).unwrap();
+                assert_eq!(back, *canon);
+            }
+        }
+        assert!(found * 100 > total * 99, "{} of {}", found, total);
+    }
+
+    #[test]
+    fn bare_aoc() {
+        for path in &[
+            "aoc/Map/MainField/A-1/A-1_Dynamic.smubin",
+            "aoc/0010/Map/MainField/A-1/A-1_Dynamic.smubin",
+            "My Mod\\aoc\\Map\\MainField\\A-1\\A-1_Dynamic.smubin",
+            "graphicPacks/BreathOfTheWild_Mod/aoc/Map/MainField/A-1/A-1_Dynamic.smubin",
+        ] {
+            assert_eq!(
+                get_canon_name(path).unwrap(),
+                "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin"
+            );
+        }
+        assert_eq!(
+            get_canon_name("content/Pack/Bootup.pack").unwrap(),
+            "Pack/Bootup.pack"
+        );
+        assert_eq!(
+            find_content_root("My Mod/aoc/Pack/AocMainField.pack"),
+            Some(ContentRoot {
+                path: Path::new("My Mod/aoc").to_path_buf(),
+                kind: RootKind::Dlc
+            })
+        );
+        assert_eq!(
+            find_content_root("My Mod/aoc/0010/Pack/AocMainField.pack")
+                .unwrap()
+                .path,
+            Path::new("My Mod/aoc/0010")
+        );
+    }
+
+    #[test]
+    fn aoc_indices() {
+        for index in &["0010", "0011", "0012"] {
+            for root in &[
+                "aoc",
+                "graphicPacks/BreathOfTheWild_Mod/aoc",
+                "mlc01/usr/title/0005000C/101C9400/content",
+            ] {
+                assert_eq!(
+                    get_canon_name(format!(
+                        "{}/{}/Map/MainField/A-1/A-1_Dynamic.smubin",
+                        root, index
+                    ))
+                    .unwrap(),
+                    "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin"
+                );
+            }
+            assert_eq!(
+                find_content_root(format!("aoc/{}/Pack/AocMainField.pack", index)),
+                Some(ContentRoot {
+                    path: Path::new("aoc").join(index),
+                    kind: RootKind::Dlc
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn non_romfs_paths() {
+        for (path, folder) in &[
+            (
+                "atmosphere/contents/01007EF00011E000/exefs/main.npdm",
+                "exefs",
+            ),
+            (
+                "sdmc:/atmosphere/titles/01007EF00011E000/cheats/4B2B1C0A.txt",
+                "cheats",
+            ),
+            ("01007EF00011E000/legalinfo/legalinfo.xml", "legalinfo"),
+            ("load/01007EF00011E000/60FPS/exefs/subsdk9", "exefs"),
+            (
+                "mods/contents/01007EF00011E000/Cheats/cheats/4B2B1C0A.txt",
+                "cheats",
+            ),
+            (
+                "atmosphere/exefs_patches/BotwFPS/4B2B1C0A.ips",
+                "exefs_patches",
+            ),
+            (
+                "atmosphere/contents/01007EF00011E000/exefs/content/Pack/Bootup.pack",
+                "exefs",
+            ),
+        ] {
+            assert_eq!(
+                try_get_canon_name(path),
+                Err(CanonError::NotRomfs {
+                    path: path.to_string(),
+                    folder
+                })
+            );
+        }
+        assert_eq!(
+            get_canon_name("load/01007EF00011E000/exefs/romfs/Pack/Bootup.pack").unwrap(),
+            "Pack/Bootup.pack"
+        );
+        assert_eq!(
+            get_canon_name("Mods/cheats/01007EF00011E000/romfs/Pack/Bootup.pack").unwrap(),
+            "Pack/Bootup.pack"
+        );
+    }
+
+    #[test]
+    fn switch_update_titles() {
+        for title in &["01007EF00011E001", "01007EF00011E002", "01007ef00011e001"] {
+            assert_eq!(
+                get_canon_name(format!(
+                    "atmosphere/contents/{}/romfs/Actor/ActorInfo.product.sbyml",
+                    title
+                ))
+                .unwrap(),
+                "Actor/ActorInfo.product.byml"
+            );
+            assert_eq!(
+                classify_path(format!("{}/romfs/Pack/Bootup.pack", title)),
+                Some((RootKind::Update, "Pack/Bootup.pack".into()))
+            );
+        }
+        assert_eq!(
+            get_canon_name("atmosphere/contents/01007EF00011F002/romfs/Pack/AocMainField.pack")
+                .unwrap(),
+            "Aoc/0010/Pack/AocMainField.pack"
+        );
+    }
+
+    #[test]
+    fn dot_segments() {
+        for (path, canon) in &[
+            (
+                "content/./Actor/Pack/../Pack/Enemy_Lizalfos_Senior.sbactorpack",
+                Some("Actor/Pack/Enemy_Lizalfos_Senior.bactorpack"),
+            ),
+            ("./content/Pack/Bootup.pack", Some("Pack/Bootup.pack")),
+            ("content//Pack///Bootup.pack", Some("Pack/Bootup.pack")),
+            ("content\\.\\Pack\\.\\Bootup.pack", Some("Pack/Bootup.pack")),
+            (
+                "../../mods/content/Pack/Bootup.pack",
+                Some("Pack/Bootup.pack"),
+            ),
+            (
+                "mods/Old/../content/Pack/Bootup.pack",
+                Some("Pack/Bootup.pack"),
+            ),
+            (
+                "aoc/0010/Map/../../0010/Pack/AocMainField.pack",
+                Some("Aoc/0010/Pack/AocMainField.pack"),
+            ),
+            (
+                "/home/link/../../content/Pack/Bootup.pack",
+                Some("Pack/Bootup.pack"),
+            ),
+            ("C:/../content/Pack/Bootup.pack", Some("Pack/Bootup.pack")),
+            ("content/../Pack/Bootup.pack", None),
+            ("content/Pack/../../Bootup.pack", None),
+            ("/../content/..", None),
+            ("content/Pack/..", None),
+        ] {
+            assert_eq!(get_canon_name(path).as_deref(), *canon, "{}", path);
+            assert_eq!(get_canon_name_cow(*path).as_deref(), *canon, "{}", path);
+        }
+        let pathological = format!(
+            "{}content/Pack/Bootup.pack{}",
+            "../".repeat(500),
+            "/..".repeat(500)
+        );
+        assert_eq!(get_canon_name(&pathological), None);
+        let pathological = format!(
+            "content/{}{}Pack/Bootup.pack",
+            "Actor/Pack/".repeat(250),
+            "../".repeat(500)
+        );
+        assert_eq!(get_canon_name(pathological).unwrap(), "Pack/Bootup.pack");
+    }
+
+    #[test]
+    fn bare_romfs() {
+        assert_eq!(
+            classify_path("romfs/Pack/Bootup_USen.pack"),
+            Some((RootKind::Base, "Pack/Bootup_USen.pack".into()))
+        );
+        assert_eq!(
+            get_canon_name("RomFS\\Actor\\Pack\\Enemy_Lizalfos_Senior.sbactorpack").unwrap(),
+            "Actor/Pack/Enemy_Lizalfos_Senior.bactorpack"
+        );
+        assert_eq!(
+            get_canon_name("sdmc:/romfs/Pack/Bootup.pack").unwrap(),
+            "Pack/Bootup.pack"
+        );
+        assert_eq!(
+            classify_path("01007EF00011F001/romfs/Pack/AocMainField.pack"),
+            Some((RootKind::Dlc, "Aoc/0010/Pack/AocMainField.pack".into()))
+        );
+        assert_eq!(
+            get_canon_name("romfs/content/Pack/Bootup.pack").unwrap(),
+            "Pack/Bootup.pack"
+        );
+        assert_eq!(get_canon_name("Dumps/romfs/Pack/Bootup.pack"), None);
+    }
+
+    #[test]
+    fn rstb_names() {
+        for (canon, rstb) in &[
+            (
+                "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin",
+                Some("Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin"),
+            ),
+            (
+                "Aoc/0010/Map/AocField/G-2/G-2_Dynamic.mubin",
+                Some("Aoc/0010/Map/AocField/G-2/G-2_Dynamic.mubin"),
+            ),
+            (
+                "Aoc/0010/Actor/Pack/DgnObj_IbutsuWaterHead_A_01.bactorpack",
+                Some("Actor/Pack/DgnObj_IbutsuWaterHead_A_01.bactorpack"),
+            ),
+            (
+                "Actor/Pack/Enemy_Lizalfos_Senior.bactorpack",
+                Some("Actor/Pack/Enemy_Lizalfos_Senior.bactorpack"),
+            ),
+            (
+                "Map/MainField/A-1/A-1_Static.mubin",
+                Some("Map/MainField/A-1/A-1_Static.mubin"),
+            ),
+            ("Pack/Bootup.pack", None),
+            ("Aoc/0010/Pack/AocMainField.pack", None),
+            ("/bool_data_0.bgdata", None),
+            (
+                "Aoc/0010/Voice/EUfr/Stream_Demo660_0/Demo660_0_Text020.bfstm",
+                None,
+            ),
+        ] {
+            let name = canon_to_rstb_name(canon);
+            assert_eq!(name.as_deref(), *rstb);
+            if let Some(name) = name {
+                let kind = if canon.starts_with("Aoc/") {
+                    RootKind::Dlc
+                } else {
+                    RootKind::Base
+                };
+                assert_eq!(rstb_name_to_canon(&name, kind).as_deref(), Some(*canon));
+            }
+        }
+        assert_eq!(
+            rstb_name_to_canon(
+                "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin",
+                RootKind::Base
+            ),
+            None
+        );
+        assert_eq!(
+            rstb_name_to_canon("Aoc/0010/Actor/Pack/Npc_A.bactorpack", RootKind::Dlc),
+            None
+        );
+    }
+
+    #[test]
+    fn messy_sarc_names() {
+        for (name, canon) in &[
+            ("GameData/gamedata.ssarc", "GameData/gamedata.sarc"),
+            ("/bool_data_0.bgdata", "/bool_data_0.bgdata"),
+            (
+                "//revival_bool_data_0.bgdata",
+                "/revival_bool_data_0.bgdata",
+            ),
+            ("./saveformat_0.bgsvdata", "saveformat_0.bgsvdata"),
+            (
+                "./Event/EventInfo.product.sbyml",
+                "Event/EventInfo.product.byml",
+            ),
+            (
+                "/Actor/ActorInfo.product.sbyml",
+                "Actor/ActorInfo.product.byml",
+            ),
+            (
+                "Terrain/System//tera_resource.Cafe_Cafe_GX2.release.ssarc",
+                "Terrain/System/tera_resource.Cafe_Cafe_GX2.release.sarc",
+            ),
+            ("././Layout\\Common.sblarc", "Layout/Common.blarc"),
+            (
+                "Sound/Settings/Fx/EnvFx/MainField.fxparam",
+                "Sound/Settings/Fx/EnvFx/MainField.fxparam",
+            ),
+            ("Map//MainField/Static.smubin", "Map/MainField/Static.mubin"),
+            (
+                "/Physics/TeraMeshRigidBody/MainField/9-8.shktmrb",
+                "Physics/TeraMeshRigidBody/MainField/9-8.hktmrb",
+            ),
+            (
+                "Actor/Pack//GameROMPlayer.sbactorpack",
+                "Actor/Pack/GameROMPlayer.bactorpack",
+            ),
+            (
+                "Dummy.settings/Dummy.settings",
+                "Dummy.settings/Dummy.settings",
+            ),
+        ] {
+            assert_eq!(get_canon_name_without_root(name), *canon);
+        }
+    }
+
+    #[test]
+    fn nested_canon_names() {
+        let nested =
+            get_nested_canon_name("Pack/Bootup.pack//Actor/ActorInfo.product.sbyml").unwrap();
+        assert_eq!(nested.parent, "Pack/Bootup.pack");
+        assert_eq!(nested.inner, ["Actor/ActorInfo.product.byml"]);
+        assert_eq!(
+            nested.to_string(),
+            "Pack/Bootup.pack//Actor/ActorInfo.product.byml"
+        );
+        let nested =
+            get_nested_canon_name("content\\Pack\\TitleBG.pack//Actor/Pack/Dm_Npc_RevivalFairy.sbactorpack//Actor/Physics/Dm_Npc_RevivalFairy.bphysics").unwrap();
+        assert_eq!(nested.parent, "Pack/TitleBG.pack");
+        assert_eq!(
+            nested.inner,
+            [
+                "Actor/Pack/Dm_Npc_RevivalFairy.bactorpack",
+                "Actor/Physics/Dm_Npc_RevivalFairy.bphysics"
+            ]
+        );
+        let nested = get_nested_canon_name(
+            "Aoc/0010/Pack/AocMainField.pack//Map/MainField/A-1/A-1_Static.smubin",
         )
+        .unwrap();
+        assert_eq!(nested.parent, "Aoc/0010/Pack/AocMainField.pack");
+        assert_eq!(
+            nested.inner,
+            ["Aoc/0010/Map/MainField/A-1/A-1_Static.mubin"]
+        );
+        let nested = get_nested_canon_name("content/Pack/Bootup.pack").unwrap();
+        assert_eq!(nested.parent, "Pack/Bootup.pack");
+        assert!(nested.inner.is_empty());
+        assert_eq!(get_nested_canon_name("content/Pack/Bootup.pack//"), None);
+        assert_eq!(
+            get_nested_canon_name("//Actor/Pack/Npc_A.sbactorpack"),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "wiiu-data", feature = "switch-data"))]
+    fn regex_compatibility() {
+        let mut paths: Vec<String> = vec![
+            "content".into(),
+            "content/".into(),
+            "aoc/".into(),
+            "aoc/0010".into(),
+            "aoc/0010/".into(),
+            "graphicPacks/Mod".into(),
+            "graphicPacks//content/Pack/Bootup.pack".into(),
+            "graphicPacks/Mod/Pack/Bootup.pack".into(),
+            "sdcafiine/0005000E/101c9300/Mod/aoc/0010/Pack/AocMainField.pack".into(),
+            "mlc01/usr/title/0005000c/101C9500/content/Pack/AocMainField.pack".into(),
+            "mlc01/usr/title/0005000d/101C9500/content/Pack/Bootup.pack".into(),
+            "mods/contents/01007EF00011E002/Mod/romfs/Pack/Bootup.pack".into(),
+            "load/01007EF00011E003/Mod/romfs/Pack/Bootup.pack".into(),
+            "load/01007EF00011F000//romfs/Pack/Bootup.pack".into(),
+            "atmosphere/titles/01007EF00011E001/romfs/Pack/Bootup.pack".into(),
+            "atmosphere/contents/romfs/Pack/Bootup.pack".into(),
+            "01007ef00011f002/romfs/Pack/AocMainField.pack".into(),
+            "Content/Actor/Pack/Npc_A.sbactorpack".into(),
+            "AOC/0010/Pack/AocMainField.pack".into(),
+            "content/Actor/aocfix/Npc_A.sbactorpack".into(),
+            "mods/ゼルダ/content/Actor/Pack/Npc_A.sbactorpack".into(),
+        ];
+        for table in [Platform::WiiU, Platform::Switch]
+            .iter()
+            .copied()
+            .map(hashes::get_hash_table)
+        {
+            let mut names: Vec<_> = table.keys().collect();
+            names.sort_unstable();
+            for (i, name) in names.into_iter().step_by(40).enumerate() {
+                let root = [
+                    "content",
+                    "aoc/0010",
+                    "aoc",
+                    "mods/content/My Mod/content",
+                    "graphicPacks/BreathOfTheWild_Mod/content",
+                    "sdcafiine/00050000101C9400/Mod/aoc/0010",
+                    "mlc01/usr/title/0005000E/101C9400/content",
+                    "mlc01/usr/title/0005000C/101C9400/content/0010",
+                    "atmosphere/contents/01007EF00011E000/romfs",
+                    "atmosphere/titles/01007EF00011F001/romfs",
+                    "load/01007EF00011E000/Mod/romfs",
+                    "mods/contents/01007ef00011f002/Mod/romfs",
+                    "sdmc:/atmosphere/contents/01007EF00011E000/romfs",
+                    "C:/Users/Link/Mods",
+                ][i % 14];
+                paths.push(format!("{}/{}", root, name));
+            }
+        }
+        for path in &paths {
+            assert_eq!(
+                find_canon(path).map(|parts| (parts.kind, parts.build())),
+                regex_find_canon(path).map(|parts| (parts.kind, parts.build())),
+                "{}",
+                path
+            );
+        }
     }
 }
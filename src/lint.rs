@@ -0,0 +1,343 @@
+use crate::hashes::{has_stock_hashes, Platform, StockHashTable};
+use crate::{
+    canonicalize_dir, dir_root_kind, find_content_root, is_aoc_index, path_platform_hint,
+    sibling_variants, titles, RootKind,
+};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The kind of packaging mistake a [`LintIssue`] is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    /// A content root inside another one, like `content/content`, which the game never reads
+    NestedRoot,
+    /// A file with a canonical resource path which is not stock where it is, but would be under
+    /// the other content root, like a DLC map section under `content`
+    WrongRoot,
+    /// A Wii U title ID folder used in a Switch mod layout
+    WrongPlatformTitle,
+    /// A Switch title ID folder with game files directly in it instead of in `romfs`
+    MissingRomfs,
+    /// A top level folder with no content root anywhere in it
+    UnrecognizedFolder,
+    /// An empty folder whose name looks like a misspelled root folder, like `contnet`
+    RootTypo,
+}
+
+/// A packaging mistake found by [`lint_mod_root`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    /// What kind of mistake it is
+    pub kind: LintKind,
+    /// The file or folder with the mistake
+    pub path: PathBuf,
+    /// A human readable suggestion for fixing it, like `move to aoc/0010`
+    pub suggestion: String,
+}
+
+/// The names of the root folders which a misspelled folder name is compared with
+const ROOT_FOLDERS: &[&str] = &["content", "aoc", "romfs"];
+
+/// Checks a mod folder for common packaging mistakes, like a `content` folder zipped inside
+/// another one, DLC files under base game content, a Wii U title ID in a Switch mod, or a
+/// Switch title folder without its `romfs` folder. Whether files are in the right place is
+/// checked against the stock file lists, for the platform the mod's layout is for if it can be
+/// told. Symlinks are not followed, and folders which cannot be read are skipped. Example:
+///
+/// ```no_run
+/// use botw_utils::lint_mod_root;
+/// for issue in lint_mod_root("mods/My Mod".as_ref()) {
+///     println!("{}: {}", issue.path.display(), issue.suggestion);
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `root` - The top folder of the mod
+///
+/// # Returns
+///
+/// Returns a Vec of the issues found, sorted by path.
+pub fn lint_mod_root(root: &Path) -> Vec<LintIssue> {
+    let tables: Vec<_> = [Platform::WiiU, Platform::Switch]
+        .iter()
+        .copied()
+        .filter(|platform| has_stock_hashes(*platform))
+        .map(|platform| (platform, StockHashTable::get(platform)))
+        .collect();
+    let mut linter = Linter {
+        issues: Vec::new(),
+        resource_folders: tables
+            .iter()
+            .flat_map(|(_, table)| table.get_stock_files())
+            .filter(|canon| !canon.starts_with('/'))
+            .filter_map(|canon| Some(canon.split_once('/')?.0))
+            .filter(|folder| *folder != "Aoc")
+            .collect(),
+    };
+    if find_content_root(root).is_some() {
+        linter.visit(root, Some(root));
+    } else {
+        linter.visit(root, None);
+        for dir in subfolders(root) {
+            if !linter.has_root(&dir) && !linter.issues.iter().any(|i| i.path.starts_with(&dir)) {
+                let suggestion = match similar_root(&dir) {
+                    Some(name) => format!("rename to {}", name),
+                    None => "move its files under a content root like content or aoc/0010, or \
+                             remove it"
+                        .to_owned(),
+                };
+                linter.issue(LintKind::UnrecognizedFolder, dir, suggestion);
+            }
+        }
+    }
+    let full_root = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let root_names = crate::root_folder_names(&full_root);
+    for (path, canon) in canonicalize_dir(root).skip_errors(true).flatten() {
+        let platform =
+            path_platform_hint(root_names.join(path.strip_prefix(root).unwrap_or(&path)));
+        let stock = |name: &str| {
+            tables.iter().any(|(table_platform, table)| {
+                (platform.is_none() || platform.as_ref() == Some(table_platform))
+                    && table.contains(name)
+            })
+        };
+        if stock(&canon) {
+            continue;
+        }
+        if let Some(sibling) = sibling_variants(&canon).find(|sibling| stock(sibling)) {
+            let dlc = sibling.starts_with("Aoc/0010/");
+            let folder = match (platform, dlc) {
+                (Some(Platform::Switch), true) => "01007EF00011F001/romfs",
+                (Some(Platform::Switch), false) => "01007EF00011E000/romfs",
+                (_, true) => "aoc/0010",
+                (_, false) => "content",
+            };
+            linter.issue(LintKind::WrongRoot, path, format!("move to {}", folder));
+        }
+    }
+    linter.issues.sort_by(|a, b| a.path.cmp(&b.path));
+    linter.issues
+}
+
+/// The state of a lint over a mod folder's layout
+struct Linter {
+    issues: Vec<LintIssue>,
+    resource_folders: HashSet<&'static str>,
+}
+
+impl Linter {
+    fn issue(&mut self, kind: LintKind, path: PathBuf, suggestion: String) {
+        self.issues.push(LintIssue {
+            kind,
+            path,
+            suggestion,
+        });
+    }
+
+    /// Whether a folder is a content root or has one anywhere inside it
+    fn has_root(&self, dir: &Path) -> bool {
+        dir_root_kind(dir).is_some() || subfolders(dir).iter().any(|sub| self.has_root(sub))
+    }
+
+    /// Checks the layout of a folder and everything in it, given the content root it is in
+    fn visit(&mut self, dir: &Path, root: Option<&Path>) {
+        let subs = subfolders(dir);
+        let name = dir.file_name().and_then(|name| name.to_str()).unwrap_or("");
+        let mut root = root.map(Path::to_path_buf);
+        if let Some(kind) = dir_root_kind(dir) {
+            match &root {
+                // The DLC index folder in `aoc/0010` belongs to the `aoc` root it is in
+                Some(outer) if is_aoc_index(name) && dir.parent() == Some(outer) => {}
+                Some(outer) if outer != dir => {
+                    let suggestion = format!("move its files up into {}", outer.display());
+                    self.issue(LintKind::NestedRoot, dir.to_path_buf(), suggestion);
+                }
+                _ => {}
+            }
+            if kind == RootKind::Dlc || root.is_none() {
+                root = Some(dir.to_path_buf());
+            }
+        }
+        if let Some(kind) = titles::wiiu_title_kind(name) {
+            let parent = dir.parent().and_then(Path::file_name);
+            if matches!(parent, Some(parent) if ["contents", "titles", "load"]
+                .iter()
+                .any(|folder| parent.eq_ignore_ascii_case(folder)))
+            {
+                let title = match kind {
+                    RootKind::Dlc => "01007EF00011F001",
+                    _ => "01007EF00011E000",
+                };
+                self.issue(
+                    LintKind::WrongPlatformTitle,
+                    dir.to_path_buf(),
+                    format!("rename to {}", title),
+                );
+            }
+        }
+        if titles::switch_folder_kind(name).is_some() {
+            let has_romfs = subs
+                .iter()
+                .any(|sub| matches!(folder_name(sub), Some(n) if n.eq_ignore_ascii_case("romfs")));
+            if !has_romfs
+                && subs.iter().any(
+                    |sub| matches!(folder_name(sub), Some(n) if self.resource_folders.contains(n)),
+                )
+            {
+                self.issue(
+                    LintKind::MissingRomfs,
+                    dir.to_path_buf(),
+                    "move its files into a romfs folder inside it".to_owned(),
+                );
+            }
+        }
+        if subs.is_empty() && is_empty(dir) {
+            if let Some(similar) = similar_root(dir) {
+                self.issue(
+                    LintKind::RootTypo,
+                    dir.to_path_buf(),
+                    format!("rename to {}", similar),
+                );
+            }
+        }
+        for sub in subs {
+            self.visit(&sub, root.as_deref());
+        }
+    }
+}
+
+/// Lists the folders directly inside a folder, not following symlinks, in name order
+fn subfolders(dir: &Path) -> Vec<PathBuf> {
+    let mut subs: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| matches!(entry.file_type(), Ok(t) if t.is_dir()))
+        .map(|entry| entry.path())
+        .collect();
+    subs.sort();
+    subs
+}
+
+fn folder_name(dir: &Path) -> Option<&str> {
+    dir.file_name()?.to_str()
+}
+
+fn is_empty(dir: &Path) -> bool {
+    match fs::read_dir(dir) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(_) => false,
+    }
+}
+
+/// Finds the root folder name a folder name looks like a misspelling of, if any
+fn similar_root(dir: &Path) -> Option<&'static str> {
+    let name = dir.file_name()?.to_str()?.to_ascii_lowercase();
+    ROOT_FOLDERS.iter().copied().find(|root| {
+        let distance = edit_distance(&name, root);
+        distance > 0 && distance <= root.len() / 3
+    })
+}
+
+/// Counts the single character insertions, deletions, and substitutions between two names
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (above + 1)
+                .min(row[j] + 1)
+                .min(diagonal + usize::from(ca != *cb));
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(all(test, any(feature = "wiiu-data", feature = "switch-data")))]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn mod_layout_issues() {
+        let dir = TempDir::new("lint");
+        for file in &[
+            "rules.txt",
+            "content/Pack/Bootup.pack",
+            "content/Map/MainField/A-1/A-1_Static.smubin",
+            "content/Map/AocField/A-1/A-1.00_Clustering.sblwp",
+            "content/content/Pack/TitleBG.pack",
+            "aoc/0010/Map/MainField/A-1/A-1_Static.smubin",
+            "atmosphere/contents/00050000101C9400/romfs/Pack/Bootup.pack",
+            "atmosphere/contents/01007EF00011E000/Actor/Pack/Enemy_Lizal_Senior.sbactorpack",
+            "Textures/Link.png",
+        ] {
+            dir.write(file, b"");
+        }
+        fs::create_dir_all(dir.join("contnet")).unwrap();
+        fs::create_dir_all(dir.join("content/Empty")).unwrap();
+        let issues: Vec<(LintKind, PathBuf, String)> = lint_mod_root(&dir)
+            .into_iter()
+            .map(|issue| {
+                let path = issue.path.strip_prefix(&dir).unwrap().to_path_buf();
+                (issue.kind, path, issue.suggestion)
+            })
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (
+                    LintKind::UnrecognizedFolder,
+                    PathBuf::from("Textures"),
+                    "move its files under a content root like content or aoc/0010, or remove it"
+                        .to_owned()
+                ),
+                (
+                    LintKind::WrongPlatformTitle,
+                    PathBuf::from("atmosphere/contents/00050000101C9400"),
+                    "rename to 01007EF00011E000".to_owned()
+                ),
+                (
+                    LintKind::MissingRomfs,
+                    PathBuf::from("atmosphere/contents/01007EF00011E000"),
+                    "move its files into a romfs folder inside it".to_owned()
+                ),
+                (
+                    LintKind::WrongRoot,
+                    PathBuf::from("content/Map/AocField/A-1/A-1.00_Clustering.sblwp"),
+                    "move to aoc/0010".to_owned()
+                ),
+                (
+                    LintKind::NestedRoot,
+                    PathBuf::from("content/content"),
+                    format!("move its files up into {}", dir.join("content").display())
+                ),
+                (
+                    LintKind::RootTypo,
+                    PathBuf::from("contnet"),
+                    "rename to content".to_owned()
+                ),
+            ]
+        );
+        assert!(lint_mod_root(&dir.join("aoc")).is_empty());
+        let nested = dir.join("atmosphere/MyMod");
+        let file = dir.write(
+            "atmosphere/MyMod/content/Map/AocField/A-1/A-1.00_Clustering.sblwp",
+            b"",
+        );
+        let issues: Vec<(LintKind, PathBuf, String)> = lint_mod_root(&nested)
+            .into_iter()
+            .map(|issue| (issue.kind, issue.path, issue.suggestion))
+            .collect();
+        assert_eq!(
+            issues,
+            [(LintKind::WrongRoot, file, "move to aoc/0010".to_owned())]
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use crate::hashes::{has_stock_hashes, Platform, StockHashTable};
+use crate::{strip_aoc_prefix, AOC_PREFIX};
+use std::collections::BTreeSet;
+
+/// Groups of resources which have to be changed together, by the naming conventions that tie
+/// them to each other. A `{name}` placeholder stands for the same text everywhere in a group,
+/// like the name of a map unit, while `*` stands for any text, like the quarter of a map section.
+/// Neither matches across a `/` or `.`. Members which do not exist for a given match, like the
+/// static compound files of a shrine in the field map layout, are left out of the results, so a
+/// group can cover several layouts. DLC resources are related to resources with the same
+/// `Aoc/0010/` prefix.
+const RELATIONS: &[&[&str]] = &[
+    // Map units, both field map sections and shrines
+    &[
+        "Map/{field}/{unit}/{unit}_Static.mubin",
+        "Map/{field}/{unit}/{unit}_Dynamic.mubin",
+        "Map/{field}/{unit}/{unit}_TeraTree.blwp",
+        "Map/{field}/{unit}/{unit}_Clustering.blwp",
+        "Map/{field}/{unit}/{unit}.*_Clustering.blwp",
+        "Map/DungeonData/{field}/{unit}.bdgnenv",
+        "Physics/StaticCompound/{field}/{unit}.hksc",
+        "Physics/StaticCompound/{field}/{unit}-*.hksc",
+        "NavMesh/{field}/{unit}/{unit}.hknm2",
+    ],
+    // Actor packs and the resources named after them
+    &[
+        "Actor/Pack/{actor}.bactorpack",
+        "Actor/ActorLink/{actor}.bxml",
+        "Actor/ModelList/{actor}.bmodellist",
+        "Actor/Physics/{actor}.bphysics",
+        "Model/{actor}.bfres",
+        "Model/{actor}.Tex.bfres",
+        "Model/{actor}.Tex1.bfres",
+        "Model/{actor}.Tex2.bfres",
+        "Model/{actor}_Animation.bfres",
+    ],
+    // Terrain tiles
+    &[
+        "Terrain/{set}/{field}/{tile}.hght.stera",
+        "Terrain/{set}/{field}/{tile}.mate.stera",
+        "Terrain/{set}/{field}/{tile}.grass.extm.stera",
+        "Terrain/{set}/{field}/{tile}.water.extm.stera",
+    ],
+];
+
+/// Placeholder values bound while matching a name against a member of a relation group
+type Bindings<'a, 'b> = Vec<(&'a str, &'b str)>;
+
+/// Matches a name against a relation group member, binding its placeholders. Placeholders which
+/// are already bound have to match their bound value.
+fn match_member<'a, 'b>(member: &'a str, name: &'b str, bindings: &mut Bindings<'a, 'b>) -> bool {
+    if let Some(rest) = member.strip_prefix('{') {
+        let end = match rest.find('}') {
+            Some(end) => end,
+            None => return false,
+        };
+        let (var, rest) = (&rest[..end], &rest[end + 1..]);
+        if let Some(&(_, value)) = bindings.iter().find(|(bound, _)| *bound == var) {
+            return match name.strip_prefix(value) {
+                Some(name) => match_member(rest, name, bindings),
+                None => false,
+            };
+        }
+        match_any(name, |value, name| {
+            bindings.push((var, value));
+            if match_member(rest, name, bindings) {
+                return true;
+            }
+            bindings.pop();
+            false
+        })
+    } else if let Some(rest) = member.strip_prefix('*') {
+        match_any(name, |_, name| match_member(rest, name, bindings))
+    } else {
+        match (member.chars().next(), name.chars().next()) {
+            (None, None) => true,
+            (Some(m), Some(n)) if m == n => {
+                match_member(&member[m.len_utf8()..], &name[n.len_utf8()..], bindings)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Tries each non-empty run of text at the start of a name which a placeholder can stand for,
+/// until `matches` accepts one with the rest of the name
+fn match_any<'b>(name: &'b str, mut matches: impl FnMut(&'b str, &'b str) -> bool) -> bool {
+    let limit = name.find(['/', '.']).unwrap_or(name.len());
+    (1..=limit)
+        .filter(|&end| name.is_char_boundary(end))
+        .any(|end| matches(&name[..end], &name[end..]))
+}
+
+/// Finds the resources which usually have to be changed together with a resource, like the
+/// `_Static` map file, static compound, and clustering files of a map section's `_Dynamic` map
+/// file, so tools can warn when a mod changes one without the others. The relationships come
+/// from naming conventions, and only files which exist in the stock game on either platform are
+/// given. Example:
+///
+#[cfg_attr(any(feature = "wiiu-data", feature = "switch-data"), doc = "```")]
+#[cfg_attr(
+    not(any(feature = "wiiu-data", feature = "switch-data")),
+    doc = "```ignore"
+)]
+/// use botw_utils::related_files;
+/// let related = related_files("Map/MainField/A-1/A-1_Dynamic.mubin");
+/// assert!(related.contains(&"Map/MainField/A-1/A-1_Static.mubin".to_owned()));
+/// assert!(related.contains(&"Physics/StaticCompound/MainField/A-1-0.hksc".to_owned()));
+/// ```
+///
+/// # Arguments
+///
+/// * `canon` - The canonical resource path of the file
+///
+/// # Returns
+///
+/// Returns a Vec of the canonical resource paths of the related files, sorted, without the file
+/// itself. It is empty for files with no known relationships.
+pub fn related_files(canon: &str) -> Vec<String> {
+    let (prefix, name) = match strip_aoc_prefix(canon) {
+        Some(name) => (AOC_PREFIX, name),
+        None => ("", canon),
+    };
+    let tables: Vec<_> = [Platform::WiiU, Platform::Switch]
+        .iter()
+        .copied()
+        .filter(|platform| has_stock_hashes(*platform))
+        .map(StockHashTable::get)
+        .collect();
+    let mut related = BTreeSet::new();
+    for group in RELATIONS {
+        for member in group.iter() {
+            let mut bindings = Bindings::new();
+            if !match_member(member, name, &mut bindings) {
+                continue;
+            }
+            for other in group.iter().filter(|other| *other != member) {
+                let filled = bindings
+                    .iter()
+                    .fold(other.to_string(), |filled, (var, value)| {
+                        filled.replace(&["{", var, "}"].concat(), value)
+                    });
+                for table in &tables {
+                    if !filled.contains(['{', '*']) {
+                        let full = [prefix, &filled].concat();
+                        if table.contains(&full) {
+                            related.insert(full);
+                        }
+                        continue;
+                    }
+                    // Only the files in the folder of the member's literal start can match it
+                    let literal = &filled[..filled.find(['{', '*']).unwrap_or(filled.len())];
+                    let folder = [prefix, &literal[..literal.rfind('/').unwrap_or(0)]].concat();
+                    for key in table.files_under(&folder) {
+                        if let Some(key_name) = key.strip_prefix(prefix) {
+                            if key_name.starts_with(literal)
+                                && match_member(&filled, key_name, &mut Bindings::new())
+                            {
+                                related.insert(key.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    related.remove(canon);
+    related.into_iter().collect()
+}
+
+#[cfg(all(test, feature = "wiiu-data", feature = "switch-data"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn related_resources() {
+        assert_eq!(
+            related_files("Map/MainField/A-1/A-1_Dynamic.mubin"),
+            [
+                "Map/MainField/A-1/A-1.00_Clustering.blwp",
+                "Map/MainField/A-1/A-1.01_Clustering.blwp",
+                "Map/MainField/A-1/A-1.10_Clustering.blwp",
+                "Map/MainField/A-1/A-1.11_Clustering.blwp",
+                "Map/MainField/A-1/A-1_Static.mubin",
+                "Map/MainField/A-1/A-1_TeraTree.blwp",
+                "Physics/StaticCompound/MainField/A-1-0.hksc",
+                "Physics/StaticCompound/MainField/A-1-1.hksc",
+                "Physics/StaticCompound/MainField/A-1-2.hksc",
+                "Physics/StaticCompound/MainField/A-1-3.hksc",
+            ]
+        );
+        assert_eq!(
+            related_files("Physics/StaticCompound/CDungeon/Dungeon000.hksc"),
+            [
+                "Map/CDungeon/Dungeon000/Dungeon000_Clustering.blwp",
+                "Map/CDungeon/Dungeon000/Dungeon000_Dynamic.mubin",
+                "Map/CDungeon/Dungeon000/Dungeon000_Static.mubin",
+                "Map/CDungeon/Dungeon000/Dungeon000_TeraTree.blwp",
+                "Map/DungeonData/CDungeon/Dungeon000.bdgnenv",
+                "NavMesh/CDungeon/Dungeon000/Dungeon000.hknm2",
+            ]
+        );
+        let dlc = related_files("Aoc/0010/Map/AocField/A-1/A-1_Static.mubin");
+        assert!(dlc.contains(&"Aoc/0010/Physics/StaticCompound/AocField/A-1-3.hksc".to_owned()));
+        assert!(dlc.iter().all(|name| name.starts_with("Aoc/0010/")));
+        assert_eq!(
+            related_files("Model/Weapon_Sword_070.Tex1.bfres"),
+            [
+                "Actor/ActorLink/Weapon_Sword_070.bxml",
+                "Actor/ModelList/Weapon_Sword_070.bmodellist",
+                "Actor/Pack/Weapon_Sword_070.bactorpack",
+                "Model/Weapon_Sword_070.Tex.bfres",
+                "Model/Weapon_Sword_070.Tex2.bfres",
+                "Model/Weapon_Sword_070.bfres",
+            ]
+        );
+        assert_eq!(
+            related_files("Terrain/A/MainField/5000000000.hght.stera"),
+            [
+                "Terrain/A/MainField/5000000000.grass.extm.stera",
+                "Terrain/A/MainField/5000000000.mate.stera",
+                "Terrain/A/MainField/5000000000.water.extm.stera",
+            ]
+        );
+        assert!(related_files("Pack/Bootup.pack").is_empty());
+        assert!(related_files("Actor/Pack/My_New_Actor.bactorpack").is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use crate::RootKind;
+use alloc::vec::Vec;
+
+/// A layout of root folder which canonical resource paths are found under. In the patterns, `*`
+/// stands for a folder with any name, like a mod name, `x` for any digit of a title ID or DLC
+/// index, and `(a|b)` for either of two folder names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RootPattern {
+    /// A human readable name for the layout, like `Cemu graphic pack`
+    pub name: &'static str,
+    /// The folders which lead up to the root, like `graphicPacks/*/content`. They can be
+    /// anywhere in a path, not only at the start.
+    pub pattern: &'static str,
+    /// The kind of content under the root
+    pub kind: RootKind,
+}
+
+/// Makes a [`RootPattern`], to keep the table below short
+const fn root(name: &'static str, pattern: &'static str, kind: RootKind) -> RootPattern {
+    RootPattern {
+        name,
+        pattern,
+        kind,
+    }
+}
+
+/// Every root layout the canonicalization functions recognize
+const KNOWN_ROOTS: &[RootPattern] = &[
+    root("Wii U content", "content", RootKind::Base),
+    root("Wii U DLC", "aoc/00xx", RootKind::Dlc),
+    root(
+        "Cemu graphic pack",
+        "graphicPacks/*/content",
+        RootKind::Base,
+    ),
+    root(
+        "Cemu graphic pack DLC",
+        "graphicPacks/*/aoc/00xx",
+        RootKind::Dlc,
+    ),
+    root(
+        "SDCafiine mod",
+        "sdcafiine/0005000x101C9x00/*/content",
+        RootKind::Base,
+    ),
+    root(
+        "SDCafiine mod DLC",
+        "sdcafiine/0005000x101C9x00/*/aoc/00xx",
+        RootKind::Dlc,
+    ),
+    root("Wii U volume content", "vol/content", RootKind::Base),
+    root(
+        "Wii U volume DLC",
+        "vol/aoc0005000C101C9x00/content/00xx",
+        RootKind::Dlc,
+    ),
+    root("Loadiine game", "wiiu/games/*/content", RootKind::Base),
+    root("Loadiine game DLC", "wiiu/games/*/aoc/00xx", RootKind::Dlc),
+    root("Yuzu mod", "load/01007EF00011E000/*/romfs", RootKind::Base),
+    root(
+        "Yuzu mod DLC",
+        "load/01007EF00011F00x/*/romfs",
+        RootKind::Dlc,
+    ),
+    root(
+        "Ryujinx mod",
+        "mods/contents/01007EF00011E000/*/romfs",
+        RootKind::Base,
+    ),
+    root(
+        "Ryujinx mod DLC",
+        "mods/contents/01007EF00011F00x/*/romfs",
+        RootKind::Dlc,
+    ),
+    root(
+        "Cemu base game",
+        "mlc01/usr/title/00050000/101C9x00/content",
+        RootKind::Base,
+    ),
+    root(
+        "Cemu update",
+        "mlc01/usr/title/0005000E/101C9x00/content",
+        RootKind::Update,
+    ),
+    root(
+        "Cemu DLC",
+        "mlc01/usr/title/0005000C/101C9x00/content/00xx",
+        RootKind::Dlc,
+    ),
+    root(
+        "Wii U base game title",
+        "00050000101C9x00/content",
+        RootKind::Base,
+    ),
+    root(
+        "Wii U update title",
+        "0005000E101C9x00/content",
+        RootKind::Update,
+    ),
+    root(
+        "Wii U DLC title",
+        "0005000C101C9x00/content/00xx",
+        RootKind::Dlc,
+    ),
+    root("Switch base game", "01007EF00011E000/romfs", RootKind::Base),
+    root(
+        "Switch update",
+        "01007EF00011E(001|002|800)/romfs",
+        RootKind::Update,
+    ),
+    root("Switch DLC", "01007EF00011F00x/romfs", RootKind::Dlc),
+    root(
+        "Atmosphere mod",
+        "atmosphere/(titles|contents)/01007EF00011E000/romfs",
+        RootKind::Base,
+    ),
+    root(
+        "Atmosphere DLC mod",
+        "atmosphere/(titles|contents)/01007EF00011F00x/romfs",
+        RootKind::Dlc,
+    ),
+    root(
+        "SX OS mod",
+        "sxos/titles/01007EF00011E000/romfs",
+        RootKind::Base,
+    ),
+    root(
+        "SX OS DLC mod",
+        "sxos/titles/01007EF00011F00x/romfs",
+        RootKind::Dlc,
+    ),
+    root(
+        "ReiNX mod",
+        "reinx/titles/01007EF00011E000/romfs",
+        RootKind::Base,
+    ),
+    root(
+        "ReiNX DLC mod",
+        "reinx/titles/01007EF00011F00x/romfs",
+        RootKind::Dlc,
+    ),
+    root("Extracted romfs", "romfs", RootKind::Base),
+];
+
+/// Gets the root folder layouts which canonical resource paths are found under, the same ones
+/// listed by [`CanonError::UnrecognizedRoot`](crate::CanonError::UnrecognizedRoot). Example:
+///
+/// ```
+/// use botw_utils::{known_roots, RootKind};
+/// let hints: Vec<String> = known_roots()
+///     .iter()
+///     .filter(|root| root.kind == RootKind::Dlc)
+///     .map(|root| format!("{} ({})", root.name, root.pattern))
+///     .collect();
+/// assert!(hints.contains(&"Cemu graphic pack DLC (graphicPacks/*/aoc/00xx)".to_owned()));
+/// ```
+pub fn known_roots() -> &'static [RootPattern] {
+    KNOWN_ROOTS
+}
+
+/// Gets the patterns of every known root, as listed by [`crate::CanonError::UnrecognizedRoot`]
+pub(crate) fn root_patterns() -> Vec<&'static str> {
+    KNOWN_ROOTS.iter().map(|root| root.pattern).collect()
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    /// Fills in the wildcards of a root pattern with folder names it matches, so every entry of
+    /// the table is checked against the real matchers
+    fn example_root(pattern: &str) -> String {
+        [
+            ("*", "My Mod"),
+            ("00xx", "0010"),
+            ("0005000x", "00050000"),
+            ("101C9x00", "101C9400"),
+            ("F00x", "F001"),
+            ("(001|002|800)", "800"),
+            ("(titles|contents)", "contents"),
+        ]
+        .iter()
+        .fold(pattern.to_owned(), |path, (wildcard, name)| {
+            path.replace(wildcard, name)
+        })
+    }
+
+    #[test]
+    fn roots_match() {
+        for root in known_roots() {
+            let path = [example_root(root.pattern).as_str(), "/Pack/Bootup.pack"].concat();
+            let canon = match root.kind {
+                RootKind::Dlc => "Aoc/0010/Pack/Bootup.pack",
+                _ => "Pack/Bootup.pack",
+            };
+            assert_eq!(
+                crate::classify_path(&path),
+                Some((root.kind, canon.to_owned())),
+                "{}",
+                root.name
+            );
+        }
+    }
+}
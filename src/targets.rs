@@ -0,0 +1,196 @@
+use crate::{hashes::Platform, physical_name, strip_aoc_prefix};
+use std::path::{Path, PathBuf};
+
+/// A region of the Wii U version of BOTW, which decides its title IDs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WiiURegion {
+    /// Japan (`101C9300`)
+    Jpn,
+    /// North America (`101C9400`)
+    Usa,
+    /// Europe (`101C9500`)
+    Eur,
+}
+
+impl WiiURegion {
+    /// Gets the title ID of the base game in this region, like `00050000101C9400`
+    pub fn base_title_id(self) -> &'static str {
+        match self {
+            WiiURegion::Jpn => "00050000101C9300",
+            WiiURegion::Usa => "00050000101C9400",
+            WiiURegion::Eur => "00050000101C9500",
+        }
+    }
+}
+
+/// A place merged or converted mod files can be exported to, each with its own folder layout
+#[derive(Debug, PartialEq, Eq)]
+pub enum ModTarget {
+    /// A Cemu graphic pack, as `graphicPacks/<name>/content` and `graphicPacks/<name>/aoc/0010`
+    GraphicPack {
+        /// The folder name of the graphic pack
+        name: String,
+    },
+    /// An SDCafiine mod on a Wii U SD card, as `sdcafiine/<title ID>/<name>/content` and
+    /// `sdcafiine/<title ID>/<name>/aoc/0010`
+    Sdcafiine {
+        /// The region of the game the mod is for
+        region: WiiURegion,
+        /// The folder name of the mod
+        name: String,
+    },
+    /// An Atmosphere layout on a Switch SD card, with DLC files under the DLC's own title, as
+    /// `atmosphere/contents/01007EF00011E000/romfs` and
+    /// `atmosphere/contents/01007EF00011F001/romfs`
+    Atmosphere,
+    /// A Yuzu mod, as `load/01007EF00011E000/<name>/romfs` and
+    /// `load/01007EF00011F001/<name>/romfs`
+    Yuzu {
+        /// The folder name of the mod
+        name: String,
+    },
+    /// A raw game dump for a platform, laid out like
+    /// [`canon_to_physical`](crate::canon_to_physical)
+    Dump {
+        /// Whether to use the Wii U or Switch root folder layout
+        platform: Platform,
+    },
+}
+
+/// Convert a [canonical resource path](https://zeldamods.org/wiki/Canonical_resource_path) into
+/// the physical path the file should be exported to for a mod target, relative to the target's
+/// top folder, like an SD card's root. Like with [`canon_to_physical`](crate::canon_to_physical),
+/// the `s` is re-added to the extensions of files the game stores yaz0 compressed. Bare names
+/// like `/bool_data_0.bgdata`, which only exist inside archives, are placed directly under the
+/// content root. Example:
+///
+/// ```
+/// use botw_utils::{build_output_path, ModTarget, WiiURegion};
+/// use std::path::Path;
+/// let target = ModTarget::Sdcafiine {
+///     region: WiiURegion::Usa,
+///     name: "My Mod".to_owned(),
+/// };
+/// assert_eq!(
+///    build_output_path("Aoc/0010/Pack/AocMainField.pack", &target),
+///    Path::new("sdcafiine/00050000101C9400/My Mod/aoc/0010/Pack/AocMainField.pack")
+/// );
+/// assert_eq!(
+///    build_output_path("Actor/Pack/Enemy_Lizalfos_Senior.bactorpack", &ModTarget::Atmosphere),
+///    Path::new("atmosphere/contents/01007EF00011E000/romfs")
+///        .join("Actor/Pack/Enemy_Lizalfos_Senior.sbactorpack")
+/// );
+/// ```
+///
+/// # Arguments
+///
+/// * `canon` - The canonical resource path of the file
+/// * `target` - The mod target to lay the file out for
+///
+/// # Returns
+///
+/// Returns the physical path of the file as a PathBuf.
+pub fn build_output_path(canon: &str, target: &ModTarget) -> PathBuf {
+    let (dlc, path) = match strip_aoc_prefix(canon) {
+        Some(path) => (true, path),
+        None => (false, canon),
+    };
+    let wiiu_root = |top: PathBuf| {
+        if dlc {
+            top.join("aoc/0010")
+        } else {
+            top.join("content")
+        }
+    };
+    let switch_title = if dlc {
+        "01007EF00011F001"
+    } else {
+        "01007EF00011E000"
+    };
+    let root = match target {
+        ModTarget::GraphicPack { name } => wiiu_root(Path::new("graphicPacks").join(name)),
+        ModTarget::Sdcafiine { region, name } => wiiu_root(
+            Path::new("sdcafiine")
+                .join(region.base_title_id())
+                .join(name),
+        ),
+        ModTarget::Atmosphere => Path::new("atmosphere/contents")
+            .join(switch_title)
+            .join("romfs"),
+        ModTarget::Yuzu { name } => Path::new("load")
+            .join(switch_title)
+            .join(name)
+            .join("romfs"),
+        ModTarget::Dump {
+            platform: Platform::WiiU,
+        } => wiiu_root(PathBuf::new()),
+        ModTarget::Dump {
+            platform: Platform::Switch,
+        } => Path::new(switch_title).join("romfs"),
+    };
+    root.join(physical_name(path.trim_start_matches('/')))
+}
+
+#[cfg(all(test, feature = "wiiu-data", feature = "switch-data"))]
+mod tests {
+    use super::*;
+    use crate::{get_canon_name, hashes::get_hash_table};
+
+    #[test]
+    fn output_paths_round_trip() {
+        let targets = [
+            ModTarget::GraphicPack {
+                name: "BreathOfTheWild_MyMod".to_owned(),
+            },
+            ModTarget::Sdcafiine {
+                region: WiiURegion::Eur,
+                name: "My Mod".to_owned(),
+            },
+            ModTarget::Atmosphere,
+            ModTarget::Yuzu {
+                name: "My Mod".to_owned(),
+            },
+            ModTarget::Dump {
+                platform: Platform::WiiU,
+            },
+            ModTarget::Dump {
+                platform: Platform::Switch,
+            },
+        ];
+        for &platform in &[Platform::WiiU, Platform::Switch] {
+            for canon in get_hash_table(platform)
+                .keys()
+                .filter(|canon| !canon.starts_with('/'))
+                .step_by(7)
+            {
+                for target in &targets {
+                    let path = build_output_path(canon, target);
+                    assert_eq!(
+                        get_canon_name(&path).as_deref(),
+                        Some(*canon),
+                        "{}",
+                        path.display()
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            build_output_path(
+                "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin",
+                &ModTarget::Yuzu {
+                    name: "My Mod".to_owned()
+                }
+            ),
+            Path::new("load/01007EF00011F001/My Mod/romfs/Map/MainField/A-1/A-1_Dynamic.smubin")
+        );
+        assert_eq!(
+            build_output_path(
+                "/bool_data_0.bgdata",
+                &ModTarget::GraphicPack {
+                    name: "BreathOfTheWild_MyMod".to_owned()
+                }
+            ),
+            Path::new("graphicPacks/BreathOfTheWild_MyMod/content/bool_data_0.sbgdata")
+        );
+    }
+}
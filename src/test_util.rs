@@ -0,0 +1,46 @@
+//! Helpers shared by the tests which need files on disk
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// A scratch folder in the system's temporary folder, named for a test and the process running
+/// it, which is removed with everything in it when dropped, even if the test fails
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Makes an empty scratch folder, removing whatever an earlier run left behind
+    pub(crate) fn new(name: &str) -> TempDir {
+        let dir = std::env::temp_dir().join(format!("botw-utils-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    /// Writes a file at a path in the folder, making the folders above it
+    pub(crate) fn write<D: AsRef<[u8]>>(&self, file: &str, data: D) -> PathBuf {
+        let path = self.0.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, data).unwrap();
+        path
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,81 @@
+//! Contains the title IDs used by BOTW on Wii U and Switch, and the kind of content under each
+
+use crate::RootKind;
+
+/// List of known Wii U title IDs for BOTW, for the JPN (`101C9300`), USA (`101C9400`), and EUR
+/// (`101C9500`) regions
+pub const WIIU_TITLES: &[(&str, RootKind)] = &[
+    ("00050000101C9300", RootKind::Base),
+    ("00050000101C9400", RootKind::Base),
+    ("00050000101C9500", RootKind::Base),
+    ("0005000E101C9300", RootKind::Update),
+    ("0005000E101C9400", RootKind::Update),
+    ("0005000E101C9500", RootKind::Update),
+    ("0005000C101C9300", RootKind::Dlc),
+    ("0005000C101C9400", RootKind::Dlc),
+    ("0005000C101C9500", RootKind::Dlc),
+];
+
+/// List of known Switch title IDs for BOTW. Unlike on Wii U, the base game and its updates are
+/// usually installed under the same `01007EF00011E000` title ID, but dumpers like nxdumptool name
+/// the update by its own `01007EF00011E800` title ID.
+pub const SWITCH_TITLES: &[(&str, RootKind)] = &[
+    ("01007EF00011E000", RootKind::Base),
+    ("01007EF00011E001", RootKind::Update),
+    ("01007EF00011E002", RootKind::Update),
+    ("01007EF00011E800", RootKind::Update),
+    ("01007EF00011F000", RootKind::Dlc),
+    ("01007EF00011F001", RootKind::Dlc),
+    ("01007EF00011F002", RootKind::Dlc),
+];
+
+/// Finds a title ID in a list of titles, ignoring the case of the hex digits
+fn find_title(titles: &[(&str, RootKind)], title_id: &str) -> Option<RootKind> {
+    titles
+        .iter()
+        .find(|(id, _)| id.eq_ignore_ascii_case(title_id))
+        .map(|(_, kind)| *kind)
+}
+
+/// Gets the kind of content for a known Wii U BOTW title ID like `0005000E101C9400`
+pub fn wiiu_title_kind(title_id: &str) -> Option<RootKind> {
+    find_title(WIIU_TITLES, title_id)
+}
+
+/// Gets the kind of content for a Wii U BOTW title ID split into its high and low halves, like
+/// the `0005000E/101C9400` folders Cemu uses
+pub(crate) fn split_wiiu_title_kind(high: &str, low: &str) -> Option<RootKind> {
+    WIIU_TITLES
+        .iter()
+        .find(|(id, _)| id[..8].eq_ignore_ascii_case(high) && id[8..].eq_ignore_ascii_case(low))
+        .map(|(_, kind)| *kind)
+}
+
+/// Gets the kind of content for a known Switch BOTW title ID like `01007EF00011F001`
+pub fn switch_title_kind(title_id: &str) -> Option<RootKind> {
+    find_title(SWITCH_TITLES, title_id)
+}
+
+/// Gets the kind of content for a folder named by a known Switch BOTW title ID, either alone or
+/// followed by a description like the `01007EF00011E000 - The Legend of Zelda Breath of the Wild
+/// v0 (BASE)` folders nxdumptool makes. The description has to start with a space, bracket,
+/// dash, or underscore, so longer hex strings do not count.
+pub(crate) fn switch_folder_kind(name: &str) -> Option<RootKind> {
+    let title_id = name.get(..16)?;
+    match name[16..].chars().next() {
+        None | Some(' ' | '(' | '[' | '-' | '_') => switch_title_kind(title_id),
+        Some(_) => None,
+    }
+}
+
+/// Gets the kind of content for any known BOTW title ID. Example:
+///
+/// ```
+/// use botw_utils::{titles::title_kind, RootKind};
+/// assert_eq!(title_kind("0005000c101c9500"), Some(RootKind::Dlc));
+/// assert_eq!(title_kind("01007EF00011E000"), Some(RootKind::Base));
+/// assert_eq!(title_kind("0005000E101C9600"), None);
+/// ```
+pub fn title_kind(title_id: &str) -> Option<RootKind> {
+    wiiu_title_kind(title_id).or_else(|| switch_title_kind(title_id))
+}
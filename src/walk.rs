@@ -0,0 +1,461 @@
+use crate::{canon_under_root, dir_root_kind, find_content_root, CanonError, Rejection, RootKind};
+use std::fs::{self, ReadDir};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The content root files in a folder being walked belong to. Files have canonical resource
+/// paths relative to `base`, after `offset` when the walk started inside the root.
+#[derive(Debug, Clone)]
+struct Root {
+    base: PathBuf,
+    offset: PathBuf,
+    kind: RootKind,
+}
+
+/// A folder being walked, with the content root its files belong to, if any, its resolved
+/// path when symlinks are followed, and whether it is inside an ignored folder
+#[derive(Debug)]
+struct Frame {
+    dir: PathBuf,
+    entries: ReadDir,
+    root: Option<Root>,
+    real: Option<PathBuf>,
+    ignored: bool,
+}
+
+/// An iterator over the files in a folder and their canonical resource paths, made by
+/// [`canonicalize_dir`]
+#[derive(Debug)]
+pub struct CanonDir {
+    start: Option<PathBuf>,
+    stack: Vec<Frame>,
+    follow_links: bool,
+    skip_errors: bool,
+    use_default_ignores: bool,
+    ignores: Vec<String>,
+    ignored: Vec<PathBuf>,
+    root_kind: Option<RootKind>,
+}
+
+/// The glob patterns of the files [`canonicalize_dir`] ignores by default, which are left in
+/// mods by operating systems, file managers, and version control rather than being part of the
+/// mod: Windows thumbnail caches and folder settings, macOS Finder data and the resource forks
+/// zips made on macOS keep in `__MACOSX` folders, and the placeholder files which keep empty
+/// folders in git. Patterns ending with `/` are for folders, whose whole contents are ignored,
+/// and the others for files. The patterns are matched against the name of each file or folder,
+/// ignoring case, and `*` matches any run of characters and `?` any one character.
+pub fn default_ignores() -> &'static [&'static str] {
+    &[
+        "Thumbs.db",
+        "desktop.ini",
+        ".DS_Store",
+        "._*",
+        "__MACOSX/",
+        ".gitkeep",
+    ]
+}
+
+/// Checks whether a file or folder name matches a glob pattern, ignoring ASCII case
+fn glob_matches(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| glob_matches(rest, &name[skip..])),
+        Some((first, rest)) => match name.split_first() {
+            Some((c, name)) if *first == b'?' || first.eq_ignore_ascii_case(c) => {
+                glob_matches(rest, name)
+            }
+            _ => false,
+        },
+    }
+}
+
+/// Walks a mod folder or game dump, giving the path and [canonical resource
+/// path](https://zeldamods.org/wiki/Canonical_resource_path) of each file in it. The content root
+/// of the folder is found once when the walk starts, like with
+/// [`find_content_root`](crate::find_content_root), and after that only folders which are roots
+/// themselves, like a `content` or `aoc` folder inside a mod, are checked, so files are not
+/// matched against every root layout one by one. By default symlinks are not followed, and files
+/// which do not have canonical resource paths, like a graphic pack's `rules.txt`, are given as
+/// errors. Example:
+///
+/// ```no_run
+/// use botw_utils::canonicalize_dir;
+/// for (path, canon) in canonicalize_dir("mods/My Mod").skip_errors(true).flatten() {
+///     println!("{} is {}", path.display(), canon);
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `root` - The folder to walk
+///
+/// # Returns
+///
+/// Returns a [`CanonDir`] iterator over the files in the folder. Folders which cannot be read are
+/// given as [`CanonError::Io`] errors, even when other errors are skipped. Junk files matching
+/// [`default_ignores`] are left out, and can be found with [`CanonDir::ignored`].
+pub fn canonicalize_dir<P: AsRef<Path>>(root: P) -> CanonDir {
+    CanonDir {
+        start: Some(root.as_ref().to_path_buf()),
+        stack: Vec::new(),
+        follow_links: false,
+        skip_errors: false,
+        use_default_ignores: true,
+        ignores: Vec::new(),
+        ignored: Vec::new(),
+        root_kind: None,
+    }
+}
+
+impl CanonDir {
+    /// Sets whether symlinks are followed. Symlinks which lead back to a folder the walk is already
+    /// inside are skipped, so loops do not go on forever.
+    pub fn follow_links(mut self, follow_links: bool) -> CanonDir {
+        self.follow_links = follow_links;
+        self
+    }
+
+    /// Sets whether files which do not have canonical resource paths are skipped instead of being
+    /// given as errors
+    pub fn skip_errors(mut self, skip_errors: bool) -> CanonDir {
+        self.skip_errors = skip_errors;
+        self
+    }
+
+    /// Sets whether the files matching [`default_ignores`] are left out of the walk, which they
+    /// are by default. Patterns added with [`ignore`](CanonDir::ignore) are used either way.
+    pub fn use_default_ignores(mut self, use_defaults: bool) -> CanonDir {
+        self.use_default_ignores = use_defaults;
+        self
+    }
+
+    /// Adds a glob pattern of files to leave out of the walk, in the form of the
+    /// [`default_ignores`], like `*.bak` for files or `.git/` for folders
+    pub fn ignore<S: Into<String>>(mut self, pattern: S) -> CanonDir {
+        self.ignores.push(pattern.into());
+        self
+    }
+
+    /// Gets the files which have been left out of the walk so far because they match an ignore
+    /// pattern, including the files inside ignored folders
+    pub fn ignored(&self) -> &[PathBuf] {
+        &self.ignored
+    }
+
+    /// Sets the folder the walk starts at as a content root of a kind, for callers which were
+    /// told what a folder holds instead of finding it from the folder names
+    #[cfg(feature = "sarc")]
+    pub(crate) fn root_kind(mut self, kind: RootKind) -> CanonDir {
+        self.root_kind = Some(kind);
+        self
+    }
+
+    /// Checks whether a file or folder in the walk matches an ignore pattern
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name.as_bytes(),
+            None => return false,
+        };
+        let defaults: &[&str] = if self.use_default_ignores {
+            default_ignores()
+        } else {
+            &[]
+        };
+        defaults
+            .iter()
+            .copied()
+            .chain(self.ignores.iter().map(String::as_str))
+            .any(|pattern| match pattern.strip_suffix('/') {
+                Some(folder) => is_dir && glob_matches(folder.as_bytes(), name),
+                None => !is_dir && glob_matches(pattern.as_bytes(), name),
+            })
+    }
+
+    /// Opens the folder the walk starts at and finds the content root it belongs to, unless it
+    /// was given one
+    fn open(&mut self, dir: PathBuf) -> Result<(), CanonError> {
+        let entries = fs::read_dir(&dir).map_err(|e| io_error(&dir, &e))?;
+        if let Some(kind) = self.root_kind {
+            let root = Root {
+                base: dir.clone(),
+                offset: PathBuf::new(),
+                kind,
+            };
+            self.push(dir, entries, Some(root), false);
+            return Ok(());
+        }
+        let root = find_content_root(&dir).map(|found| {
+            let full = fs::canonicalize(&dir).unwrap_or_else(|_| dir.clone());
+            Root {
+                base: dir.clone(),
+                offset: full
+                    .strip_prefix(&found.path)
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default(),
+                kind: found.kind,
+            }
+        });
+        self.push(dir, entries, root, false);
+        Ok(())
+    }
+
+    /// Starts walking a folder, unless following symlinks has led back to a folder the walk is
+    /// already inside
+    fn push(&mut self, dir: PathBuf, entries: ReadDir, root: Option<Root>, ignored: bool) {
+        let real = if self.follow_links {
+            let real = fs::canonicalize(&dir).unwrap_or_else(|_| dir.clone());
+            if self
+                .stack
+                .iter()
+                .any(|frame| frame.real.as_ref() == Some(&real))
+            {
+                return;
+            }
+            Some(real)
+        } else {
+            None
+        };
+        self.stack.push(Frame {
+            dir,
+            entries,
+            root,
+            real,
+            ignored,
+        });
+    }
+}
+
+impl Iterator for CanonDir {
+    type Item = Result<(PathBuf, String), CanonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(start) = self.start.take() {
+            if let Err(e) = self.open(start) {
+                return Some(Err(e));
+            }
+        }
+        loop {
+            let frame = self.stack.last_mut()?;
+            let entry = match frame.entries.next() {
+                Some(Ok(entry)) => entry,
+                Some(Err(e)) => return Some(Err(io_error(&frame.dir, &e))),
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            let path = entry.path();
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(e) => return Some(Err(io_error(&path, &e))),
+            };
+            let is_dir = if file_type.is_symlink() {
+                if !self.follow_links {
+                    continue;
+                }
+                match fs::metadata(&path) {
+                    Ok(meta) => meta.is_dir(),
+                    Err(e) => return Some(Err(io_error(&path, &e))),
+                }
+            } else {
+                file_type.is_dir()
+            };
+            let ignored = frame.ignored || self.is_ignored(&path, is_dir);
+            if is_dir {
+                let frame = self.stack.last().unwrap();
+                let root = match dir_root_kind(&path) {
+                    Some(kind) => Some(Root {
+                        base: path.clone(),
+                        offset: PathBuf::new(),
+                        kind,
+                    }),
+                    None => frame.root.clone(),
+                };
+                match fs::read_dir(&path) {
+                    Ok(entries) => self.push(path, entries, root, ignored),
+                    Err(e) => return Some(Err(io_error(&path, &e))),
+                }
+                continue;
+            }
+            if ignored {
+                self.ignored.push(path);
+                continue;
+            }
+            let frame = self.stack.last().unwrap();
+            match canon_file(frame.root.as_ref(), &path) {
+                Ok(canon) => return Some(Ok((path, canon))),
+                Err(_) if self.skip_errors => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Finds the canonical resource path of a file under the content root of its folder
+fn canon_file(root: Option<&Root>, path: &Path) -> Result<String, CanonError> {
+    let lossy = || path.to_string_lossy();
+    let root = root.ok_or_else(|| Rejection::UnrecognizedRoot.into_error(&lossy()))?;
+    let rest = root
+        .offset
+        .join(path.strip_prefix(&root.base).unwrap_or(path));
+    let rest = rest
+        .to_str()
+        .ok_or_else(|| CanonError::NonUtf8Path(path.to_path_buf()))?;
+    canon_under_root(rest, root.kind).map_err(|rejection| rejection.into_error(&lossy()))
+}
+
+/// Gets the path of the file an error is about
+pub(crate) fn error_path(error: &CanonError) -> PathBuf {
+    match error {
+        CanonError::NonUtf8Path(path)
+        | CanonError::Io { path, .. }
+        | CanonError::InvalidYaz0 { path, .. } => path.clone(),
+        CanonError::UnrecognizedRoot { path, .. }
+        | CanonError::NotUnderContentRoot { path }
+        | CanonError::InvalidCanonPath { path, .. }
+        | CanonError::NotRomfs { path, .. }
+        | CanonError::InvalidEncoding { path }
+        | CanonError::IsDirectory { path } => PathBuf::from(path),
+    }
+}
+
+/// Keeps the kind of an I/O error with the path it happened on
+pub(crate) fn io_error(path: &Path, error: &io::Error) -> CanonError {
+    CanonError::Io {
+        path: path.to_path_buf(),
+        kind: error.kind(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    /// Makes a scratch mod folder with a few representative files in it
+    fn mod_folder(name: &str) -> TempDir {
+        let dir = TempDir::new(name);
+        for file in &[
+            "rules.txt",
+            "content/Actor/Pack/Enemy_Lizal_Senior.sbactorpack",
+            "content/Pack/Bootup.pack",
+            "aoc/0010/Map/MainField/A-1/A-1_Dynamic.smubin",
+            "01007EF00011F001/romfs/Pack/AocMainField.pack",
+        ] {
+            dir.write(file, b"");
+        }
+        dir
+    }
+
+    fn canon_names(walk: CanonDir) -> Vec<String> {
+        let mut names: Vec<String> = walk.map(|result| result.unwrap().1).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn walk_mod_folder() {
+        let dir = mod_folder("walk");
+        assert_eq!(
+            canon_names(canonicalize_dir(&dir).skip_errors(true)),
+            [
+                "Actor/Pack/Enemy_Lizal_Senior.bactorpack",
+                "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin",
+                "Aoc/0010/Pack/AocMainField.pack",
+                "Pack/Bootup.pack",
+            ]
+        );
+        let errors: Vec<_> = canonicalize_dir(&dir).filter_map(Result::err).collect();
+        assert!(matches!(
+            errors.as_slice(),
+            [CanonError::UnrecognizedRoot { path, .. }] if path.ends_with("rules.txt")
+        ));
+        assert_eq!(
+            canon_names(canonicalize_dir(dir.join("content/Actor"))),
+            ["Actor/Pack/Enemy_Lizal_Senior.bactorpack"]
+        );
+        assert!(matches!(
+            canonicalize_dir(dir.join("missing")).next(),
+            Some(Err(CanonError::Io {
+                kind: io::ErrorKind::NotFound,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn ignored_files() {
+        let dir = mod_folder("ignores");
+        for file in &[
+            "content/Pack/Thumbs.db",
+            "content/Pack/._Bootup.pack",
+            "content/Pack/bootup.pack.bak",
+            "content/Actor/.gitkeep",
+            "__MACOSX/content/Pack/._Bootup.pack",
+            "__MACOSX/content/Actor/Pack/._Enemy_Lizal_Senior.sbactorpack",
+            ".DS_Store",
+        ] {
+            dir.write(file, b"");
+        }
+        let mut walk = canonicalize_dir(&dir).skip_errors(true).ignore("*.BAK");
+        let mut names: Vec<String> = walk.by_ref().map(|result| result.unwrap().1).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "Actor/Pack/Enemy_Lizal_Senior.bactorpack",
+                "Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin",
+                "Aoc/0010/Pack/AocMainField.pack",
+                "Pack/Bootup.pack",
+            ]
+        );
+        let mut ignored = walk.ignored().to_vec();
+        ignored.sort();
+        assert_eq!(
+            ignored,
+            [
+                ".DS_Store",
+                "__MACOSX/content/Actor/Pack/._Enemy_Lizal_Senior.sbactorpack",
+                "__MACOSX/content/Pack/._Bootup.pack",
+                "content/Actor/.gitkeep",
+                "content/Pack/._Bootup.pack",
+                "content/Pack/Thumbs.db",
+                "content/Pack/bootup.pack.bak",
+            ]
+            .iter()
+            .map(|file| dir.join(file))
+            .collect::<Vec<_>>()
+        );
+        let all: Vec<_> = canonicalize_dir(&dir)
+            .use_default_ignores(false)
+            .skip_errors(true)
+            .collect();
+        assert_eq!(all.len(), 10);
+        assert!(glob_matches(b"Thumbs.db", b"THUMBS.DB"));
+        assert!(glob_matches(b"*.b?k", b"a.bak") && !glob_matches(b"*.b?k", b"a.bk"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn walk_symlinks() {
+        let dir = mod_folder("symlinks");
+        std::os::unix::fs::symlink(dir.join("content/Pack"), dir.join("content/Linked")).unwrap();
+        std::os::unix::fs::symlink(dir.join("content"), dir.join("content/Loop")).unwrap();
+        let content = dir.join("content");
+        assert_eq!(
+            canon_names(canonicalize_dir(&content)),
+            [
+                "Actor/Pack/Enemy_Lizal_Senior.bactorpack",
+                "Pack/Bootup.pack"
+            ]
+        );
+        assert_eq!(
+            canon_names(canonicalize_dir(&content).follow_links(true)),
+            [
+                "Actor/Pack/Enemy_Lizal_Senior.bactorpack",
+                "Linked/Bootup.pack",
+                "Pack/Bootup.pack",
+            ]
+        );
+    }
+}
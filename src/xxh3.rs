@@ -0,0 +1,215 @@
+//! The 64-bit XXH3 hash with a seed of 0 and the default secret, as specified by xxHash 0.8, the
+//! first release with stable XXH3 output. The XXH3 in `twox-hash` 1.6 predates it and gives
+//! different hashes, so it cannot check tables made by other tools.
+
+const PRIME32_1: u64 = 0x9E37_79B1;
+const PRIME32_2: u64 = 0x85EB_CA77;
+const PRIME32_3: u64 = 0xC2B2_AE3D;
+const PRIME64_1: u64 = 0x9E37_79B1_85EB_CA87;
+const PRIME64_2: u64 = 0xC2B2_AE3D_27D4_EB4F;
+const PRIME64_3: u64 = 0x1656_67B1_9E37_79F9;
+const PRIME64_4: u64 = 0x85EB_CA77_C2B2_AE63;
+const PRIME64_5: u64 = 0x27D4_EB2F_1656_67C5;
+const PRIME_MX1: u64 = 0x1656_6791_9E37_79F9;
+const PRIME_MX2: u64 = 0x9FB2_1C65_1E98_DF25;
+
+/// The default secret every XXH3 hash without a custom secret is keyed with
+const SECRET: [u8; 192] = [
+    0xb8, 0xfe, 0x6c, 0x39, 0x23, 0xa4, 0x4b, 0xbe, 0x7c, 0x01, 0x81, 0x2c, 0xf7, 0x21, 0xad, 0x1c,
+    0xde, 0xd4, 0x6d, 0xe9, 0x83, 0x90, 0x97, 0xdb, 0x72, 0x40, 0xa4, 0xa4, 0xb7, 0xb3, 0x67, 0x1f,
+    0xcb, 0x79, 0xe6, 0x4e, 0xcc, 0xc0, 0xe5, 0x78, 0x82, 0x5a, 0xd0, 0x7d, 0xcc, 0xff, 0x72, 0x21,
+    0xb8, 0x08, 0x46, 0x74, 0xf7, 0x43, 0x24, 0x8e, 0xe0, 0x35, 0x90, 0xe6, 0x81, 0x3a, 0x26, 0x4c,
+    0x3c, 0x28, 0x52, 0xbb, 0x91, 0xc3, 0x00, 0xcb, 0x88, 0xd0, 0x65, 0x8b, 0x1b, 0x53, 0x2e, 0xa3,
+    0x71, 0x64, 0x48, 0x97, 0xa2, 0x0d, 0xf9, 0x4e, 0x38, 0x19, 0xef, 0x46, 0xa9, 0xde, 0xac, 0xd8,
+    0xa8, 0xfa, 0x76, 0x3f, 0xe3, 0x9c, 0x34, 0x3f, 0xf9, 0xdc, 0xbb, 0xc7, 0xc7, 0x0b, 0x4f, 0x1d,
+    0x8a, 0x51, 0xe0, 0x4b, 0xcd, 0xb4, 0x59, 0x31, 0xc8, 0x9f, 0x7e, 0xc9, 0xd9, 0x78, 0x73, 0x64,
+    0xea, 0xc5, 0xac, 0x83, 0x34, 0xd3, 0xeb, 0xc3, 0xc5, 0x81, 0xa0, 0xff, 0xfa, 0x13, 0x63, 0xeb,
+    0x17, 0x0d, 0xdd, 0x51, 0xb7, 0xf0, 0xda, 0x49, 0xd3, 0x16, 0x55, 0x26, 0x29, 0xd4, 0x68, 0x9e,
+    0x2b, 0x16, 0xbe, 0x58, 0x7d, 0x47, 0xa1, 0xfc, 0x8f, 0xf8, 0xb8, 0xd1, 0x7a, 0xd0, 0x31, 0xce,
+    0x45, 0xcb, 0x3a, 0x8f, 0x95, 0x16, 0x04, 0x28, 0xaf, 0xd7, 0xfb, 0xca, 0xbb, 0x4b, 0x40, 0x7e,
+];
+
+/// The bytes of a stripe, the block of input one round of the long hash reads
+const STRIPE_LEN: usize = 64;
+/// The stripes in each block of the long hash, between which the accumulators are scrambled
+const STRIPES_PER_BLOCK: usize = (SECRET.len() - STRIPE_LEN) / 8;
+
+fn read32(data: &[u8], at: usize) -> u64 {
+    u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]) as u64
+}
+
+fn read64(data: &[u8], at: usize) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&data[at..at + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+/// Multiplies two u64s into a u128 and folds its halves together
+fn mul_fold(a: u64, b: u64) -> u64 {
+    let product = a as u128 * b as u128;
+    product as u64 ^ (product >> 64) as u64
+}
+
+fn xxh64_avalanche(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(PRIME64_2);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(PRIME64_3);
+    hash ^ (hash >> 32)
+}
+
+fn avalanche(mut hash: u64) -> u64 {
+    hash ^= hash >> 37;
+    hash = hash.wrapping_mul(PRIME_MX1);
+    hash ^ (hash >> 32)
+}
+
+fn rrmxmx(mut hash: u64, len: usize) -> u64 {
+    hash ^= hash.rotate_left(49) ^ hash.rotate_left(24);
+    hash = hash.wrapping_mul(PRIME_MX2);
+    hash ^= (hash >> 35).wrapping_add(len as u64);
+    hash = hash.wrapping_mul(PRIME_MX2);
+    hash ^ (hash >> 28)
+}
+
+/// Mixes 16 bytes of input at `at` with 16 bytes of the secret at `secret_at`
+fn mix16(data: &[u8], at: usize, secret_at: usize) -> u64 {
+    mul_fold(
+        read64(data, at) ^ read64(&SECRET, secret_at),
+        read64(data, at + 8) ^ read64(&SECRET, secret_at + 8),
+    )
+}
+
+fn hash_short(data: &[u8]) -> u64 {
+    let len = data.len();
+    match len {
+        0 => xxh64_avalanche(read64(&SECRET, 56) ^ read64(&SECRET, 64)),
+        1..=3 => {
+            let combined = ((data[0] as u32) << 16)
+                | ((data[len >> 1] as u32) << 24)
+                | data[len - 1] as u32
+                | ((len as u32) << 8);
+            xxh64_avalanche(combined as u64 ^ (read32(&SECRET, 0) ^ read32(&SECRET, 4)))
+        }
+        4..=8 => {
+            let input = read32(data, len - 4).wrapping_add(read32(data, 0) << 32);
+            rrmxmx(input ^ (read64(&SECRET, 8) ^ read64(&SECRET, 16)), len)
+        }
+        _ => {
+            let low = read64(data, 0) ^ (read64(&SECRET, 24) ^ read64(&SECRET, 32));
+            let high = read64(data, len - 8) ^ (read64(&SECRET, 40) ^ read64(&SECRET, 48));
+            avalanche(
+                (len as u64)
+                    .wrapping_add(low.swap_bytes())
+                    .wrapping_add(high)
+                    .wrapping_add(mul_fold(low, high)),
+            )
+        }
+    }
+}
+
+fn hash_medium(data: &[u8]) -> u64 {
+    let len = data.len();
+    let mut acc = (len as u64).wrapping_mul(PRIME64_1);
+    if len <= 128 {
+        let pairs = (len - 1) / 32;
+        for i in (0..=pairs).rev() {
+            acc = acc
+                .wrapping_add(mix16(data, 16 * i, 32 * i))
+                .wrapping_add(mix16(data, len - 16 * (i + 1), 32 * i + 16));
+        }
+        return avalanche(acc);
+    }
+    for i in 0..8 {
+        acc = acc.wrapping_add(mix16(data, 16 * i, 16 * i));
+    }
+    acc = avalanche(acc);
+    for i in 8..len / 16 {
+        acc = acc.wrapping_add(mix16(data, 16 * i, 16 * (i - 8) + 3));
+    }
+    avalanche(acc.wrapping_add(mix16(data, len - 16, 136 - 17)))
+}
+
+fn accumulate_stripe(acc: &mut [u64; 8], stripe: &[u8], secret_at: usize) {
+    for i in 0..8 {
+        let value = read64(stripe, 8 * i);
+        let key = value ^ read64(&SECRET, secret_at + 8 * i);
+        acc[i ^ 1] = acc[i ^ 1].wrapping_add(value);
+        acc[i] = acc[i].wrapping_add((key & 0xFFFF_FFFF).wrapping_mul(key >> 32));
+    }
+}
+
+fn hash_long(data: &[u8]) -> u64 {
+    let len = data.len();
+    let mut acc = [
+        PRIME32_3, PRIME64_1, PRIME64_2, PRIME64_3, PRIME64_4, PRIME32_2, PRIME64_5, PRIME32_1,
+    ];
+    let block_len = STRIPE_LEN * STRIPES_PER_BLOCK;
+    let blocks = (len - 1) / block_len;
+    for block in 0..blocks {
+        for stripe in 0..STRIPES_PER_BLOCK {
+            let at = block * block_len + stripe * STRIPE_LEN;
+            accumulate_stripe(&mut acc, &data[at..], stripe * 8);
+        }
+        for (i, value) in acc.iter_mut().enumerate() {
+            *value ^= *value >> 47;
+            *value ^= read64(&SECRET, SECRET.len() - STRIPE_LEN + 8 * i);
+            *value = value.wrapping_mul(PRIME32_1);
+        }
+    }
+    let stripes = ((len - 1) - block_len * blocks) / STRIPE_LEN;
+    for stripe in 0..stripes {
+        let at = blocks * block_len + stripe * STRIPE_LEN;
+        accumulate_stripe(&mut acc, &data[at..], stripe * 8);
+    }
+    accumulate_stripe(
+        &mut acc,
+        &data[len - STRIPE_LEN..],
+        SECRET.len() - STRIPE_LEN - 7,
+    );
+    let mut result = (len as u64).wrapping_mul(PRIME64_1);
+    for i in 0..4 {
+        result = result.wrapping_add(mul_fold(
+            acc[2 * i] ^ read64(&SECRET, 11 + 16 * i),
+            acc[2 * i + 1] ^ read64(&SECRET, 11 + 16 * i + 8),
+        ));
+    }
+    avalanche(result)
+}
+
+/// Gets the 64-bit XXH3 hash of some data with a seed of 0
+pub(crate) fn hash64(data: &[u8]) -> u64 {
+    match data.len() {
+        0..=16 => hash_short(data),
+        17..=240 => hash_medium(data),
+        _ => hash_long(data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xxh3_hashes() {
+        let data: Vec<u8> = (0..5000u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        // Checked against the XXH3_64bits of libxxhash 0.8
+        for &(len, hash) in &[
+            (0, 0x2D06_8005_38D3_94C2),
+            (3, 0xE140_90F5_54A5_EA90),
+            (8, 0xCD1C_7F88_482F_CAEF),
+            (16, 0x81E9_EB86_3446_0BB9),
+            (17, 0x9998_430F_D0A6_55BE),
+            (128, 0x75EC_A5C5_D559_4884),
+            (129, 0xA05D_A42E_7A4E_4667),
+            (240, 0x5EB2_467C_8C9E_3969),
+            (241, 0x2D43_1E98_4C44_1F15),
+            (1024, 0xE99D_EF11_45F1_2936),
+            (5000, 0xB9DA_EDE5_F99F_736E),
+        ] {
+            assert_eq!(hash64(&data[..len]), hash, "length {}", len);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Counts heap allocations made by canonicalization, which needs a global allocator and so
+//! cannot live with the unit tests.
+use botw_utils::{get_canon_name, get_canon_name_cow};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::borrow::Cow;
+use std::cell::Cell;
+
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+const PATHS: &[&str] = &[
+    "content/Pack/Bootup.pack",
+    "atmosphere/contents/01007EF00011E000/romfs/Actor/ActorInfo.product.byml",
+    "aoc/0010/Map/MainField/A-1/A-1_Dynamic.smubin",
+    "content\\Actor\\ActorInfo.product.sbyml",
+];
+
+#[test]
+fn canon_name_allocations() {
+    // Run every input once first, so one-time setup in the standard library, like the first
+    // use of the thread's allocation counter, is not counted against the calls below
+    for path in PATHS {
+        get_canon_name(path);
+        get_canon_name_cow(path);
+    }
+
+    let (canon, count) = count_allocations(|| get_canon_name_cow("content/Pack/Bootup.pack"));
+    assert!(matches!(canon, Some(Cow::Borrowed("Pack/Bootup.pack"))));
+    assert_eq!(count, 0);
+
+    let (canon, count) = count_allocations(|| {
+        get_canon_name_cow(
+            "atmosphere/contents/01007EF00011E000/romfs/Actor/ActorInfo.product.byml",
+        )
+    });
+    assert_eq!(canon.as_deref(), Some("Actor/ActorInfo.product.byml"));
+    assert_eq!(count, 0);
+
+    let (canon, count) =
+        count_allocations(|| get_canon_name_cow("aoc/0010/Map/MainField/A-1/A-1_Dynamic.smubin"));
+    assert_eq!(
+        canon.as_deref(),
+        Some("Aoc/0010/Map/MainField/A-1/A-1_Dynamic.mubin")
+    );
+    assert_eq!(count, 1);
+
+    let (canon, count) =
+        count_allocations(|| get_canon_name_cow("content\\Actor\\ActorInfo.product.sbyml"));
+    assert_eq!(canon.as_deref(), Some("Actor/ActorInfo.product.byml"));
+    assert_eq!(count, 1);
+
+    let (canon, count) = count_allocations(|| get_canon_name("content/Pack/Bootup.pack"));
+    assert_eq!(canon.as_deref(), Some("Pack/Bootup.pack"));
+    assert_eq!(count, 1);
+}
@@ -0,0 +1,47 @@
+//! Checks loading hash tables from files given by the environment, which has to happen in its
+//! own process so that the other tests see the embedded tables.
+use botw_utils::hashes::{Platform, StockHashTable, TableError};
+use std::env;
+use std::panic;
+
+#[path = "../src/test_util.rs"]
+mod test_util;
+use test_util::TempDir;
+
+#[test]
+fn hash_table_overrides() {
+    let dir = TempDir::new("overrides");
+    let from_env = dir.write("env.json", r#"{"Pack/Bootup.pack": [1]}"#);
+    let explicit = dir.write("explicit.json", r#"{"Pack/Bootup.pack": [2]}"#);
+    let malformed = dir.write("malformed.json", r#"{"Pack/Bootup.pack": [1"#);
+    let embedded = StockHashTable::new(Platform::Switch);
+    assert!(embedded.len() > 1);
+
+    env::set_var("BOTW_UTILS_HASHES_NX", &from_env);
+    let table = StockHashTable::new(Platform::Switch);
+    assert_eq!(table.len(), 1);
+    assert_eq!(table.get_hashes("Pack/Bootup.pack"), Some(&[1][..]));
+    assert_eq!(table.platform(), Some(Platform::Switch));
+    let table = StockHashTable::new_with_source(Platform::Switch, Some(&explicit)).unwrap();
+    assert_eq!(table.get_hashes("Pack/Bootup.pack"), Some(&[2][..]));
+    assert!(StockHashTable::new(Platform::WiiU).len() > 1);
+
+    env::set_var("BOTW_UTILS_HASHES_NX", &malformed);
+    assert!(matches!(
+        StockHashTable::new_with_source(Platform::Switch, None),
+        Err(TableError::InvalidJson(_))
+    ));
+    let message = panic::catch_unwind(|| StockHashTable::new(Platform::Switch))
+        .unwrap_err()
+        .downcast::<String>()
+        .unwrap();
+    assert!(message.contains("BOTW_UTILS_HASHES_NX"), "{}", message);
+    assert!(message.contains("malformed.json"), "{}", message);
+    assert_eq!(
+        StockHashTable::new_with_source(Platform::Switch, Some(&dir.join("missing.json"))),
+        Err(TableError::Io(std::io::ErrorKind::NotFound))
+    );
+
+    env::remove_var("BOTW_UTILS_HASHES_NX");
+    assert_eq!(StockHashTable::new(Platform::Switch), embedded);
+}